@@ -0,0 +1,134 @@
+//! Wall-clock comparisons of search settings, run with `cargo bench`.
+//!
+//! Each case solves the same seeded instance both ways, checks that the
+//! two agree where they should, and prints the time each took.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use ndarray::Array3;
+use time_scheduler::{Cell, Schedule};
+
+/// A random cost for each activity in each slot, and another for leaving
+/// it unscheduled.
+struct Costs {
+    slot: Array3<i64>,
+    unscheduled: Vec<i64>,
+}
+
+impl Costs {
+    fn random(nplaces: usize, ntimes: usize, nactivities: usize, seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        Costs {
+            slot: Array3::from_shape_fn((nplaces, ntimes, nactivities), |_| rng.i64(-20..100)),
+            unscheduled: (0..nactivities).map(|_| rng.i64(50..200)).collect(),
+        }
+    }
+
+    fn penalty(&self, schedule: &Schedule<usize>) -> i64 {
+        let placed: i64 = schedule
+            .slots()
+            .indexed_iter()
+            .filter_map(|((p, t), a)| a.map(|a| self.slot[[p, t, a]]))
+            .sum();
+        let dropped: i64 = schedule
+            .get_unscheduled_activities()
+            .map(|&a| self.unscheduled[a])
+            .sum();
+        placed + dropped
+    }
+
+    /// What `activity` costs where it sits, or an empty cell costs nothing.
+    fn cost_at(&self, cell: Cell, activity: Option<&usize>) -> i64 {
+        match (cell, activity) {
+            (_, None) => 0,
+            (Cell::Slot(p, t), Some(&a)) => self.slot[[p, t, a]],
+            (Cell::Unscheduled(_), Some(&a)) => self.unscheduled[a],
+        }
+    }
+
+    fn delta(&self, schedule: &Schedule<usize>, a: Cell, b: Cell) -> i64 {
+        let in_a = schedule.activity_in(a).unwrap();
+        let in_b = schedule.activity_in(b).unwrap();
+        self.cost_at(a, in_b) + self.cost_at(b, in_a)
+            - self.cost_at(a, in_a)
+            - self.cost_at(b, in_b)
+    }
+}
+
+/// Time one solve of `start`, returning the schedule it ended on.
+fn time(
+    start: &Schedule<usize>,
+    solve: impl FnOnce(&mut Schedule<usize>),
+) -> (Duration, Schedule<usize>) {
+    let mut schedule = start.clone();
+    let began = Instant::now();
+    solve(black_box(&mut schedule));
+    (began.elapsed(), schedule)
+}
+
+fn report(case: &str, baseline: (&str, Duration), other: (&str, Duration)) {
+    let speedup = baseline.1.as_secs_f64() / other.1.as_secs_f64();
+    println!(
+        "{case}: {} {:.3?}, {} {:.3?}, speedup {speedup:.2}x",
+        baseline.0, baseline.1, other.0, other.1
+    );
+}
+
+/// Eight restarts one after another, then spread across threads.
+fn parallel_restarts() {
+    let (nplaces, ntimes, nactivities) = (10, 8, 90);
+    let costs = Costs::random(nplaces, ntimes, nactivities, 1);
+    let start = Schedule::new(nplaces, ntimes, 0..nactivities);
+    let penalty = |s: &Schedule<usize>| costs.penalty(s);
+
+    let (sequential, in_order) = time(&start, |s| {
+        s.improve(penalty)
+            .with_noise()
+            .restarts(8)
+            .with_rng_seed(2)
+            .run()
+    });
+    let (parallel, spread) = time(&start, |s| {
+        s.improve(penalty)
+            .with_noise()
+            .restarts(8)
+            .with_rng_seed(2)
+            .run_parallel()
+    });
+    assert_eq!(in_order, spread);
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    report(
+        &format!("8 restarts, {nplaces}x{ntimes}, {threads} threads"),
+        ("run", sequential),
+        ("run_parallel", parallel),
+    );
+}
+
+/// Greedy swaps scored by evaluating the whole schedule, then by deltas.
+fn penalty_deltas() {
+    let (nplaces, ntimes, nactivities) = (20, 10, 220);
+    let costs = Costs::random(nplaces, ntimes, nactivities, 3);
+    let start = Schedule::new(nplaces, ntimes, 0..nactivities);
+    let penalty = |s: &Schedule<usize>| costs.penalty(s);
+    let nswaps = 20;
+
+    let (full, evaluated) = time(&start, |s| s.improve(penalty).max_swaps(nswaps).run());
+    let (local, by_delta) = time(&start, |s| {
+        s.improve(penalty)
+            .penalty_delta(|s, a, b| costs.delta(s, a, b))
+            .max_swaps(nswaps)
+            .run()
+    });
+    assert_eq!(evaluated, by_delta);
+    report(
+        &format!("{nswaps} greedy swaps, {nplaces}x{ntimes}"),
+        ("full penalty", full),
+        ("penalty_delta", local),
+    );
+}
+
+fn main() {
+    parallel_restarts();
+    penalty_deltas();
+}
@@ -1,19 +1,79 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
 use std::fs;
-use std::time::Duration;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
 
-use clap::Parser;
-use ndarray::Axis;
+use clap::{Parser, ValueEnum};
+use ndarray::{ArrayView1, Axis};
 use ordered_float::NotNan;
 use serde::{Deserialize, Serialize};
-use time_scheduler::{Schedule, SchedulingInstance};
+use time_scheduler::{
+    export::{to_ascii_grid, write_csv},
+    Acceptance, Improver, Infeasible, Profile, Schedule, SchedulingInstance,
+};
 
 #[derive(Parser)]
 struct Args {
     #[arg(short = 's', long = "nswaps", help = "Number of swaps per restart")]
     nswaps: Option<usize>,
-    #[arg(short = 'n', long = "noise", help = "Use noise moves")]
+    #[arg(
+        long = "algorithm",
+        value_enum,
+        default_value_t = Algorithm::Hill,
+        help = "Local search algorithm"
+    )]
+    algorithm: Algorithm,
+    #[arg(
+        short = 'n',
+        long = "noise",
+        conflicts_with = "algorithm",
+        help = "Use noise moves (shorthand for --algorithm noise)"
+    )]
     noise: bool,
+    #[arg(
+        long = "noise-prob",
+        default_value = "0.5",
+        value_parser = parse_probability,
+        help = "Chance of a random move at each swap with --algorithm noise"
+    )]
+    noise_prob: f32,
+    #[arg(
+        long = "tabu-tenure",
+        default_value_t = 7,
+        help = "Greedy steps a swapped pair stays tabu with --algorithm tabu"
+    )]
+    tabu_tenure: usize,
+    #[arg(
+        long = "init-temp",
+        default_value_t = 10.0,
+        value_parser = parse_positive,
+        help = "Starting temperature with --algorithm annealing"
+    )]
+    init_temp: f64,
+    #[arg(
+        long = "cooling",
+        default_value_t = 0.999,
+        value_parser = parse_cooling,
+        help = "Factor the temperature is multiplied by after each swap with --algorithm annealing"
+    )]
+    cooling: f64,
+    #[arg(
+        long = "rain",
+        default_value_t = 0.01,
+        value_parser = parse_non_negative,
+        help = "Fall in the water level after each swap with --algorithm great-deluge"
+    )]
+    rain: f64,
+    #[arg(
+        long = "history",
+        default_value_t = 50,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Swaps back to compare with under --algorithm lahc"
+    )]
+    history: u64,
     #[arg(
         short = 'r',
         long = "nrestarts",
@@ -28,19 +88,470 @@ struct Args {
     proportional: bool,
     #[arg(short = 't', long = "timeout", help = "Runtime timeout in seconds")]
     timeout: Option<u64>,
+    #[arg(
+        long = "total-timeout",
+        value_name = "SECS",
+        conflicts_with = "timeout",
+        help = "Wall-clock budget in seconds for all instances together, split by --allocate"
+    )]
+    total_timeout: Option<u64>,
+    #[arg(
+        long = "allocate",
+        value_enum,
+        default_value_t = Allocation::Even,
+        requires = "total_timeout",
+        help = "How --total-timeout is split across instances"
+    )]
+    allocate: Allocation,
+    #[arg(long = "seed", help = "Random seed for reproducible runs")]
+    seed: Option<u64>,
+    #[arg(
+        long = "select",
+        help = "Schedule exactly this many activities, choosing the best subset"
+    )]
+    select: Option<usize>,
+    #[arg(
+        long = "greedy-init",
+        help = "Start from the most important activities scheduled, the rest unscheduled"
+    )]
+    greedy_init: bool,
+    #[arg(
+        long = "max-per-topic",
+        help = "Never schedule more than this many sessions of one topic in one time slot"
+    )]
+    max_per_topic: Option<usize>,
+    #[arg(
+        long = "not-adjacent",
+        value_name = "TOPIC:TOPIC",
+        value_parser = parse_topic_pair,
+        help = "Never run these two topics back to back in one room; may be repeated"
+    )]
+    not_adjacent: Vec<(TopicLabel, TopicLabel)>,
+    #[arg(
+        long = "room-churn",
+        default_value = "0",
+        help = "Weight of the penalty for a topic hopping rooms between consecutive slots"
+    )]
+    room_churn: f32,
+    #[arg(
+        long = "transition-cost",
+        default_value = "0",
+        help = "Penalty for related activities back to back in the same room"
+    )]
+    transition_cost: f32,
+    #[arg(
+        long = "room-gaps",
+        default_value = "0",
+        help = "Weight of the penalty for empty slots between a room's sessions"
+    )]
+    room_gaps: f32,
+    #[arg(
+        long = "fairness",
+        default_value = "0",
+        help = "Weight of the penalty for topics clustering early or late"
+    )]
+    fairness: f32,
+    #[arg(
+        long = "preference-weight",
+        default_value = "1",
+        help = "Penalty per slot of distance from an activity's preferred room or time"
+    )]
+    preference_weight: f32,
     #[arg(
         long = "json",
         help = "Output results in JSON format for script parsing"
     )]
     json: bool,
-    #[arg(help = "JSON file containing problem instances")]
-    instances_file: String,
+    #[arg(
+        long = "jsonl",
+        conflicts_with = "json",
+        help = "Output one JSON object per line as each instance finishes"
+    )]
+    jsonl: bool,
+    #[arg(
+        short = 'q',
+        long = "score-only",
+        visible_alias = "quiet",
+        conflicts_with_all = ["json", "jsonl"],
+        help = "Print only the instance id and final penalty per line"
+    )]
+    score_only: bool,
+    #[arg(
+        long = "emit-schedule",
+        help = "Include the final schedule in --json or --jsonl results"
+    )]
+    emit_schedule: bool,
+    #[arg(
+        long = "format",
+        value_enum,
+        default_value_t = Format::Text,
+        conflicts_with_all = ["json", "jsonl", "score_only"],
+        help = "Human-readable output format"
+    )]
+    format: Format,
+    #[arg(
+        long = "explain",
+        conflicts_with_all = ["json", "jsonl", "score_only"],
+        help = "After each summary line, break the final penalty down by term, time slot and place"
+    )]
+    explain: bool,
+    #[arg(
+        long = "profile",
+        help = "Report on stderr where each run spent its time: evaluating, swapping or reshuffling"
+    )]
+    profile: bool,
+    #[arg(
+        long = "progress",
+        help = "Show a progress bar on stderr while searching; only on a terminal, never with --json or --jsonl, and only when built with the progress feature"
+    )]
+    progress: bool,
+    #[arg(
+        long = "column-width",
+        default_value = "12",
+        help = "Column width for --format grid; longer labels are truncated"
+    )]
+    column_width: usize,
+    #[arg(
+        long = "merge",
+        help = "Merge same-id instances from different files into one problem"
+    )]
+    merge: bool,
+    #[arg(
+        long = "weights",
+        value_name = "FILE",
+        help = "JSON file overriding the built-in penalty weights"
+    )]
+    weights: Option<String>,
+    #[arg(
+        long = "missed-priority-weight",
+        value_name = "W",
+        help = "Cost per unit of drop weight of each unscheduled activity (default 1)"
+    )]
+    missed_priority_weight: Option<f32>,
+    #[arg(
+        long = "priority-conflict-weight",
+        value_name = "W",
+        help = "Cost of high-priority activities competing for a time slot (default 1)"
+    )]
+    priority_conflict_weight: Option<f32>,
+    #[arg(
+        long = "topic-conflict-weight",
+        value_name = "W",
+        help = "Cost of each same-topic clash within a time slot (default 10)"
+    )]
+    topic_conflict_weight: Option<f32>,
+    #[arg(
+        long = "lateness-weight",
+        value_name = "W",
+        help = "Cost per unit of priority per time slot of delay (default 0.1)"
+    )]
+    lateness_weight: Option<f32>,
+    #[arg(
+        long = "empty-slot-weight",
+        value_name = "W",
+        help = "Soft cost of each empty slot, in place of counting it with unscheduled activities"
+    )]
+    empty_slot_weight: Option<f32>,
+    #[arg(required = true, help = "JSON files containing problem instances")]
+    instances_files: Vec<String>,
+}
+
+/// Local search algorithms the improver can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Algorithm {
+    /// Plain hill climbing, stopping at the first local optimum
+    Hill,
+    /// Hill climbing mixed with random moves, see --noise-prob
+    Noise,
+    /// Tabu search, taking the best non-tabu swap even when it is worse,
+    /// see --tabu-tenure
+    Tabu,
+    /// Simulated annealing on random swaps, see --init-temp and --cooling
+    Annealing,
+    /// Great deluge on random swaps, see --rain
+    GreatDeluge,
+    /// Late acceptance hill climbing on random swaps, see --history
+    Lahc,
+}
+
+fn parse_probability(s: &str) -> Result<f32, String> {
+    let p: f32 = s.parse().map_err(|e| format!("{e}"))?;
+    if (0.0..=1.0).contains(&p) {
+        Ok(p)
+    } else {
+        Err(format!("{p} is not within 0.0..=1.0"))
+    }
+}
+
+fn parse_positive(s: &str) -> Result<f64, String> {
+    let x: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if x > 0.0 && x.is_finite() {
+        Ok(x)
+    } else {
+        Err(format!("{x} is not positive"))
+    }
+}
+
+fn parse_non_negative(s: &str) -> Result<f64, String> {
+    let x: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if x >= 0.0 && x.is_finite() {
+        Ok(x)
+    } else {
+        Err(format!("{x} is negative"))
+    }
+}
+
+fn parse_cooling(s: &str) -> Result<f64, String> {
+    let x = parse_positive(s)?;
+    if x <= 1.0 {
+        Ok(x)
+    } else {
+        Err(format!("{x} is more than 1.0"))
+    }
+}
+
+impl Args {
+    /// The selected algorithm, honoring the `--noise` shorthand.
+    fn algorithm(&self) -> Algorithm {
+        if self.noise {
+            Algorithm::Noise
+        } else {
+            self.algorithm
+        }
+    }
+}
+
+/// Ways to split a `--total-timeout` across instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Allocation {
+    /// Every instance gets the same share
+    Even,
+    /// Shares follow each instance's estimated difficulty
+    Difficulty,
+}
+
+/// Splits a `--total-timeout` across instances as they come up.
+///
+/// Each instance gets its weighted share of the time still left, so time
+/// one instance does not use, because it converged early, passes on to the
+/// rest, and an overrun is taken out of them.
+struct TimeBudget {
+    total: Duration,
+    weights: Vec<f32>,
+}
+
+impl TimeBudget {
+    fn new(
+        total: Duration,
+        allocation: Allocation,
+        instances: &[SchedulingInstance<Activity>],
+    ) -> Self {
+        let weights = instances
+            .iter()
+            .map(|instance| match allocation {
+                Allocation::Even => 1.0,
+                Allocation::Difficulty => {
+                    let difficulty = instance.difficulty(|a| a.topic, |a| a.priority as f32);
+                    // Nothing can be scheduled without slots, so spend nothing there
+                    if difficulty.is_finite() {
+                        difficulty
+                    } else {
+                        0.0
+                    }
+                }
+            })
+            .collect();
+        TimeBudget { total, weights }
+    }
+
+    /// The timeout for instance `index`, once `spent` has gone on the ones
+    /// before it.
+    fn slice(&self, index: usize, spent: Duration) -> Duration {
+        let remaining = self.total.saturating_sub(spent);
+        let rest = &self.weights[index..];
+        let rest_weight: f32 = rest.iter().sum();
+        let share = if rest_weight > 0.0 {
+            self.weights[index] / rest_weight
+        } else {
+            1.0 / rest.len() as f32
+        };
+        remaining.mul_f32(share)
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Human-readable output formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// One summary line per instance, and the terms of its penalty
+    Text,
+    /// The summary line followed by the final schedule as a boxed grid
+    Grid,
+    /// The summary line followed by the final schedule as CSV
+    Csv,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Activity {
     pub priority: usize,
     pub topic: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary_topics: Vec<usize>,
+    /// Room this activity would rather be in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_place: Option<usize>,
+    /// Time slot this activity would rather be in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_time: Option<usize>,
+    /// Cost of leaving this activity unscheduled, if not its priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop_weight: Option<f32>,
+    /// Time slots this activity may never be scheduled in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_times: Vec<usize>,
+}
+
+impl Activity {
+    /// All topics this activity belongs to, primary first.
+    fn topics(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.topic).chain(self.secondary_topics.iter().copied())
+    }
+
+    /// Cost of leaving this activity unscheduled: its
+    /// [`drop_weight`](Activity::drop_weight), defaulting to its priority.
+    fn drop_weight(&self) -> f32 {
+        self.drop_weight.unwrap_or(self.priority as f32)
+    }
+
+    /// Whether this activity may be scheduled at `time`.
+    fn available_at(&self, time: usize) -> bool {
+        !self.unavailable_times.contains(&time)
+    }
+
+    /// Canonical importance order: sorting by this key puts the most
+    /// important activities first, by descending priority and then topic.
+    fn sort_key(&self) -> impl Ord {
+        (Reverse(self.priority), self.topic)
+    }
+}
+
+/// A topic as written in an instance file: a number or a readable label
+/// such as `"databases"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(untagged)]
+enum TopicLabel {
+    Id(usize),
+    Name(String),
+}
+
+impl std::str::FromStr for TopicLabel {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse() {
+            Ok(id) => TopicLabel::Id(id),
+            Err(_) => TopicLabel::Name(s.to_string()),
+        })
+    }
+}
+
+/// Parse a `--not-adjacent` pair such as `rust:databases` or `3:7`.
+fn parse_topic_pair(s: &str) -> Result<(TopicLabel, TopicLabel), String> {
+    match s.split_once(':') {
+        Some((a, b)) if !a.is_empty() && !b.is_empty() => {
+            Ok((a.parse().unwrap(), b.parse().unwrap()))
+        }
+        _ => Err(format!("expected TOPIC:TOPIC, got {s:?}")),
+    }
+}
+
+/// An activity as read from an instance file, before topic labels are
+/// interned.
+#[derive(Debug, Deserialize)]
+struct ActivityInput {
+    priority: usize,
+    topic: TopicLabel,
+    #[serde(default)]
+    secondary_topics: Vec<TopicLabel>,
+    #[serde(default)]
+    preferred_place: Option<usize>,
+    #[serde(default)]
+    preferred_time: Option<usize>,
+    #[serde(default)]
+    drop_weight: Option<f32>,
+    #[serde(default)]
+    unavailable_times: Vec<usize>,
+}
+
+/// Interns topic labels to the numeric ids used by the penalty math.
+///
+/// Numeric topics keep their value. Named topics are numbered down from
+/// `usize::MAX` so they never collide with numeric ones.
+#[derive(Debug, Default)]
+struct TopicInterner {
+    names: HashMap<String, usize>,
+}
+
+impl TopicInterner {
+    fn intern(&mut self, label: TopicLabel) -> usize {
+        match label {
+            TopicLabel::Id(id) => id,
+            TopicLabel::Name(name) => {
+                let next = usize::MAX - self.names.len();
+                *self.names.entry(name).or_insert(next)
+            }
+        }
+    }
+
+    /// The label a topic id was interned from.
+    fn label(&self, id: usize) -> String {
+        self.names
+            .iter()
+            .find(|&(_, &named)| named == id)
+            .map_or_else(|| id.to_string(), |(name, _)| name.clone())
+    }
+
+    fn activity(&mut self, input: ActivityInput) -> Activity {
+        Activity {
+            priority: input.priority,
+            topic: self.intern(input.topic),
+            secondary_topics: input
+                .secondary_topics
+                .into_iter()
+                .map(|label| self.intern(label))
+                .collect(),
+            preferred_place: input.preferred_place,
+            preferred_time: input.preferred_time,
+            drop_weight: input.drop_weight,
+            unavailable_times: input.unavailable_times,
+        }
+    }
+
+    /// Intern the topics of every activity in `instance`.
+    fn instance(&mut self, instance: Instance) -> SchedulingInstance<Activity> {
+        SchedulingInstance {
+            id: instance.id,
+            nplaces: instance.nplaces,
+            ntimes: instance.ntimes,
+            activities: instance
+                .activities
+                .into_iter()
+                .map(|input| self.activity(input))
+                .collect(),
+            buffer_times: instance.buffer_times,
+            constraints: instance.constraints,
+            place_labels: instance.place_labels,
+            time_labels: instance.time_labels,
+        }
+    }
+}
+
+/// How much one unscheduled activity counts against the other penalty
+/// when a run must be scored with a single number
+const UNSCHEDULED_WEIGHT: f32 = 1000.0;
+
+/// A run's penalty as a single number, for [`Schedule::normalized_penalty`]
+fn combined_penalty((unscheduled, other): (usize, f32)) -> f32 {
+    unscheduled as f32 * UNSCHEDULED_WEIGHT + other
 }
 
 #[derive(Serialize)]
@@ -52,7 +563,15 @@ struct RunResult {
     final_other_penalty: f32,
     unscheduled_improvement: i32,
     other_improvement: f32,
+    /// Final penalty as a fraction of initial penalty, from
+    /// [`Schedule::normalized_penalty`]
+    normalized_penalty: f32,
+    /// The terms of `final_other_penalty`
+    final_breakdown: PenaltyBreakdown,
     config: RunConfig,
+    /// The final schedule, with `--emit-schedule`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<Schedule<Activity>>,
 }
 
 #[derive(Serialize)]
@@ -61,140 +580,2234 @@ struct RunConfig {
     restarts: Option<usize>,
     proportional: bool,
     timeout: Option<u64>,
+    total_timeout: Option<u64>,
     nswaps: Option<usize>,
+    seed: Option<u64>,
+}
+
+/// Sum of squared per-topic counts among the given simultaneous activities.
+/// Multi-topic activities count toward each of their topics.
+#[cfg(test)]
+fn topic_conflict<'a>(activities: impl Iterator<Item = &'a Activity>) -> f32 {
+    topic_conflict_in(activities, &mut HashMap::new())
 }
 
-fn activity_penalty(schedule: &Schedule<Activity>) -> (usize, f32) {
-    let nunscheduled = schedule.get_unscheduled_activities().count();
-    let nempty = schedule.empty_slots_count();
+/// [`topic_conflict`], counting in a caller-provided map that is cleared
+/// first so its allocation can be reused.
+fn topic_conflict_in<'a>(
+    activities: impl Iterator<Item = &'a Activity>,
+    topic_counts: &mut HashMap<usize, f32>,
+) -> f32 {
+    topic_counts.clear();
+    for topic in activities.flat_map(Activity::topics) {
+        *topic_counts.entry(topic).or_insert(0.0) += 1.0;
+    }
+    topic_counts.values().map(|&c| c * c).sum::<f32>()
+}
 
-    let mut other_penalty = 0.0;
+/// Buffers reused across penalty evaluations, so that the per-time-slot
+/// conflict scans don't allocate on every call.
+#[derive(Debug, Default)]
+struct PenaltyScratch {
+    squared_priorities: Vec<NotNan<f32>>,
+    topic_counts: HashMap<usize, f32>,
+    topic_slot_counts: HashMap<usize, usize>,
+    /// Conflicts of each time slot as last computed, by time
+    columns: Vec<CachedColumn>,
+    /// The [`conflict_key`] of the time slot being evaluated
+    column_key: Vec<usize>,
+}
 
-    // Penalty for unscheduled activities based on their priority
-    let missed_out = schedule
-        .get_unscheduled_activities()
-        .map(|a| 1.0 * a.priority as f32)
-        .sum::<f32>();
-    other_penalty += missed_out;
+/// The unweighted conflicts of one time slot and the [`conflict_key`] they
+/// were computed for. A swap changes at most two time slots, so nearly
+/// every cached column still matches the next schedule evaluated.
+#[derive(Debug, Default)]
+struct CachedColumn {
+    key: Vec<usize>,
+    conflicts: (f32, f32),
+}
 
-    // Priority and topic conflicts within time slots
-    let mut topic_conflicts = 0.0;
-    let mut priority_conflicts = 0.0;
-    for r in schedule.slots().axis_iter(Axis(1)) {
-        let mut vars: Vec<_> = r
-            .iter()
-            .filter_map(|a| a.as_ref())
-            .map(|a| {
-                let p = a.priority as f32;
-                p * p
-            })
-            .map(|p| NotNan::new(p).unwrap())
-            .collect();
-        vars.sort();
-        let big3 = vars
-            .into_iter()
-            .rev()
-            .take(3)
-            .map(NotNan::into_inner)
-            .sum::<f32>();
-        priority_conflicts += 1.0 * f32::sqrt(big3);
+/// Weights of the penalty terms that are always on, loadable from a
+/// `--weights` file and overridden by the `--*-weight` flags. Missing
+/// fields keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PenaltyWeights {
+    /// Cost of each empty slot as a soft term. Unset, empty slots count
+    /// with the unscheduled activities in the hard part of the penalty, so
+    /// no soft saving is worth leaving a slot empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    empty_slot: Option<f32>,
+    /// Cost per unit of drop weight of each unscheduled activity.
+    missed_priority: f32,
+    /// Cost of the priorities of activities competing for a time slot.
+    priority_conflict: f32,
+    /// Cost of each same-topic clash within a time slot.
+    topic_conflict: f32,
+    /// Cost per unit of priority per time slot of delay.
+    lateness: f32,
+}
 
-        let mut topic_counts: HashMap<usize, f32> = HashMap::new();
-        for a in r.iter().filter_map(|a| a.as_ref()) {
-            *topic_counts.entry(a.topic).or_insert(0.0) += 1.0;
+impl Default for PenaltyWeights {
+    fn default() -> Self {
+        Self {
+            empty_slot: None,
+            missed_priority: 1.0,
+            priority_conflict: 1.0,
+            topic_conflict: 10.0,
+            lateness: 0.1,
         }
-        let tc = topic_counts.values().map(|&c| c * c).sum::<f32>();
-        topic_conflicts += 10.0 * tc;
     }
-    other_penalty += priority_conflicts + topic_conflicts;
+}
 
-    // Lateness penalty (earlier time slots are preferred)
-    let mut lateness = 0.0;
-    for (t, c) in schedule.slots().axis_iter(Axis(0)).enumerate() {
-        for a in c.into_iter().flatten() {
-            lateness += 0.1 * a.priority as f32 * t as f32;
+impl PenaltyWeights {
+    /// Apply the `--*-weight` flags given on the command line.
+    fn override_from(self, args: &Args) -> Self {
+        Self {
+            empty_slot: args.empty_slot_weight.or(self.empty_slot),
+            missed_priority: args.missed_priority_weight.unwrap_or(self.missed_priority),
+            priority_conflict: args
+                .priority_conflict_weight
+                .unwrap_or(self.priority_conflict),
+            topic_conflict: args.topic_conflict_weight.unwrap_or(self.topic_conflict),
+            lateness: args.lateness_weight.unwrap_or(self.lateness),
         }
     }
-    other_penalty += lateness;
-
-    (nunscheduled + nempty, other_penalty)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-
-    let file_contents = fs::read_to_string(&args.instances_file)?;
-    let instances: Vec<SchedulingInstance<Activity>> = serde_json::from_str(&file_contents)?;
+/// Read a `--weights` file.
+fn load_weights(path: &str) -> Result<PenaltyWeights, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    Ok(serde_json::from_str(&text).map_err(|e| format!("{path}: {e}"))?)
+}
 
-    let mut results = Vec::new();
+/// Options shaping the conference penalty function.
+#[derive(Debug, Clone, Default)]
+struct PenaltyConfig {
+    /// Weights of the always-on terms.
+    weights: PenaltyWeights,
+    /// Schedule exactly this many activities instead of as many as fit.
+    select: Option<usize>,
+    /// Weight of the room-churn term; zero disables it.
+    room_churn: f32,
+    /// Cost of related activities back to back in one room; zero disables it.
+    transition_cost: f32,
+    /// Cost of each empty slot between two sessions in one room; zero
+    /// disables it.
+    room_gaps: f32,
+    /// Weight of the topic time-fairness term; zero disables it.
+    fairness: f32,
+    /// Cost per unit of distance from an activity's preferred place or time.
+    preference_weight: f32,
+    /// Hard cap on sessions of one topic in one time slot.
+    max_per_topic: Option<usize>,
+    /// Topic pairs that may never run back to back in one place.
+    not_adjacent: Vec<(usize, usize)>,
+}
 
-    for instance in instances {
-        let mut schedule = Schedule::new(
-            instance.nplaces,
-            instance.ntimes,
-            instance.activities.into_iter(),
-        );
+impl PenaltyConfig {
+    fn from_args(args: &Args) -> Self {
+        Self {
+            weights: PenaltyWeights::default(),
+            select: args.select,
+            room_churn: args.room_churn,
+            transition_cost: args.transition_cost,
+            room_gaps: args.room_gaps,
+            fairness: args.fairness,
+            preference_weight: args.preference_weight,
+            max_per_topic: args.max_per_topic,
+            not_adjacent: Vec::new(),
+        }
+    }
+}
 
-        let (initial_unscheduled, initial_other_penalty) = activity_penalty(&schedule);
+/// Whether two activities share any topic.
+fn share_topic(a: &Activity, b: &Activity) -> bool {
+    a.topics().any(|x| b.topics().any(|y| x == y))
+}
 
-        // Use the new builder API
-        let mut improver = schedule.improve(activity_penalty);
-        if let Some(nswaps) = args.nswaps {
-            improver = improver.max_swaps(nswaps);
-        }
-        if args.noise {
-            improver = improver.with_noise();
+/// Total distance in place index between same-topic activities in
+/// consecutive time slots, modelling attendees walking between rooms.
+fn room_churn(schedule: &Schedule<Activity>) -> f32 {
+    let slots = schedule.slots();
+    let (nplaces, ntimes) = schedule.dimensions();
+    let mut churn = 0.0;
+    for t in 1..ntimes {
+        for p1 in 0..nplaces {
+            let Some(a) = &slots[(p1, t - 1)] else {
+                continue;
+            };
+            for p2 in 0..nplaces {
+                match &slots[(p2, t)] {
+                    Some(b) if share_topic(a, b) => churn += p1.abs_diff(p2) as f32,
+                    _ => (),
+                }
+            }
         }
-        if let Some(restarts) = args.restarts {
-            if args.proportional {
-                improver = improver.restarts_proportional(restarts);
-            } else {
-                improver = improver.restarts(restarts);
+    }
+    churn
+}
+
+/// Number of empty slots in `row` between its first and last activity,
+/// each one an idle stretch for the staff of that room.
+fn row_gaps<'a>(row: impl IntoIterator<Item = &'a Option<Activity>>) -> usize {
+    let mut gaps = 0;
+    let mut pending = None;
+    for slot in row {
+        match (slot, &mut pending) {
+            (Some(_), Some(empty)) => {
+                gaps += *empty;
+                *empty = 0;
             }
+            (Some(_), None) => pending = Some(0),
+            (None, Some(empty)) => *empty += 1,
+            (None, None) => (),
         }
-        if let Some(timeout_secs) = args.timeout {
-            improver = improver.timeout(Duration::from_secs(timeout_secs));
+    }
+    gaps
+}
+
+/// Total [`row_gaps`] over all places.
+fn room_gaps(schedule: &Schedule<Activity>) -> usize {
+    schedule.slots().axis_iter(Axis(0)).map(row_gaps).sum()
+}
+
+/// Mean time index of the scheduled activities of each topic. Multi-topic
+/// activities count toward each of their topics.
+fn topic_mean_times(schedule: &Schedule<Activity>) -> HashMap<usize, f32> {
+    let mut totals: HashMap<usize, (f32, f32)> = HashMap::new();
+    for ((_, t), a) in schedule.slots().indexed_iter() {
+        for topic in a.iter().flat_map(Activity::topics) {
+            let (sum, count) = totals.entry(topic).or_insert((0.0, 0.0));
+            *sum += t as f32;
+            *count += 1.0;
         }
-        improver.run();
+    }
+    totals
+        .into_iter()
+        .map(|(topic, (sum, count))| (topic, sum / count))
+        .collect()
+}
 
-        let (final_unscheduled, final_other_penalty) = activity_penalty(&schedule);
-        let unscheduled_improvement = initial_unscheduled as i32 - final_unscheduled as i32;
-        let other_improvement = initial_other_penalty - final_other_penalty;
+/// Variance across topics of their mean time index: zero when every topic
+/// gets early and late slots alike, large when some topics are always early.
+fn topic_time_spread(schedule: &Schedule<Activity>) -> f32 {
+    let means = topic_mean_times(schedule);
+    if means.is_empty() {
+        return 0.0;
+    }
+    let n = means.len() as f32;
+    let mean = means.values().sum::<f32>() / n;
+    means
+        .values()
+        .map(|&m| (m - mean) * (m - mean))
+        .sum::<f32>()
+        / n
+}
 
-        if args.json {
-            results.push(RunResult {
-                instance_id: instance.id,
-                initial_unscheduled,
-                initial_other_penalty,
-                final_unscheduled,
-                final_other_penalty,
-                unscheduled_improvement,
-                other_improvement,
-                config: RunConfig {
-                    noise: args.noise,
-                    restarts: args.restarts,
-                    proportional: args.proportional,
-                    timeout: args.timeout,
-                    nswaps: args.nswaps,
-                },
-            });
+/// Total distance of scheduled activities from their preferred place and
+/// time, for those that have preferences.
+fn preference_distance(schedule: &Schedule<Activity>) -> f32 {
+    let mut distance = 0;
+    for ((p, t), a) in schedule.slots().indexed_iter() {
+        let Some(a) = a else {
+            continue;
+        };
+        distance += a.preferred_place.map_or(0, |pp| p.abs_diff(pp));
+        distance += a.preferred_time.map_or(0, |pt| t.abs_diff(pt));
+    }
+    distance as f32
+}
+
+/// [`activity_penalty_with`] on fresh buffers.
+#[cfg(test)]
+fn activity_penalty(schedule: &Schedule<Activity>, config: &PenaltyConfig) -> (usize, f32) {
+    activity_penalty_with(schedule, config, &mut PenaltyScratch::default())
+}
+
+/// Penalty as `(hard, other)`: `hard` counts unscheduled activities plus
+/// empty slots, unless [`PenaltyWeights::empty_slot`] makes those a soft
+/// term, or with [`PenaltyConfig::select`] the distance from the target
+/// number of scheduled activities. Per-time-slot work reuses the buffers
+/// in `scratch`.
+fn activity_penalty_with(
+    schedule: &Schedule<Activity>,
+    config: &PenaltyConfig,
+    scratch: &mut PenaltyScratch,
+) -> (usize, f32) {
+    let hard = match config.select {
+        Some(k) => schedule.distance_from_target(k),
+        None => {
+            let empty = match config.weights.empty_slot {
+                Some(_) => 0,
+                None => schedule.empty_slots_count(),
+            };
+            schedule.get_unscheduled_activities().count() + empty
+        }
+    };
+
+    (hard, penalty_breakdown(schedule, config, scratch).total())
+}
+
+/// The soft part of the penalty, split into its named terms. Each term is
+/// already weighted, so they add up to the `other` penalty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+struct PenaltyBreakdown {
+    empty_slots: f32,
+    missed_priority: f32,
+    priority_conflicts: f32,
+    topic_conflicts: f32,
+    lateness: f32,
+    room_churn: f32,
+    transitions: f32,
+    room_gaps: f32,
+    preferences: f32,
+    fairness: f32,
+}
+
+impl PenaltyBreakdown {
+    /// Each term with a readable name, in a fixed order.
+    fn components(&self) -> [(&'static str, f32); 10] {
+        [
+            ("empty slots", self.empty_slots),
+            ("missed priority", self.missed_priority),
+            ("priority conflicts", self.priority_conflicts),
+            ("topic conflicts", self.topic_conflicts),
+            ("lateness", self.lateness),
+            ("room churn", self.room_churn),
+            ("transitions", self.transitions),
+            ("room gaps", self.room_gaps),
+            ("preferences", self.preferences),
+            ("fairness", self.fairness),
+        ]
+    }
+
+    fn total(&self) -> f32 {
+        self.components().iter().map(|&(_, x)| x).sum()
+    }
+
+    /// The terms that contribute anything, on one indented line, such as
+    /// `  penalty: topic conflicts 20.00, lateness 1.50`.
+    fn summary_line(&self) -> String {
+        let terms: Vec<String> = self
+            .components()
+            .iter()
+            .filter(|&&(_, x)| x != 0.0)
+            .map(|(name, x)| format!("{name} {x:.2}"))
+            .collect();
+        if terms.is_empty() {
+            "  penalty: none".to_string()
         } else {
-            println!(
-                "{} unscheduled:{}->{} other:{:.2}->{:.2} improvements:{},{:.2}",
-                instance.id,
-                initial_unscheduled,
-                final_unscheduled,
-                initial_other_penalty,
-                final_other_penalty,
-                unscheduled_improvement,
-                other_improvement
-            );
+            format!("  penalty: {}", terms.join(", "))
         }
     }
+}
+
+/// Compute each term of the soft penalty; terms whose weight is zero are
+/// skipped and left at zero.
+fn penalty_breakdown(
+    schedule: &Schedule<Activity>,
+    config: &PenaltyConfig,
+    scratch: &mut PenaltyScratch,
+) -> PenaltyBreakdown {
+    // Penalty for unscheduled activities based on their drop weight
+    let mut breakdown = PenaltyBreakdown {
+        missed_priority: schedule
+            .get_unscheduled_activities()
+            .map(Activity::drop_weight)
+            .sum::<f32>()
+            * config.weights.missed_priority,
+        ..PenaltyBreakdown::default()
+    };
+
+    // Empty slots, when they are not counted in the hard term
+    if let Some(weight) = config.weights.empty_slot.filter(|&w| w != 0.0) {
+        breakdown.empty_slots = weight * schedule.empty_slots_count() as f32;
+    }
 
-    if args.json {
-        println!("{}", serde_json::to_string_pretty(&results)?);
+    // Priority and topic conflicts within time slots, recomputed only for
+    // the slots that changed since the last evaluation
+    for (t, r) in schedule.slots().axis_iter(Axis(1)).enumerate() {
+        let (priority, topic) = cached_time_slot_conflicts(t, r, &config.weights, scratch);
+        breakdown.priority_conflicts += priority;
+        breakdown.topic_conflicts += topic;
     }
 
-    Ok(())
+    // Lateness penalty (earlier time slots are preferred, buffer slots are exempt)
+    let nbuffered = schedule.dimensions().1 - schedule.buffer_times();
+    for ((_, t), a) in schedule.slots().indexed_iter() {
+        if let Some(a) = a.as_ref().filter(|_| t < nbuffered) {
+            breakdown.lateness += config.weights.lateness * a.priority as f32 * t as f32;
+        }
+    }
+
+    // Room churn: a topic's consecutive sessions should be in nearby rooms
+    if config.room_churn != 0.0 {
+        breakdown.room_churn = config.room_churn * room_churn(schedule);
+    }
+
+    // Optional cost of related activities back to back in one room
+    if config.transition_cost != 0.0 {
+        let related = |a: &Activity, b: &Activity| f32::from(u8::from(share_topic(a, b)));
+        breakdown.transitions = config.transition_cost * schedule.transition_cost(related);
+    }
+
+    // Optional cost of idle slots between a room's sessions
+    if config.room_gaps != 0.0 {
+        breakdown.room_gaps = config.room_gaps * room_gaps(schedule) as f32;
+    }
+
+    // Soft preferences for a particular room or time
+    if config.preference_weight != 0.0 {
+        breakdown.preferences = config.preference_weight * preference_distance(schedule);
+    }
+
+    // Optional fairness penalty so no topic monopolizes the early slots
+    if config.fairness != 0.0 {
+        breakdown.fairness = config.fairness * topic_time_spread(schedule);
+    }
+
+    breakdown
+}
+
+/// [`activity_penalty_with`], rejecting schedules over the
+/// [`PenaltyConfig::max_per_topic`] cap or with
+/// [`PenaltyConfig::not_adjacent`] topics back to back as infeasible.
+fn feasible_penalty(
+    schedule: &Schedule<Activity>,
+    config: &PenaltyConfig,
+    scratch: &mut PenaltyScratch,
+) -> Result<(usize, f32), Infeasible> {
+    let mut violations = forbidden_neighbors(schedule, &config.not_adjacent);
+    if let Some(cap) = config.max_per_topic {
+        violations += topic_cap_excess(schedule, cap, &mut scratch.topic_slot_counts);
+    }
+    if violations > 0 {
+        return Err(Infeasible(violations));
+    }
+    Ok(activity_penalty_with(schedule, config, scratch))
+}
+
+/// Whether `a` then `b`, in either order, is one of the `pairs` of topics
+/// that may not be adjacent. Secondary topics count too.
+fn forbidden_pair(a: &Activity, b: &Activity, pairs: &[(usize, usize)]) -> bool {
+    let has = |activity: &Activity, topic| activity.topics().any(|t| t == topic);
+    pairs
+        .iter()
+        .any(|&(x, y)| (has(a, x) && has(b, y)) || (has(a, y) && has(b, x)))
+}
+
+/// Number of consecutive time slots in one place whose activities are one
+/// of the `pairs` of topics that may not be adjacent.
+fn forbidden_neighbors(schedule: &Schedule<Activity>, pairs: &[(usize, usize)]) -> usize {
+    if pairs.is_empty() {
+        return 0;
+    }
+    let mut count = 0;
+    for row in schedule.slots().axis_iter(Axis(0)) {
+        for time in 1..row.len() {
+            if let (Some(a), Some(b)) = (&row[time - 1], &row[time]) {
+                count += usize::from(forbidden_pair(a, b, pairs));
+            }
+        }
+    }
+    count
+}
+
+/// Number of sessions beyond `cap` of any one topic in any one time slot,
+/// counting secondary topics too.
+fn topic_cap_excess(
+    schedule: &Schedule<Activity>,
+    cap: usize,
+    counts: &mut HashMap<usize, usize>,
+) -> usize {
+    let mut excess = 0;
+    for column in schedule.slots().axis_iter(Axis(1)) {
+        counts.clear();
+        for topic in column.iter().flatten().flat_map(Activity::topics) {
+            *counts.entry(topic).or_insert(0) += 1;
+        }
+        excess += counts
+            .values()
+            .map(|&c| c.saturating_sub(cap))
+            .sum::<usize>();
+    }
+    excess
+}
+
+/// Total conflict among the activities sharing one time slot.
+fn time_slot_conflict(
+    column: ArrayView1<Option<Activity>>,
+    weights: &PenaltyWeights,
+    scratch: &mut PenaltyScratch,
+) -> f32 {
+    let (priority, topic) = time_slot_conflicts(column, weights, scratch);
+    priority + topic
+}
+
+/// Priority and topic conflicts among the activities sharing one time slot.
+fn time_slot_conflicts(
+    column: ArrayView1<Option<Activity>>,
+    weights: &PenaltyWeights,
+    scratch: &mut PenaltyScratch,
+) -> (f32, f32) {
+    let (priority, topic) = unweighted_conflicts(
+        column,
+        &mut scratch.squared_priorities,
+        &mut scratch.topic_counts,
+    );
+    (
+        weights.priority_conflict * priority,
+        weights.topic_conflict * topic,
+    )
+}
+
+/// [`time_slot_conflicts`] of the time slot at `time`, reusing the
+/// conflicts cached in `scratch` if the slot holds what it did when they
+/// were computed.
+fn cached_time_slot_conflicts(
+    time: usize,
+    column: ArrayView1<Option<Activity>>,
+    weights: &PenaltyWeights,
+    scratch: &mut PenaltyScratch,
+) -> (f32, f32) {
+    if scratch.columns.len() <= time {
+        scratch.columns.resize_with(time + 1, CachedColumn::default);
+    }
+    conflict_key(column, &mut scratch.column_key);
+    let cached = &mut scratch.columns[time];
+    if !cached.key.iter().eq(&scratch.column_key) {
+        cached.conflicts = unweighted_conflicts(
+            column,
+            &mut scratch.squared_priorities,
+            &mut scratch.topic_counts,
+        );
+        // The old key's buffer serves for the next time slot
+        std::mem::swap(&mut cached.key, &mut scratch.column_key);
+    }
+    let (priority, topic) = cached.conflicts;
+    (
+        weights.priority_conflict * priority,
+        weights.topic_conflict * topic,
+    )
+}
+
+/// Everything the conflicts of a time slot depend on: the priority, number
+/// of topics and topics of each activity in it, in place order.
+fn conflict_key(column: ArrayView1<Option<Activity>>, key: &mut Vec<usize>) {
+    key.clear();
+    for a in column.iter().flatten() {
+        key.push(a.priority);
+        key.push(1 + a.secondary_topics.len());
+        key.extend(a.topics());
+    }
+}
+
+/// Priority and topic conflicts of one time slot, before weighting: the
+/// root of the sum of the three largest squared priorities, and the
+/// [`topic_conflict`].
+fn unweighted_conflicts(
+    column: ArrayView1<Option<Activity>>,
+    vars: &mut Vec<NotNan<f32>>,
+    topic_counts: &mut HashMap<usize, f32>,
+) -> (f32, f32) {
+    vars.clear();
+    vars.extend(
+        column
+            .iter()
+            .filter_map(|a| a.as_ref())
+            .map(|a| {
+                let p = a.priority as f32;
+                p * p
+            })
+            .map(|p| NotNan::new(p).unwrap()),
+    );
+    vars.sort();
+    let big3 = vars
+        .iter()
+        .rev()
+        .take(3)
+        .map(|p| p.into_inner())
+        .sum::<f32>();
+    let topic_conflict = topic_conflict_in(column.iter().filter_map(|a| a.as_ref()), topic_counts);
+    (f32::sqrt(big3), topic_conflict)
+}
+
+/// Penalty terms charged to a single scheduled activity at `(place, time)`:
+/// lateness and, weighted, distance from its preferences.
+fn cell_penalty(
+    schedule: &Schedule<Activity>,
+    config: &PenaltyConfig,
+    (p, t): (usize, usize),
+    a: &Activity,
+) -> f32 {
+    let nbuffered = schedule.dimensions().1 - schedule.buffer_times();
+    let lateness = if t < nbuffered {
+        config.weights.lateness * a.priority as f32 * t as f32
+    } else {
+        0.0
+    };
+    let distance = a.preferred_place.map_or(0, |pp| p.abs_diff(pp))
+        + a.preferred_time.map_or(0, |pt| t.abs_diff(pt));
+    lateness + config.preference_weight * distance as f32
+}
+
+/// Penalty of each time slot: its conflicts plus the [`cell_penalty`] of
+/// its activities. The rest of the total penalty is the empty-slot,
+/// missed-priority, room-churn, transition-cost, room-gap and fairness
+/// terms, which span time slots.
+fn column_penalties(
+    schedule: &Schedule<Activity>,
+    config: &PenaltyConfig,
+    scratch: &mut PenaltyScratch,
+) -> Vec<f32> {
+    schedule
+        .slots()
+        .axis_iter(Axis(1))
+        .enumerate()
+        .map(|(t, column)| {
+            let cells = column
+                .iter()
+                .enumerate()
+                .filter_map(|(p, a)| Some(cell_penalty(schedule, config, (p, t), a.as_ref()?)))
+                .sum::<f32>();
+            time_slot_conflict(column, &config.weights, scratch) + cells
+        })
+        .collect()
+}
+
+/// Penalty of each place: the [`cell_penalty`] of its activities plus its
+/// transition costs and room gaps. The rest of the total penalty is the
+/// empty-slot, missed-priority, conflict, room-churn and fairness terms,
+/// which span places.
+fn row_penalties(schedule: &Schedule<Activity>, config: &PenaltyConfig) -> Vec<f32> {
+    schedule
+        .slots()
+        .axis_iter(Axis(0))
+        .enumerate()
+        .map(|(p, row)| {
+            let cells = row
+                .iter()
+                .enumerate()
+                .filter_map(|(t, a)| Some(cell_penalty(schedule, config, (p, t), a.as_ref()?)))
+                .sum::<f32>();
+            let transitions = row
+                .windows(2)
+                .into_iter()
+                .filter(|pair| match (&pair[0], &pair[1]) {
+                    (Some(a), Some(b)) => share_topic(a, b),
+                    _ => false,
+                })
+                .count();
+            cells
+                + config.transition_cost * transitions as f32
+                + config.room_gaps * row_gaps(row) as f32
+        })
+        .collect()
+}
+
+/// A concrete rule broken by a schedule, for a human reviewer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Conflict {
+    /// Several sessions of one topic share a time slot.
+    TopicClash {
+        time: usize,
+        topic: usize,
+        count: usize,
+    },
+    /// A time slot holds more sessions of one topic than the
+    /// [`PenaltyConfig::max_per_topic`] cap allows.
+    TopicCapExceeded {
+        time: usize,
+        topic: usize,
+        count: usize,
+        cap: usize,
+    },
+    /// Sessions sharing a topic are back to back in one place, at `time - 1`
+    /// and `time`. Only reported when transitions cost something.
+    BackToBack { place: usize, time: usize },
+    /// Topics listed in [`PenaltyConfig::not_adjacent`] are back to back in
+    /// one place, at `time - 1` and `time`.
+    ForbiddenNeighbors { place: usize, time: usize },
+}
+
+impl Conflict {
+    fn describe(&self, topics: &TopicInterner) -> String {
+        match *self {
+            Conflict::TopicClash { time, topic, count } => {
+                format!(
+                    "time {time}: {count} sessions of topic {} clash",
+                    topics.label(topic)
+                )
+            }
+            Conflict::TopicCapExceeded {
+                time,
+                topic,
+                count,
+                cap,
+            } => format!(
+                "time {time}: {count} sessions of topic {}, over the cap of {cap}",
+                topics.label(topic)
+            ),
+            Conflict::BackToBack { place, time } => format!(
+                "place {place}: related sessions back to back at times {} and {time}",
+                time - 1
+            ),
+            Conflict::ForbiddenNeighbors { place, time } => format!(
+                "place {place}: topics that may not be adjacent run at times {} and {time}",
+                time - 1
+            ),
+        }
+    }
+}
+
+/// List the conflicts in a schedule, time slot by time slot and then place
+/// by place. Topics within a time slot are reported in id order.
+fn conflicts(schedule: &Schedule<Activity>, config: &PenaltyConfig) -> Vec<Conflict> {
+    let mut found = Vec::new();
+    for (time, column) in schedule.slots().axis_iter(Axis(1)).enumerate() {
+        let mut counts = BTreeMap::new();
+        for topic in column.iter().flatten().flat_map(Activity::topics) {
+            *counts.entry(topic).or_insert(0) += 1;
+        }
+        for (topic, count) in counts {
+            if count > 1 {
+                found.push(Conflict::TopicClash { time, topic, count });
+            }
+            if let Some(cap) = config.max_per_topic.filter(|&cap| count > cap) {
+                found.push(Conflict::TopicCapExceeded {
+                    time,
+                    topic,
+                    count,
+                    cap,
+                });
+            }
+        }
+    }
+    if config.transition_cost != 0.0 {
+        for (place, row) in schedule.slots().axis_iter(Axis(0)).enumerate() {
+            for time in 1..row.len() {
+                if let (Some(a), Some(b)) = (&row[time - 1], &row[time]) {
+                    if share_topic(a, b) {
+                        found.push(Conflict::BackToBack { place, time });
+                    }
+                }
+            }
+        }
+    }
+    if !config.not_adjacent.is_empty() {
+        for (place, row) in schedule.slots().axis_iter(Axis(0)).enumerate() {
+            for time in 1..row.len() {
+                if let (Some(a), Some(b)) = (&row[time - 1], &row[time]) {
+                    if forbidden_pair(a, b, &config.not_adjacent) {
+                        found.push(Conflict::ForbiddenNeighbors { place, time });
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Describe where a schedule's soft penalty comes from: each named term,
+/// their total, and the three costliest time slots and places.
+fn explain(
+    schedule: &Schedule<Activity>,
+    config: &PenaltyConfig,
+    scratch: &mut PenaltyScratch,
+) -> String {
+    let breakdown = penalty_breakdown(schedule, config, scratch);
+    let mut out = String::new();
+    for (name, value) in breakdown.components() {
+        out.push_str(&format!("  {name}: {value:.2}\n"));
+    }
+    out.push_str(&format!("  total: {:.2}\n", breakdown.total()));
+
+    let worst = |penalties: Vec<f32>, prefix: char| {
+        let mut indexed: Vec<(usize, f32)> = penalties.into_iter().enumerate().collect();
+        indexed.sort_by(|a, b| b.1.total_cmp(&a.1));
+        indexed
+            .iter()
+            .take(3)
+            .map(|(i, x)| format!("{prefix}{i} {x:.2}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let columns = column_penalties(schedule, config, scratch);
+    out.push_str(&format!("  worst time slots: {}\n", worst(columns, 't')));
+    let rows = row_penalties(schedule, config);
+    out.push_str(&format!("  worst places: {}\n", worst(rows, 'p')));
+    out
+}
+
+/// Format a final penalty as `id unscheduled other`, e.g. for `sort -k2n -k3g`.
+fn score_line(instance_id: &str, unscheduled: usize, other_penalty: f32) -> String {
+    format!("{instance_id} {unscheduled} {other_penalty:.2}")
+}
+
+/// Write one result as a single line of JSON, flushing so that a consumer
+/// sees it immediately.
+fn write_jsonl(out: &mut impl Write, result: &RunResult) -> Result<(), Box<dyn std::error::Error>> {
+    serde_json::to_writer(&mut *out, result)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+type Instance = SchedulingInstance<ActivityInput>;
+
+/// Read and concatenate the instance lists of several files, merging
+/// same-id instances when `merge` is set.
+fn load_instances(
+    paths: &[String],
+    merge: bool,
+) -> Result<Vec<Instance>, Box<dyn std::error::Error>> {
+    let mut instances = Vec::new();
+    for path in paths {
+        let file_contents = fs::read_to_string(path)?;
+        let file_instances: Vec<Instance> =
+            serde_json::from_str(&file_contents).map_err(|e| format!("{path}: {e}"))?;
+        instances.extend(file_instances);
+    }
+    if merge {
+        instances = merge_instances(instances)?;
+    }
+    Ok(instances)
+}
+
+/// Combine instances sharing an id into one problem holding all their
+/// activities, keeping the order in which ids first appear. Instances with
+/// the same id must agree on their grid, and their constraints combine.
+fn merge_instances(instances: Vec<Instance>) -> Result<Vec<Instance>, String> {
+    let mut merged: Vec<Instance> = Vec::new();
+    for instance in instances {
+        let Some(target) = merged.iter_mut().find(|m| m.id == instance.id) else {
+            merged.push(instance);
+            continue;
+        };
+        let grid = |i: &Instance| (i.nplaces, i.ntimes, i.buffer_times);
+        if grid(target) != grid(&instance) {
+            return Err(format!(
+                "instance {}: cannot merge a {}x{} grid (buffer {}) with a {}x{} grid (buffer {})",
+                instance.id,
+                target.nplaces,
+                target.ntimes,
+                target.buffer_times,
+                instance.nplaces,
+                instance.ntimes,
+                instance.buffer_times,
+            ));
+        }
+        target.activities.extend(instance.activities);
+        target.place_labels = target.place_labels.take().or(instance.place_labels);
+        target.time_labels = target.time_labels.take().or(instance.time_labels);
+        target
+            .constraints
+            .locked
+            .extend(instance.constraints.locked);
+        target
+            .constraints
+            .forbidden
+            .extend(instance.constraints.forbidden);
+    }
+    Ok(merged)
+}
+
+/// Width of the `--progress` bar, in characters.
+#[cfg(feature = "progress")]
+const PROGRESS_WIDTH: usize = 30;
+
+/// Whether to draw the `--progress` bar: never over machine-readable output,
+/// when stderr is not a terminal, or without the `progress` feature.
+fn progress_enabled(args: &Args, stderr_is_terminal: bool) -> bool {
+    cfg!(feature = "progress") && args.progress && stderr_is_terminal && !(args.json || args.jsonl)
+}
+
+/// Render one `--progress` line: a bar filling toward the timeout, if any,
+/// then the elapsed time and the best penalty so far.
+#[cfg(feature = "progress")]
+fn progress_bar(elapsed: Duration, timeout: Option<Duration>, best: &str) -> String {
+    let secs = elapsed.as_secs_f32();
+    match timeout {
+        Some(timeout) => {
+            let fraction = (secs / timeout.as_secs_f32().max(f32::EPSILON)).min(1.0);
+            let filled = (fraction * PROGRESS_WIDTH as f32).round() as usize;
+            format!(
+                "[{}{}] {secs:.1}s/{}s best {best}",
+                "#".repeat(filled),
+                "-".repeat(PROGRESS_WIDTH - filled),
+                timeout.as_secs(),
+            )
+        }
+        None => format!("[{secs:.1}s] best {best}"),
+    }
+}
+
+/// Summarize where one run spent its time, as shares of its wall-clock time.
+fn profile_line(id: &str, run: usize, profile: &Profile) -> String {
+    let total = profile.total.as_secs_f64();
+    let share = |part: Duration| {
+        if total > 0.0 {
+            100.0 * part.as_secs_f64() / total
+        } else {
+            0.0
+        }
+    };
+    format!(
+        "profile: {id} run {run}: {:.3}s, {:.1}% evaluating, {:.1}% swapping, {:.1}% reshuffling",
+        total,
+        share(profile.evaluating),
+        share(profile.swapping()),
+        share(profile.shuffling),
+    )
+}
+
+/// Apply the search options from the command line to an improver.
+/// A penalty as a single number, for the sampling algorithms.
+trait Energy {
+    fn energy(&self) -> f64;
+}
+
+impl Energy for (usize, f32) {
+    fn energy(&self) -> f64 {
+        f64::from(combined_penalty(*self))
+    }
+}
+
+impl Energy for Result<(usize, f32), Infeasible> {
+    fn energy(&self) -> f64 {
+        // Infeasible swaps are never kept, whatever their energy
+        self.as_ref().map_or(f64::INFINITY, Energy::energy)
+    }
+}
+
+fn configure_improver<'a, F, P>(
+    mut improver: Improver<'a, Activity, F, P>,
+    args: &Args,
+) -> Improver<'a, Activity, F, P>
+where
+    F: Fn(&Schedule<Activity>) -> P,
+    P: Copy + PartialOrd + Debug + Energy,
+{
+    if let Some(nswaps) = args.nswaps {
+        improver = improver.max_swaps(nswaps);
+    }
+    improver = match args.algorithm() {
+        Algorithm::Hill => improver,
+        Algorithm::Noise => improver.noise_prob(args.noise_prob),
+        Algorithm::Tabu => improver.tabu(args.tabu_tenure),
+        Algorithm::Annealing => {
+            let rule = Acceptance::Annealing {
+                initial_temperature: args.init_temp,
+                cooling: args.cooling,
+            };
+            improver.acceptance(rule, P::energy)
+        }
+        Algorithm::GreatDeluge => {
+            improver.acceptance(Acceptance::GreatDeluge { rain: args.rain }, P::energy)
+        }
+        Algorithm::Lahc => {
+            let history = usize::try_from(args.history).unwrap_or(usize::MAX);
+            improver.acceptance(Acceptance::Late { history }, P::energy)
+        }
+    };
+    if let Some(restarts) = args.restarts {
+        if args.proportional {
+            improver = improver.restarts_proportional(restarts);
+        } else {
+            improver = improver.restarts(restarts);
+        }
+    }
+    if let Some(timeout_secs) = args.timeout {
+        improver = improver.timeout(Duration::from_secs(timeout_secs));
+    }
+    if let Some(seed) = args.seed {
+        improver = improver.with_rng_seed(seed);
+    }
+    if let Some(k) = args.select {
+        improver = improver.target_scheduled(k);
+    }
+    improver
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    run(&args, &mut io::stdout().lock(), io::stderr().is_terminal())
+}
+
+/// Solve every instance `args` names, writing the results to `out`.
+fn run(
+    args: &Args,
+    out: &mut impl Write,
+    stderr_is_terminal: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.progress && !cfg!(feature = "progress") {
+        eprintln!("warning: built without the progress feature, so --progress draws nothing");
+    }
+    let mut penalty_config = PenaltyConfig::from_args(args);
+    if let Some(path) = &args.weights {
+        penalty_config.weights = load_weights(path)?;
+    }
+    penalty_config.weights = penalty_config.weights.override_from(args);
+    let mut topics = TopicInterner::default();
+    penalty_config.not_adjacent = args
+        .not_adjacent
+        .iter()
+        .map(|(a, b)| (topics.intern(a.clone()), topics.intern(b.clone())))
+        .collect();
+    // The penalty closure owns one scratch buffer for the whole run
+    let scratch = RefCell::new(PenaltyScratch::default());
+    let penalty = |s: &Schedule<Activity>| {
+        activity_penalty_with(s, &penalty_config, &mut scratch.borrow_mut())
+    };
+    let constrained =
+        |s: &Schedule<Activity>| feasible_penalty(s, &penalty_config, &mut scratch.borrow_mut());
+
+    let instances: Vec<SchedulingInstance<Activity>> =
+        load_instances(&args.instances_files, args.merge)?
+            .into_iter()
+            .map(|instance| topics.instance(instance))
+            .collect();
+    let budget = args
+        .total_timeout
+        .map(|secs| TimeBudget::new(Duration::from_secs(secs), args.allocate, &instances));
+    let started = Instant::now();
+
+    let mut results = Vec::new();
+
+    for (index, instance) in instances.into_iter().enumerate() {
+        instance
+            .validate()
+            .map_err(|e| format!("instance {}: {e}", instance.id))?;
+        for warning in instance.warnings() {
+            eprintln!("warning: instance {}: {warning}", instance.id);
+        }
+        let mut activities = instance.activities;
+        let has_unavailable = activities.iter().any(|a| !a.unavailable_times.is_empty());
+        if args.greedy_init {
+            // The least important activities overflow into the unscheduled list
+            activities.sort_by_key(Activity::sort_key);
+        }
+        let mut schedule = Schedule::new(instance.nplaces, instance.ntimes, activities.into_iter())
+            .with_buffer_times(instance.buffer_times)
+            .with_constraints(instance.constraints);
+        if let Some(labels) = instance.place_labels {
+            schedule = schedule.with_place_labels(labels);
+        }
+        if let Some(labels) = instance.time_labels {
+            schedule = schedule.with_time_labels(labels);
+        }
+
+        let (initial_unscheduled, initial_other_penalty) = penalty(&schedule);
+        let initial = schedule.clone();
+
+        // Use the new builder API
+        let mut improver = configure_improver(schedule.improve_feasible(constrained), args);
+        if has_unavailable {
+            improver = improver.allowed_slots(|a: &Activity, _, t| a.available_at(t));
+        }
+        let slice = budget
+            .as_ref()
+            .map(|budget| budget.slice(index, started.elapsed()));
+        if let Some(slice) = slice {
+            improver = improver.timeout(slice);
+        }
+        let show_progress = progress_enabled(args, stderr_is_terminal);
+        #[cfg(feature = "progress")]
+        if show_progress {
+            let timeout = slice.or(args.timeout.map(Duration::from_secs));
+            improver = improver.on_progress(move |progress| {
+                let best = match progress.penalty {
+                    Ok((unscheduled, other)) => format!("{unscheduled} unscheduled, {other:.2}"),
+                    Err(Infeasible(violations)) => format!("{violations} violations"),
+                };
+                eprint!("\r{}", progress_bar(progress.elapsed, timeout, &best));
+            });
+        }
+        if args.profile {
+            let mut steps = improver.profile().steps();
+            steps.by_ref().for_each(drop);
+            for (run, profile) in steps.run_profiles().iter().enumerate() {
+                eprintln!("{}", profile_line(&instance.id, run, profile));
+            }
+        } else {
+            improver.run();
+        }
+        if show_progress {
+            // Clear the bar before any results are printed
+            eprint!("\r\x1b[K");
+        }
+        if let Err(Infeasible(violations)) = constrained(&schedule) {
+            eprintln!(
+                "warning: instance {}: {violations} violations of --max-per-topic or --not-adjacent",
+                instance.id
+            );
+        }
+
+        let (final_unscheduled, final_other_penalty) = penalty(&schedule);
+        let unscheduled_improvement = initial_unscheduled as i32 - final_unscheduled as i32;
+        let other_improvement = initial_other_penalty - final_other_penalty;
+        let normalized_penalty =
+            schedule.normalized_penalty(&initial, |s| combined_penalty(penalty(s)));
+        let final_breakdown =
+            penalty_breakdown(&schedule, &penalty_config, &mut scratch.borrow_mut());
+
+        if args.json || args.jsonl {
+            let result = RunResult {
+                instance_id: instance.id,
+                initial_unscheduled,
+                initial_other_penalty,
+                final_unscheduled,
+                final_other_penalty,
+                unscheduled_improvement,
+                other_improvement,
+                normalized_penalty,
+                final_breakdown,
+                config: RunConfig {
+                    noise: args.algorithm() == Algorithm::Noise,
+                    restarts: args.restarts,
+                    proportional: args.proportional,
+                    timeout: args.timeout,
+                    total_timeout: args.total_timeout,
+                    nswaps: args.nswaps,
+                    seed: args.seed,
+                },
+                schedule: args.emit_schedule.then(|| schedule.clone()),
+            };
+            if args.jsonl {
+                write_jsonl(out, &result)?;
+            } else {
+                results.push(result);
+            }
+        } else if args.score_only {
+            writeln!(
+                out,
+                "{}",
+                score_line(&instance.id, final_unscheduled, final_other_penalty)
+            )?;
+        } else {
+            writeln!(
+                out,
+                "{} unscheduled:{}->{} other:{:.2}->{:.2} improvements:{},{:.2}",
+                instance.id,
+                initial_unscheduled,
+                final_unscheduled,
+                initial_other_penalty,
+                final_other_penalty,
+                unscheduled_improvement,
+                other_improvement
+            )?;
+            if !args.explain {
+                writeln!(out, "{}", final_breakdown.summary_line())?;
+            }
+            let label = |a: &Activity| format!("{}:{}", topics.label(a.topic), a.priority);
+            match args.format {
+                Format::Text => (),
+                Format::Grid => write!(
+                    out,
+                    "{}",
+                    to_ascii_grid(&schedule, args.column_width, label)
+                )?,
+                Format::Csv => write_csv(&schedule, out, label)?,
+            }
+            if args.explain {
+                let mut scratch = scratch.borrow_mut();
+                write!(out, "{}", explain(&schedule, &penalty_config, &mut scratch))?;
+                for conflict in conflicts(&schedule, &penalty_config) {
+                    writeln!(out, "  conflict: {}", conflict.describe(&topics))?;
+                }
+            }
+        }
+    }
+
+    if args.json {
+        writeln!(out, "{}", serde_json::to_string_pretty(&results)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(topic: usize, secondary_topics: &[usize]) -> Activity {
+        Activity {
+            priority: 1,
+            topic,
+            secondary_topics: secondary_topics.to_vec(),
+            ..Activity::default()
+        }
+    }
+
+    #[test]
+    fn dual_topic_conflicts_with_both_topics() {
+        let dual = activity(1, &[2]);
+        let alone = topic_conflict([&dual].into_iter());
+        assert_eq!(alone, 2.0);
+
+        // Sharing either topic with a neighbor raises the conflict equally
+        let with_first = topic_conflict([&dual, &activity(1, &[])].into_iter());
+        let with_second = topic_conflict([&dual, &activity(2, &[])].into_iter());
+        let with_neither = topic_conflict([&dual, &activity(3, &[])].into_iter());
+        assert_eq!(with_first, with_second);
+        assert!(with_first > with_neither);
+    }
+
+    #[test]
+    fn normalized_penalty_counts_unscheduled_activities_heavily() {
+        let config = PenaltyConfig::default();
+        let penalty = |s: &Schedule<Activity>| combined_penalty(activity_penalty(s, &config));
+        let activities = || (1..=3).map(|topic| activity(topic, &[]));
+        let initial = Schedule::new(1, 2, activities());
+        assert_eq!(initial.normalized_penalty(&initial, penalty), 1.0);
+
+        // Scheduling the one left-out activity removes nearly all the penalty
+        let roomier = Schedule::new(1, 3, activities());
+        assert!(roomier.normalized_penalty(&initial, penalty) < 0.05);
+    }
+
+    #[test]
+    fn jsonl_writes_one_object_per_line() {
+        let mut out = Vec::new();
+        for id in ["a", "b", "c"] {
+            let result = RunResult {
+                instance_id: id.to_string(),
+                initial_unscheduled: 4,
+                initial_other_penalty: 10.0,
+                final_unscheduled: 4,
+                final_other_penalty: 5.0,
+                unscheduled_improvement: 0,
+                other_improvement: 5.0,
+                normalized_penalty: 0.5,
+                final_breakdown: PenaltyBreakdown::default(),
+                config: RunConfig {
+                    noise: false,
+                    restarts: None,
+                    proportional: false,
+                    timeout: None,
+                    total_timeout: None,
+                    nswaps: None,
+                    seed: None,
+                },
+                schedule: None,
+            };
+            write_jsonl(&mut out, &result).unwrap();
+        }
+
+        let text = String::from_utf8(out).unwrap();
+        let ids: Vec<String> = text
+            .lines()
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line).unwrap();
+                value["instance_id"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn score_line_is_id_and_numbers() {
+        let line = score_line("instance_007", 3, 12.345);
+        assert_eq!(line, "instance_007 3 12.35");
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn select_leaves_lowest_priorities_unscheduled() {
+        let activities = (1..=6).map(|priority| Activity {
+            priority,
+            topic: priority,
+            ..Activity::default()
+        });
+        let mut schedule = Schedule::new(2, 2, activities);
+        let config = PenaltyConfig {
+            select: Some(2),
+            ..PenaltyConfig::default()
+        };
+        let penalty = |s: &Schedule<Activity>| activity_penalty(s, &config);
+        schedule
+            .improve(penalty)
+            .target_scheduled(2)
+            .restarts(5)
+            .with_rng_seed(3)
+            .run();
+
+        assert_eq!(penalty(&schedule).0, 0);
+        let mut unscheduled: Vec<usize> = schedule
+            .get_unscheduled_activities()
+            .map(|a| a.priority)
+            .collect();
+        unscheduled.sort();
+        assert_eq!(unscheduled, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn greedy_init_and_select_agree_on_importance() {
+        let mut rng = fastrand::Rng::with_seed(8);
+        let mut activities: Vec<Activity> = (1..=6)
+            .map(|priority| Activity {
+                priority,
+                topic: priority,
+                ..Activity::default()
+            })
+            .collect();
+        rng.shuffle(&mut activities);
+        let mut by_importance = activities.clone();
+        by_importance.sort_by_key(Activity::sort_key);
+        let most_important = &by_importance[..2];
+
+        // Greedy init schedules the first two by sort key
+        let greedy = Schedule::new(2, 1, by_importance.iter().cloned());
+        let mut scheduled: Vec<Activity> = greedy.slots().iter().flatten().cloned().collect();
+        scheduled.sort_by_key(Activity::sort_key);
+        assert_eq!(scheduled, most_important);
+
+        // K-selection from an unsorted start keeps the same two
+        let mut schedule = Schedule::new(2, 2, activities.into_iter());
+        let config = PenaltyConfig {
+            select: Some(2),
+            ..PenaltyConfig::default()
+        };
+        let penalty = |s: &Schedule<Activity>| activity_penalty(s, &config);
+        schedule
+            .improve(penalty)
+            .target_scheduled(2)
+            .restarts(5)
+            .with_rng_seed(3)
+            .run();
+        let mut selected: Vec<Activity> = schedule.slots().iter().flatten().cloned().collect();
+        selected.sort_by_key(Activity::sort_key);
+        assert_eq!(selected, most_important);
+    }
+
+    #[test]
+    fn room_churn_pulls_topic_sessions_together() {
+        // Two topic-1 sessions in consecutive slots plus unrelated fillers
+        let activities = [1, 2, 3, 4, 5, 1].map(|topic| activity(topic, &[]));
+        let mut schedule = Schedule::new(3, 2, activities.into_iter());
+        let before = room_churn(&schedule);
+
+        let config = PenaltyConfig {
+            room_churn: 100.0,
+            ..PenaltyConfig::default()
+        };
+        schedule
+            .improve(|s: &Schedule<Activity>| activity_penalty(s, &config))
+            .run();
+        assert!(room_churn(&schedule) < before);
+    }
+
+    #[test]
+    fn single_topic_json_still_parses() {
+        let a: Activity = serde_json::from_str(r#"{"priority": 1, "topic": 4}"#).unwrap();
+        assert_eq!(a, activity(4, &[]));
+    }
+
+    #[test]
+    fn lateness_grows_with_time_not_place() {
+        let config = PenaltyConfig::default();
+        let penalty_at = |cell: (usize, usize)| {
+            let important = Activity {
+                priority: 10,
+                ..activity(1, &[])
+            };
+            let mut schedule = Schedule::new(2, 2, std::iter::once(important));
+            schedule.with_slots_mut(|mut slots| slots.swap((0, 0), cell));
+            activity_penalty(&schedule, &config)
+        };
+        // A later room costs nothing more, a later time slot does
+        assert_eq!(penalty_at((1, 0)), penalty_at((0, 0)));
+        assert!(penalty_at((0, 1)) > penalty_at((0, 0)));
+    }
+
+    #[test]
+    fn empty_buffer_slots_are_not_penalized() {
+        let activities = (1..=4).map(|priority| Activity {
+            priority,
+            topic: priority,
+            ..Activity::default()
+        });
+        let config = PenaltyConfig::default();
+
+        // 2 places x 3 times with the last time slot as buffer: the 4
+        // activities exactly fill the non-buffer slots once improved
+        let mut schedule = Schedule::new(2, 3, activities).with_buffer_times(1);
+        let penalty = |s: &Schedule<Activity>| activity_penalty(s, &config);
+        schedule.improve(penalty).with_rng_seed(7).run();
+
+        let (hard, _) = activity_penalty(&schedule, &config);
+        assert_eq!(hard, 0);
+        assert_eq!(schedule.empty_slots_count(), 0);
+        let buffer = schedule.slots().column(2).iter().flatten().count();
+        assert_eq!(buffer, 0);
+    }
+
+    #[test]
+    fn named_topics_conflict_like_numeric_ones() {
+        let parse = |json: &str| {
+            let inputs: Vec<ActivityInput> = serde_json::from_str(json).unwrap();
+            let mut topics = TopicInterner::default();
+            let activities: Vec<Activity> =
+                inputs.into_iter().map(|a| topics.activity(a)).collect();
+            topic_conflict(activities.iter())
+        };
+
+        let named = parse(
+            r#"[{"priority": 1, "topic": "databases"},
+                {"priority": 2, "topic": "databases"},
+                {"priority": 3, "topic": "compilers", "secondary_topics": [7]}]"#,
+        );
+        let numeric = parse(
+            r#"[{"priority": 1, "topic": 1},
+                {"priority": 2, "topic": 1},
+                {"priority": 3, "topic": 2, "secondary_topics": [7]}]"#,
+        );
+        assert_eq!(named, numeric);
+        assert_eq!(named, 4.0 + 1.0 + 1.0);
+    }
+
+    #[test]
+    fn room_gaps_pack_sessions_together() {
+        let a = || Some(activity(1, &[]));
+        // Place 0 has two gaps, place 1 one
+        let start = Schedule::try_from(vec![
+            vec![a(), None, a(), None, a(), None],
+            vec![None, a(), None, a(), None, None],
+        ])
+        .unwrap();
+        assert_eq!(room_gaps(&start), 3);
+        assert_eq!(row_gaps(&[None, a(), None, None, a(), a(), None]), 2);
+
+        // Without lateness nothing else cares where the sessions go
+        let weights = PenaltyWeights {
+            lateness: 0.0,
+            ..PenaltyWeights::default()
+        };
+        let solve = |weight| {
+            let config = PenaltyConfig {
+                weights,
+                room_gaps: weight,
+                ..PenaltyConfig::default()
+            };
+            let mut schedule = start.clone();
+            schedule
+                .improve(|s: &Schedule<Activity>| activity_penalty(s, &config))
+                .run();
+            room_gaps(&schedule)
+        };
+        assert_eq!(solve(0.0), 3);
+        assert_eq!(solve(1.0), 0);
+    }
+
+    #[test]
+    fn transition_cost_separates_related_activities() {
+        let activities = [1, 1, 2, 3].map(|topic| Activity {
+            priority: 1,
+            topic,
+            ..Activity::default()
+        });
+        // Place 0 starts with the two topic-1 activities back to back
+        let mut schedule = Schedule::new(2, 2, activities.into_iter());
+        let related = |a: &Activity, b: &Activity| f32::from(u8::from(share_topic(a, b)));
+        assert_eq!(schedule.transition_cost(related), 1.0);
+
+        let config = PenaltyConfig {
+            transition_cost: 1000.0,
+            ..PenaltyConfig::default()
+        };
+        let penalty = |s: &Schedule<Activity>| activity_penalty(s, &config);
+        schedule.improve(penalty).with_rng_seed(3).run();
+        assert_eq!(schedule.transition_cost(related), 0.0);
+    }
+
+    #[test]
+    fn loading_two_files_merges_instances() {
+        let dir = std::env::temp_dir().join(format!("conference-merge-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, json: &str| {
+            let path = dir.join(name);
+            fs::write(&path, json).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        let instance = |id: &str, nplaces: usize, topics: &[usize]| {
+            let activities: Vec<String> = topics
+                .iter()
+                .map(|t| format!(r#"{{"priority": 1, "topic": {t}}}"#))
+                .collect();
+            format!(
+                r#"{{"id": "{id}", "nplaces": {nplaces}, "ntimes": 2, "activities": [{}]}}"#,
+                activities.join(",")
+            )
+        };
+        let first = write(
+            "first.json",
+            &format!("[{}, {}]", instance("a", 2, &[1]), instance("b", 2, &[2])),
+        );
+        let second = write(
+            "second.json",
+            &format!(
+                "[{}, {}]",
+                instance("a", 2, &[3, 4]),
+                instance("c", 1, &[5])
+            ),
+        );
+        let paths = [first, second];
+
+        // Without merging, the lists are just concatenated
+        let ids: Vec<String> = load_instances(&paths, false)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        assert_eq!(ids, ["a", "b", "a", "c"]);
+
+        // Merging takes the union, folding the two "a" instances together
+        let merged = load_instances(&paths, true).unwrap();
+        let ids: Vec<&str> = merged.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        let topics: Vec<TopicLabel> = merged[0]
+            .activities
+            .iter()
+            .map(|a| a.topic.clone())
+            .collect();
+        assert_eq!(topics, [1, 3, 4].map(TopicLabel::Id));
+
+        // Same id with a different grid is an error
+        let clash = write("clash.json", &format!("[{}]", instance("b", 3, &[6])));
+        assert!(load_instances(&[paths[0].clone(), clash], true).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fairness_spreads_topics_across_times() {
+        // One place, four times: lateness alone puts the popular topic first
+        let activities = [(1, 50), (1, 50), (2, 1), (2, 1)].map(|(topic, priority)| Activity {
+            priority,
+            topic,
+            ..Activity::default()
+        });
+        let start = Schedule::new(1, 4, activities.into_iter());
+
+        let solve = |fairness: f32| {
+            let config = PenaltyConfig {
+                fairness,
+                ..PenaltyConfig::default()
+            };
+            let penalty = |s: &Schedule<Activity>| activity_penalty(s, &config);
+            let mut schedule = start.clone();
+            schedule.improve(penalty).with_rng_seed(5).run();
+            topic_time_spread(&schedule)
+        };
+
+        let unfair = solve(0.0);
+        let fair = solve(1000.0);
+        assert_eq!(unfair, 1.0);
+        assert!(fair < unfair);
+    }
+
+    #[test]
+    fn place_preference_pulls_without_forbidding() {
+        // Three rooms, one time: both activities want room 2, which starts empty
+        let wants_room_2 = |priority| Activity {
+            priority,
+            topic: priority,
+            preferred_place: Some(2),
+            ..Activity::default()
+        };
+        let mut schedule = Schedule::new(3, 1, [wants_room_2(1), wants_room_2(2)].into_iter());
+        let config = PenaltyConfig {
+            preference_weight: 100.0,
+            ..PenaltyConfig::default()
+        };
+        let penalty = |s: &Schedule<Activity>| activity_penalty(s, &config);
+        schedule.improve(penalty).with_rng_seed(2).run();
+
+        // One gets room 2; the other still gets scheduled next door
+        assert_eq!(schedule.get_unscheduled_activities().count(), 0);
+        assert!(schedule.get_activity_at(0, 0).unwrap().is_none());
+        assert!(schedule.get_activity_at(1, 0).unwrap().is_some());
+        assert!(schedule.get_activity_at(2, 0).unwrap().is_some());
+    }
+
+    #[test]
+    fn scratch_penalty_matches_allocating_penalty() {
+        let mut rng = fastrand::Rng::with_seed(11);
+        let activities: Vec<Activity> = (0..30)
+            .map(|i| Activity {
+                priority: rng.usize(1..50),
+                topic: rng.usize(1..6),
+                secondary_topics: if i % 4 == 0 {
+                    vec![rng.usize(1..6)]
+                } else {
+                    Vec::new()
+                },
+                preferred_place: (i % 5 == 0).then(|| rng.usize(0..4)),
+                ..Activity::default()
+            })
+            .collect();
+        let config = PenaltyConfig {
+            room_churn: 2.0,
+            transition_cost: 3.0,
+            room_gaps: 4.0,
+            fairness: 5.0,
+            preference_weight: 1.5,
+            ..PenaltyConfig::default()
+        };
+
+        let mut schedule = Schedule::new(4, 6, activities.into_iter());
+        let cells: Vec<_> = schedule.all_cells().collect();
+        let mut scratch = PenaltyScratch::default();
+        for _ in 0..200 {
+            let (a, b) = (
+                cells[rng.usize(..cells.len())],
+                cells[rng.usize(..cells.len())],
+            );
+            schedule.swap(a, b).unwrap();
+            assert_eq!(
+                activity_penalty_with(&schedule, &config, &mut scratch),
+                activity_penalty(&schedule, &config)
+            );
+        }
+    }
+
+    #[test]
+    fn cached_column_conflicts_match_fresh_penalty() {
+        let mut rng = fastrand::Rng::with_seed(17);
+        let mut activity = |i: usize| Activity {
+            priority: rng.usize(1..50),
+            topic: rng.usize(1..5),
+            secondary_topics: if i % 3 == 0 {
+                vec![rng.usize(1..5)]
+            } else {
+                Vec::new()
+            },
+            ..Activity::default()
+        };
+        let activities: Vec<Activity> = (0..30).map(&mut activity).collect();
+        let small: Vec<Activity> = (0..8).map(&mut activity).collect();
+        let config = PenaltyConfig::default();
+        let reweighted = PenaltyConfig {
+            weights: PenaltyWeights {
+                priority_conflict: 3.0,
+                topic_conflict: 0.5,
+                ..PenaltyWeights::default()
+            },
+            ..PenaltyConfig::default()
+        };
+
+        let mut schedule = Schedule::new(4, 6, activities.into_iter());
+        let other = Schedule::new(2, 3, small.into_iter());
+        let cells: Vec<_> = schedule.all_cells().collect();
+        let mut scratch = PenaltyScratch::default();
+        for step in 0..300 {
+            let (a, b) = (
+                cells[rng.usize(..cells.len())],
+                cells[rng.usize(..cells.len())],
+            );
+            schedule.swap(a, b).unwrap();
+            // The cache holds unweighted conflicts, so any weights can use it,
+            // and another schedule sharing the buffers only refills it
+            let config = if step % 3 == 0 { &reweighted } else { &config };
+            assert_eq!(
+                penalty_breakdown(&schedule, config, &mut scratch),
+                penalty_breakdown(&schedule, config, &mut PenaltyScratch::default()),
+                "step {step}"
+            );
+            if step % 7 == 0 {
+                assert_eq!(
+                    activity_penalty_with(&other, config, &mut scratch),
+                    activity_penalty(&other, config)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn axis_penalties_add_up_to_total() {
+        let mut rng = fastrand::Rng::with_seed(23);
+        let activities: Vec<Activity> = (0..14)
+            .map(|i| Activity {
+                priority: rng.usize(1..20),
+                topic: rng.usize(1..4),
+                preferred_time: (i % 3 == 0).then(|| rng.usize(0..4)),
+                ..Activity::default()
+            })
+            .collect();
+        let config = PenaltyConfig {
+            room_churn: 2.0,
+            transition_cost: 3.0,
+            room_gaps: 4.0,
+            fairness: 5.0,
+            preference_weight: 1.5,
+            ..PenaltyConfig::default()
+        };
+        let schedule = Schedule::new(3, 4, activities.into_iter()).with_buffer_times(1);
+        let mut scratch = PenaltyScratch::default();
+        let (_, total) = activity_penalty(&schedule, &config);
+
+        let missed: f32 = schedule
+            .get_unscheduled_activities()
+            .map(|a| a.priority as f32)
+            .sum();
+        let related = |a: &Activity, b: &Activity| f32::from(u8::from(share_topic(a, b)));
+        let spanning = missed
+            + config.room_churn * room_churn(&schedule)
+            + config.fairness * topic_time_spread(&schedule);
+        let transitions = config.transition_cost * schedule.transition_cost(related)
+            + config.room_gaps * room_gaps(&schedule) as f32;
+        let conflicts: f32 = schedule
+            .slots()
+            .axis_iter(Axis(1))
+            .map(|column| time_slot_conflict(column, &config.weights, &mut scratch))
+            .sum();
+
+        let columns = column_penalties(&schedule, &config, &mut scratch);
+        assert_eq!(columns.len(), 4);
+        let by_time = columns.iter().sum::<f32>() + spanning + transitions;
+        assert!((by_time - total).abs() < 1e-3, "{by_time} != {total}");
+
+        let rows = row_penalties(&schedule, &config);
+        assert_eq!(rows.len(), 3);
+        let by_place = rows.iter().sum::<f32>() + spanning + conflicts;
+        assert!((by_place - total).abs() < 1e-3, "{by_place} != {total}");
+    }
+
+    #[test]
+    fn topic_cap_holds_after_improve() {
+        // Place-major filling puts all three topic-1 sessions at time 0
+        let activities = [1, 2, 3, 1, 2, 3, 1, 2, 3].map(|topic| Activity {
+            priority: topic * 10,
+            topic,
+            ..Activity::default()
+        });
+        let start = Schedule::new(3, 3, activities.into_iter());
+        let config = PenaltyConfig {
+            max_per_topic: Some(1),
+            ..PenaltyConfig::default()
+        };
+        let mut counts = HashMap::new();
+        assert_eq!(topic_cap_excess(&start, 1, &mut counts), 6);
+
+        for seed in 0..5 {
+            let mut schedule = start.clone();
+            let scratch = RefCell::new(PenaltyScratch::default());
+            let penalty =
+                |s: &Schedule<Activity>| feasible_penalty(s, &config, &mut scratch.borrow_mut());
+            schedule
+                .improve_feasible(penalty)
+                .with_noise()
+                .max_swaps(200)
+                .with_rng_seed(seed)
+                .run();
+            assert_eq!(topic_cap_excess(&schedule, 1, &mut counts), 0);
+        }
+    }
+
+    #[test]
+    fn total_timeout_stays_near_its_budget() {
+        let instance = |id: &str, nplaces, activities: Vec<Activity>| SchedulingInstance {
+            id: id.to_string(),
+            nplaces,
+            ntimes: 4,
+            activities,
+            buffer_times: 0,
+            constraints: Default::default(),
+            place_labels: None,
+            time_labels: None,
+        };
+        let packed = instance("packed", 2, vec![activity(1, &[]); 8]);
+        let sparse = |id| instance(id, 4, (0..4).map(|t| activity(t, &[])).collect());
+        let instances = [packed, sparse("sparse-a"), sparse("sparse-b")];
+        let total = Duration::from_secs(9);
+        // Every instance runs a little past its timeout before stopping
+        let overrun = Duration::from_millis(20);
+
+        for allocation in [Allocation::Even, Allocation::Difficulty] {
+            let budget = TimeBudget::new(total, allocation, &instances);
+            let mut spent = Duration::ZERO;
+            let mut slices = Vec::new();
+            for index in 0..instances.len() {
+                let slice = budget.slice(index, spent);
+                slices.push(slice);
+                spent += slice + overrun;
+            }
+            // Shares are computed in f32, so allow for rounding
+            let slack = Duration::from_millis(1);
+            assert!(
+                spent + slack >= total && spent <= total + overrun + slack,
+                "{allocation:?}: {spent:?}"
+            );
+            if allocation == Allocation::Difficulty {
+                assert!(slices[0] > slices[1], "{slices:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn not_adjacent_topics_stay_apart_after_improve() {
+        // Row-major filling puts every blacklisted pair side by side
+        let activities = [1, 2, 3, 4, 5, 6].map(|topic| Activity {
+            priority: topic * 10,
+            topic,
+            ..Activity::default()
+        });
+        let start = Schedule::new(2, 3, activities.into_iter());
+        let pairs = vec![(1, 2), (3, 4), (5, 6)];
+        assert_eq!(forbidden_neighbors(&start, &pairs), 2);
+        let config = PenaltyConfig {
+            not_adjacent: pairs.clone(),
+            ..PenaltyConfig::default()
+        };
+
+        for seed in 0..5 {
+            let mut schedule = start.clone();
+            let scratch = RefCell::new(PenaltyScratch::default());
+            let penalty =
+                |s: &Schedule<Activity>| feasible_penalty(s, &config, &mut scratch.borrow_mut());
+            schedule
+                .improve_feasible(penalty)
+                .with_noise()
+                .max_swaps(200)
+                .with_rng_seed(seed)
+                .run();
+            assert_eq!(forbidden_neighbors(&schedule, &pairs), 0);
+            assert!(conflicts(&schedule, &config).is_empty());
+        }
+    }
+
+    #[test]
+    fn explained_components_sum_to_total() {
+        let mut rng = fastrand::Rng::with_seed(31);
+        let activities: Vec<Activity> = (0..20)
+            .map(|i| Activity {
+                priority: rng.usize(1..30),
+                topic: rng.usize(1..5),
+                preferred_place: (i % 4 == 0).then(|| rng.usize(0..3)),
+                ..Activity::default()
+            })
+            .collect();
+        let config = PenaltyConfig {
+            room_churn: 1.0,
+            transition_cost: 2.0,
+            room_gaps: 2.5,
+            fairness: 3.0,
+            preference_weight: 1.0,
+            ..PenaltyConfig::default()
+        };
+        let schedule = Schedule::new(3, 5, activities.into_iter());
+        let text = explain(&schedule, &config, &mut PenaltyScratch::default());
+
+        let value = |line: &str| line.rsplit(": ").next().unwrap().parse::<f32>().unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        let total_at = lines
+            .iter()
+            .position(|l| l.starts_with("  total: "))
+            .unwrap();
+        assert_eq!(total_at, 10);
+        let components: f32 = lines[..total_at].iter().map(|l| value(l)).sum();
+        let total = value(lines[total_at]);
+        assert!((components - total).abs() < 0.05, "{components} != {total}");
+        assert!((activity_penalty(&schedule, &config).1 - total).abs() < 0.01);
+
+        assert!(lines[total_at + 1].starts_with("  worst time slots: t"));
+        assert_eq!(lines[total_at + 1].matches(", ").count(), 2);
+        assert!(lines[total_at + 2].starts_with("  worst places: p"));
+    }
+
+    #[test]
+    fn drop_weight_keeps_contractual_session_scheduled() {
+        // Three sessions for two slots; the sponsor session has the lowest
+        // priority but must not be dropped
+        let session = |priority, drop_weight| Activity {
+            priority,
+            topic: priority,
+            drop_weight,
+            ..Activity::default()
+        };
+        let penalty = |s: &Schedule<Activity>| activity_penalty(s, &PenaltyConfig::default());
+        let dropped = |sponsor_weight| {
+            let sessions = [
+                session(5, None),
+                session(6, None),
+                session(1, sponsor_weight),
+            ];
+            let mut schedule = Schedule::new(2, 1, sessions.into_iter());
+            schedule.improve(penalty).with_rng_seed(4).run();
+            let dropped: Vec<usize> = schedule
+                .get_unscheduled_activities()
+                .map(|a| a.priority)
+                .collect();
+            dropped
+        };
+
+        assert_eq!(dropped(Some(1000.0)), [5]);
+        // Without the weight it is the first to go
+        assert_eq!(dropped(None), [1]);
+    }
+
+    #[test]
+    fn unavailable_speaker_keeps_out_of_their_slot() {
+        // The keynote would lead the day, but its speaker arrives late
+        let session = |priority, unavailable_times| Activity {
+            priority,
+            topic: priority,
+            unavailable_times,
+            ..Activity::default()
+        };
+        let sessions = [session(1, vec![]), session(2, vec![]), session(9, vec![0])];
+        let penalty = |s: &Schedule<Activity>| activity_penalty(s, &PenaltyConfig::default());
+        let mut schedule = Schedule::new(1, 3, sessions.into_iter());
+        schedule
+            .improve(penalty)
+            .allowed_slots(|a: &Activity, _, t| a.available_at(t))
+            .restarts(3)
+            .with_rng_seed(2)
+            .run();
+        let keynote = schedule
+            .iter_scheduled()
+            .find(|(_, _, a)| a.priority == 9)
+            .map(|(_, t, _)| t);
+        assert_eq!(keynote, Some(1));
+        assert!(schedule.iter_scheduled().all(|(_, t, a)| a.available_at(t)));
+    }
+
+    #[test]
+    fn clashing_schedule_reports_its_conflicts() {
+        // Place 0 holds topics 1 then 2; place 1 holds topic 1 then a
+        // topic-3 session that also covers topic 1
+        let activities = [
+            activity(1, &[]),
+            activity(2, &[]),
+            activity(1, &[]),
+            activity(3, &[1]),
+        ];
+        let schedule = Schedule::new(2, 2, activities.into_iter());
+
+        let soft_only = PenaltyConfig::default();
+        assert_eq!(
+            conflicts(&schedule, &soft_only),
+            [Conflict::TopicClash {
+                time: 0,
+                topic: 1,
+                count: 2
+            }]
+        );
+
+        let strict = PenaltyConfig {
+            max_per_topic: Some(1),
+            transition_cost: 1.0,
+            ..PenaltyConfig::default()
+        };
+        assert_eq!(
+            conflicts(&schedule, &strict),
+            [
+                Conflict::TopicClash {
+                    time: 0,
+                    topic: 1,
+                    count: 2
+                },
+                Conflict::TopicCapExceeded {
+                    time: 0,
+                    topic: 1,
+                    count: 2,
+                    cap: 1
+                },
+                Conflict::BackToBack { place: 1, time: 1 },
+            ]
+        );
+
+        let mut topics = TopicInterner::default();
+        let named = topics.intern(TopicLabel::Name("databases".to_string()));
+        let clash = Conflict::TopicClash {
+            time: 3,
+            topic: named,
+            count: 2,
+        };
+        assert_eq!(
+            clash.describe(&topics),
+            "time 3: 2 sessions of topic databases clash"
+        );
+    }
+
+    #[test]
+    fn weights_file_changes_the_result() {
+        let path = std::env::temp_dir().join(format!("weights-{}.json", std::process::id()));
+        fs::write(&path, r#"{"lateness": 0.2}"#).unwrap();
+        let doubled = load_weights(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            doubled,
+            PenaltyWeights {
+                lateness: 0.2,
+                ..PenaltyWeights::default()
+            }
+        );
+
+        // Lateness 2 at time 2 beats a preference cost of 3 at time 0,
+        // until lateness is doubled to 4
+        let talk = Activity {
+            priority: 10,
+            preferred_time: Some(2),
+            ..Activity::default()
+        };
+        let best_time = |weights: PenaltyWeights| {
+            let config = PenaltyConfig {
+                weights,
+                preference_weight: 1.5,
+                ..PenaltyConfig::default()
+            };
+            let mut schedule = Schedule::new(1, 3, [talk.clone()].into_iter());
+            schedule.improve(|s| activity_penalty(s, &config)).run();
+            (0..3).find(|&t| schedule.get_activity_at(0, t).unwrap().is_some())
+        };
+        assert_eq!(best_time(PenaltyWeights::default()), Some(2));
+        assert_eq!(best_time(doubled), Some(0));
+
+        let path = std::env::temp_dir().join(format!("typo-{}.json", std::process::id()));
+        fs::write(&path, r#"{"latenes": 0.2}"#).unwrap();
+        assert!(load_weights(path.to_str().unwrap()).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn free_empty_slots_let_sessions_overflow() {
+        // Two sessions, two regular slots and a buffer slot that is exempt
+        // from lateness
+        let sessions = (1..=2).map(|priority| Activity {
+            priority,
+            topic: priority,
+            ..Activity::default()
+        });
+        let buffered = |weights: PenaltyWeights| {
+            let config = PenaltyConfig {
+                weights,
+                ..PenaltyConfig::default()
+            };
+            let mut schedule = Schedule::new(1, 3, sessions.clone()).with_buffer_times(1);
+            schedule.improve(|s| activity_penalty(s, &config)).run();
+            schedule.get_activity_at(0, 2).unwrap().is_some()
+        };
+        // Counted as hard, an empty regular slot outweighs any lateness
+        assert!(!buffered(PenaltyWeights::default()));
+        let free = PenaltyWeights {
+            empty_slot: Some(0.0),
+            ..PenaltyWeights::default()
+        };
+        assert!(buffered(free));
+        // A soft cost above the lateness saved fills the slot again
+        let costly = PenaltyWeights {
+            empty_slot: Some(1.0),
+            ..PenaltyWeights::default()
+        };
+        assert!(!buffered(costly));
+    }
+
+    #[test]
+    fn weight_flags_override_the_weights_file() {
+        let argv = [
+            "conference-scheduler",
+            "--topic-conflict-weight",
+            "2.5",
+            "--empty-slot-weight",
+            "0",
+            "x.json",
+        ];
+        let args = Args::try_parse_from(argv).unwrap();
+        let file = PenaltyWeights {
+            lateness: 0.3,
+            topic_conflict: 7.0,
+            ..PenaltyWeights::default()
+        };
+        assert_eq!(
+            file.override_from(&args),
+            PenaltyWeights {
+                empty_slot: Some(0.0),
+                lateness: 0.3,
+                topic_conflict: 2.5,
+                ..PenaltyWeights::default()
+            }
+        );
+        let none = Args::try_parse_from(["conference-scheduler", "x.json"]).unwrap();
+        assert_eq!(file.override_from(&none), file);
+    }
+
+    #[test]
+    fn progress_bar_stays_out_of_json_output() {
+        let parse = |flags: &[&str]| {
+            let argv = ["conference-scheduler"]
+                .iter()
+                .chain(flags)
+                .chain(&["x.json"]);
+            Args::try_parse_from(argv).unwrap()
+        };
+        assert_eq!(
+            progress_enabled(&parse(&["--progress"]), true),
+            cfg!(feature = "progress")
+        );
+        assert!(!progress_enabled(&parse(&["--progress"]), false));
+        assert!(!progress_enabled(&parse(&["--progress", "--json"]), true));
+        assert!(!progress_enabled(&parse(&["--progress", "--jsonl"]), true));
+        assert!(!progress_enabled(&parse(&[]), true));
+    }
+
+    #[test]
+    fn progress_with_json_prints_only_json() {
+        // Even with stderr on a terminal, stdout holds nothing but the results
+        let instances = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/instance.json");
+        let args = Args::try_parse_from([
+            "conference-scheduler",
+            "--progress",
+            "--json",
+            "--nswaps",
+            "200",
+            "--seed",
+            "1",
+            instances,
+        ])
+        .unwrap();
+        let mut out = Vec::new();
+        run(&args, &mut out, true).unwrap();
+        let results: Vec<serde_json::Value> = serde_json::from_slice(&out).unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[cfg(feature = "progress")]
+    #[test]
+    fn progress_bar_fills_toward_the_timeout() {
+        let half = progress_bar(
+            Duration::from_secs(5),
+            Some(Duration::from_secs(10)),
+            "0, 1.50",
+        );
+        assert_eq!(
+            half,
+            format!(
+                "[{}{}] 5.0s/10s best 0, 1.50",
+                "#".repeat(15),
+                "-".repeat(15)
+            )
+        );
+        let late = progress_bar(Duration::from_secs(12), Some(Duration::from_secs(10)), "x");
+        assert!(late.starts_with(&format!("[{}]", "#".repeat(PROGRESS_WIDTH))));
+        assert_eq!(
+            progress_bar(Duration::from_millis(2500), None, "x"),
+            "[2.5s] best x"
+        );
+    }
+
+    #[test]
+    fn every_algorithm_parses_and_runs() {
+        let activities = (1..=5).map(|priority| Activity {
+            priority,
+            topic: priority % 2,
+            ..Activity::default()
+        });
+        let start = Schedule::new(2, 2, activities);
+        let config = PenaltyConfig::default();
+
+        for algorithm in Algorithm::value_variants() {
+            let name = algorithm.to_possible_value().unwrap();
+            let argv = [
+                "conference-scheduler",
+                "--algorithm",
+                name.get_name(),
+                "-s",
+                "50",
+                "x.json",
+            ];
+            let args = Args::try_parse_from(argv).unwrap();
+            assert_eq!(args.algorithm(), *algorithm);
+
+            let mut schedule = start.clone();
+            let penalty = |s: &Schedule<Activity>| activity_penalty(s, &config);
+            configure_improver(schedule.improve(penalty), &args).run();
+            assert_eq!(penalty(&schedule).0, 1);
+        }
+
+        // --noise is shorthand for the noise algorithm, and can't contradict it
+        let args = Args::try_parse_from(["conference-scheduler", "-n", "x.json"]).unwrap();
+        assert_eq!(args.algorithm(), Algorithm::Noise);
+        assert!(Args::try_parse_from(["c", "-n", "--algorithm", "hill", "x.json"]).is_err());
+        assert!(Args::try_parse_from(["c", "--noise-prob", "1.5", "x.json"]).is_err());
+    }
+
+    #[test]
+    fn breakdown_fields_sum_to_the_penalty() {
+        // The two topic-1 sessions clash at time 0, and one slot stays empty
+        let activities = [activity(1, &[]), activity(2, &[]), activity(1, &[])];
+        let schedule = Schedule::new(2, 2, activities.into_iter());
+        let config = PenaltyConfig {
+            weights: PenaltyWeights {
+                empty_slot: Some(4.0),
+                ..PenaltyWeights::default()
+            },
+            ..PenaltyConfig::default()
+        };
+        let breakdown = penalty_breakdown(&schedule, &config, &mut PenaltyScratch::default());
+        let (hard, other) = activity_penalty(&schedule, &config);
+        assert_eq!(hard, 0);
+
+        // As structured fields of the JSON output
+        let value = serde_json::to_value(breakdown).unwrap();
+        let fields = value.as_object().unwrap();
+        assert_eq!(fields.len(), breakdown.components().len());
+        let sum: f64 = fields.values().map(|x| x.as_f64().unwrap()).sum();
+        assert!((sum as f32 - other).abs() < 1e-3, "{sum} != {other}");
+        assert_eq!(value["empty_slots"], 4.0);
+
+        // In the text output, only the terms that count
+        let line = breakdown.summary_line();
+        assert!(line.starts_with("  penalty: empty slots 4.00, "));
+        assert!(line.contains("topic conflicts"));
+        assert!(!line.contains("room churn"));
+        let idle = PenaltyBreakdown::default();
+        assert_eq!(idle.summary_line(), "  penalty: none");
+    }
+
+    #[test]
+    fn emitted_schedule_deserializes() {
+        let activities = (1..=5).map(|priority| Activity {
+            priority,
+            topic: priority,
+            ..Activity::default()
+        });
+        let schedule = Schedule::new(2, 3, activities);
+        let result = RunResult {
+            instance_id: "emit".to_string(),
+            initial_unscheduled: 1,
+            initial_other_penalty: 0.0,
+            final_unscheduled: 1,
+            final_other_penalty: 0.0,
+            unscheduled_improvement: 0,
+            other_improvement: 0.0,
+            normalized_penalty: 1.0,
+            final_breakdown: PenaltyBreakdown::default(),
+            config: RunConfig {
+                noise: false,
+                restarts: None,
+                proportional: false,
+                timeout: None,
+                total_timeout: None,
+                nswaps: None,
+                seed: None,
+            },
+            schedule: Some(schedule.clone()),
+        };
+
+        let value = serde_json::to_value(&result).unwrap();
+        let emitted: Schedule<Activity> =
+            serde_json::from_value(value["schedule"].clone()).unwrap();
+        assert_eq!(emitted.dimensions(), (2, 3));
+        assert_eq!(emitted.slots(), schedule.slots());
+
+        // Left out entirely unless requested
+        let result = RunResult {
+            schedule: None,
+            ..result
+        };
+        let value = serde_json::to_value(&result).unwrap();
+        assert!(value.get("schedule").is_none());
+    }
 }
@@ -52,6 +52,20 @@ struct Args {
     )]
     repeat: usize,
 
+    #[arg(
+        long = "alpha",
+        help = "Significance level for pairwise configuration comparisons",
+        default_value = "0.05"
+    )]
+    alpha: f64,
+
+    #[arg(
+        long = "weights",
+        value_name = "FILE",
+        help = "Penalty weights file passed on to every scheduler run"
+    )]
+    weights: Option<String>,
+
     #[arg(long = "json", help = "Output results in JSON format")]
     json: bool,
 }
@@ -65,6 +79,7 @@ struct RunResult {
     final_other_penalty: f32,
     unscheduled_improvement: i32,
     other_improvement: f32,
+    normalized_penalty: f32,
     config: RunConfig,
 }
 
@@ -99,11 +114,16 @@ struct Statistics {
     std_improvement: f32,
     mean_final_penalty: f32,
     std_final_penalty: f32,
+    /// Final penalty as a fraction of initial penalty: comparable across
+    /// instance sizes, with 1 meaning no improvement
+    mean_normalized_penalty: f32,
+    std_normalized_penalty: f32,
     success_rate: f32, // percentage of runs that found improvements
 }
 
 fn run_scheduler(
     instances_file: &str,
+    weights: Option<&str>,
     config: &ConfigDescription,
 ) -> Result<Vec<RunResult>, Box<dyn std::error::Error>> {
     let mut cmd = Command::new("cargo");
@@ -129,6 +149,10 @@ fn run_scheduler(
         cmd.arg("--nswaps").arg(nswaps.to_string());
     }
 
+    if let Some(weights) = weights {
+        cmd.arg("--weights").arg(weights);
+    }
+
     let output = cmd.output()?;
 
     if !output.status.success() {
@@ -143,16 +167,23 @@ fn run_scheduler(
     Ok(results)
 }
 
+/// Combined improvement of each run: unscheduled improvement (weighted
+/// heavily) plus other improvement.
+fn improvement_samples<'a>(runs: impl IntoIterator<Item = &'a RunResult>) -> Vec<f32> {
+    runs.into_iter()
+        .map(|r| r.unscheduled_improvement as f32 * 1000.0 + r.other_improvement)
+        .collect()
+}
+
+/// Mean and population standard deviation.
+fn mean_std(xs: &[f32]) -> (f32, f32) {
+    let mean = xs.iter().sum::<f32>() / xs.len() as f32;
+    let variance = xs.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / xs.len() as f32;
+    (mean, variance.sqrt())
+}
+
 fn calculate_statistics(results: &[Vec<RunResult>]) -> Statistics {
-    let improvements: Vec<f32> = results
-        .iter()
-        .flat_map(|run_results| {
-            run_results.iter().map(|r| {
-                // Combined improvement: unscheduled improvement (weighted heavily) + other improvement
-                r.unscheduled_improvement as f32 * 1000.0 + r.other_improvement
-            })
-        })
-        .collect();
+    let improvements = improvement_samples(results.iter().flatten());
 
     let final_penalties: Vec<f32> = results
         .iter()
@@ -164,26 +195,15 @@ fn calculate_statistics(results: &[Vec<RunResult>]) -> Statistics {
         })
         .collect();
 
-    let mean_improvement = improvements.iter().sum::<f32>() / improvements.len() as f32;
-    let mean_final_penalty = final_penalties.iter().sum::<f32>() / final_penalties.len() as f32;
-
-    let std_improvement = {
-        let variance = improvements
-            .iter()
-            .map(|x| (x - mean_improvement).powi(2))
-            .sum::<f32>()
-            / improvements.len() as f32;
-        variance.sqrt()
-    };
-
-    let std_final_penalty = {
-        let variance = final_penalties
-            .iter()
-            .map(|x| (x - mean_final_penalty).powi(2))
-            .sum::<f32>()
-            / final_penalties.len() as f32;
-        variance.sqrt()
-    };
+    let normalized: Vec<f32> = results
+        .iter()
+        .flatten()
+        .map(|r| r.normalized_penalty)
+        .collect();
+
+    let (mean_improvement, std_improvement) = mean_std(&improvements);
+    let (mean_final_penalty, std_final_penalty) = mean_std(&final_penalties);
+    let (mean_normalized_penalty, std_normalized_penalty) = mean_std(&normalized);
 
     let success_count = improvements.iter().filter(|&&x| x > 0.0).count();
     let success_rate = (success_count as f32 / improvements.len() as f32) * 100.0;
@@ -193,10 +213,155 @@ fn calculate_statistics(results: &[Vec<RunResult>]) -> Statistics {
         std_improvement,
         mean_final_penalty,
         std_final_penalty,
+        mean_normalized_penalty,
+        std_normalized_penalty,
         success_rate,
     }
 }
 
+/// Outcome of Welch's two-sample t-test.
+#[derive(Debug)]
+struct WelchTest {
+    t: f64,
+    df: f64,
+    /// Two-sided p-value
+    p: f64,
+}
+
+/// Welch's t-test for a difference in means between two samples with
+/// possibly unequal variances. Returns `None` if either sample has fewer
+/// than two values or both have zero variance.
+fn welch_t_test(a: &[f32], b: &[f32]) -> Option<WelchTest> {
+    fn mean_var(xs: &[f32]) -> (f64, f64, f64) {
+        let n = xs.len() as f64;
+        let mean = xs.iter().map(|&x| x as f64).sum::<f64>() / n;
+        let var = xs.iter().map(|&x| (x as f64 - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (n, mean, var)
+    }
+
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let (na, ma, va) = mean_var(a);
+    let (nb, mb, vb) = mean_var(b);
+    let (sa, sb) = (va / na, vb / nb);
+    if sa + sb == 0.0 {
+        return None;
+    }
+
+    let t = (ma - mb) / (sa + sb).sqrt();
+    let df = (sa + sb).powi(2) / (sa * sa / (na - 1.0) + sb * sb / (nb - 1.0));
+    // Two-sided tail of Student's t: I_{df/(df+t^2)}(df/2, 1/2)
+    let p = incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    Some(WelchTest { t, df, p })
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`, by continued fraction.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    // The continued fraction converges quickly only below the mean
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - incomplete_beta(b, a, 1.0 - x);
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    ln_front.exp() * beta_continued_fraction(a, b, x) / a
+}
+
+/// Continued fraction for the incomplete beta function (Lentz's method).
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..200 {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        for aa in [
+            m * (b - m) * x / ((a + m2 - 1.0) * (a + m2)),
+            -(a + m) * (a + b + m) * x / ((a + m2) * (a + m2 + 1.0)),
+        ] {
+            d = 1.0 + aa * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + aa / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+/// Natural log of the gamma function (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let mut y = x;
+    let series = COEFFS.iter().fold(1.000000000190015, |acc, c| {
+        y += 1.0;
+        acc + c / y
+    });
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Describe a configuration compactly for comparison output.
+fn config_label(config: &ConfigDescription) -> String {
+    format!(
+        "restarts={}{}",
+        config.restarts,
+        if config.noise { "+noise" } else { "" }
+    )
+}
+
+/// Print Welch's t-test on the improvement samples of every pair of
+/// configurations.
+fn print_comparisons(results: &[EvaluationResult], alpha: f64) {
+    println!("Pairwise comparisons (Welch's t-test, alpha = {alpha}):");
+    for (i, a) in results.iter().enumerate() {
+        for b in &results[i + 1..] {
+            let (label_a, label_b) = (config_label(&a.config), config_label(&b.config));
+            let samples_a = improvement_samples(&a.runs);
+            let samples_b = improvement_samples(&b.runs);
+            match welch_t_test(&samples_a, &samples_b) {
+                Some(test) => {
+                    let verdict = match (test.p < alpha, test.t > 0.0) {
+                        (false, _) => "no significant difference".to_string(),
+                        (true, true) => format!("{label_a} is better"),
+                        (true, false) => format!("{label_b} is better"),
+                    };
+                    println!(
+                        "  {label_a} vs {label_b}: t = {:.3}, df = {:.1}, p = {:.4}: {verdict}",
+                        test.t, test.df, test.p
+                    );
+                }
+                None => println!("  {label_a} vs {label_b}: not enough variation to test"),
+            }
+        }
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
@@ -241,7 +406,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 std::io::Write::flush(&mut std::io::stdout())?;
             }
 
-            let run_results = run_scheduler(&args.instances_file, &config)?;
+            let run_results =
+                run_scheduler(&args.instances_file, args.weights.as_deref(), &config)?;
             runs.push(run_results);
 
             if !args.json {
@@ -262,6 +428,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "    Mean final penalty: {:.2} ± {:.2}",
                 stats.mean_final_penalty, stats.std_final_penalty
             );
+            println!(
+                "    Mean normalized penalty: {:.3} ± {:.3}",
+                stats.mean_normalized_penalty, stats.std_normalized_penalty
+            );
             println!("    Success rate: {:.1}%", stats.success_rate);
             println!("    Total time: {:.1}s", elapsed.as_secs_f32());
             println!();
@@ -295,7 +465,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("  Proportional: {}", best.config.proportional);
             println!("  Mean improvement: {:.2}", best.stats.mean_improvement);
         }
+
+        if all_results.len() > 1 {
+            println!();
+            print_comparisons(&all_results, args.alpha);
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn welch_test_matches_known_value() {
+        // Means 3 and 6, variances 2.5 and 10: t = -1.897 on 5.88 df
+        let test = welch_t_test(&[1.0, 2.0, 3.0, 4.0, 5.0], &[2.0, 4.0, 6.0, 8.0, 10.0]).unwrap();
+        assert!((test.t + 1.8974).abs() < 1e-3);
+        assert!((test.df - 5.882).abs() < 1e-3);
+        assert!((test.p - 0.1075).abs() < 2e-3, "p = {}", test.p);
+    }
+
+    #[test]
+    fn welch_test_separates_clear_from_overlapping_samples() {
+        let high = [10.0, 11.0, 12.0, 13.0, 14.0, 12.0];
+        let low = [0.0, 1.0, 2.0, 1.0, 0.0, 2.0];
+        let similar = [11.0, 13.0, 10.0, 14.0, 12.0, 12.5];
+
+        let clear = welch_t_test(&high, &low).unwrap();
+        assert!(clear.p < 0.001 && clear.t > 0.0);
+
+        let overlapping = welch_t_test(&high, &similar).unwrap();
+        assert!(overlapping.p > 0.5);
+
+        assert!(welch_t_test(&[1.0], &low).is_none());
+        assert!(welch_t_test(&[2.0, 2.0], &[2.0, 2.0]).is_none());
+    }
+}
@@ -0,0 +1,362 @@
+use std::fs;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use serde::Deserialize;
+use serde_json::Value;
+use time_scheduler::Schedule;
+
+#[derive(Parser)]
+#[command(about = "Write solved conference schedules as an iCalendar file")]
+struct Args {
+    #[arg(help = "Results of conference-scheduler --emit-schedule, with --json or --jsonl")]
+    results_file: String,
+    #[arg(
+        long = "start",
+        value_parser = parse_datetime,
+        help = "Start of time slot 0, as local time YYYY-MM-DDTHH:MM"
+    )]
+    start: i64,
+    #[arg(
+        long = "slot-minutes",
+        default_value = "60",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Length of each time slot in minutes"
+    )]
+    slot_minutes: u32,
+    #[arg(long = "instance", help = "Only export the instance with this id")]
+    instance: Option<String>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Write to this file instead of stdout"
+    )]
+    output: Option<String>,
+}
+
+/// The parts of a conference-scheduler activity an event shows.
+#[derive(Debug, Clone, Deserialize)]
+struct Session {
+    priority: usize,
+    topic: usize,
+}
+
+/// The parts of a conference-scheduler result this needs.
+#[derive(Deserialize)]
+struct RunResult {
+    instance_id: String,
+    schedule: Option<Schedule<Session>>,
+}
+
+/// Where the events of a calendar fall.
+struct Timing {
+    /// Start of time slot 0, in minutes since 1970-01-01 00:00 local time
+    start: i64,
+    slot_minutes: u32,
+    /// When the calendar was made, in minutes since the Unix epoch, UTC
+    stamp: i64,
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parse `YYYY-MM-DDTHH:MM` into minutes since 1970-01-01 00:00.
+fn parse_datetime(s: &str) -> Result<i64, String> {
+    let invalid = || format!("{s} is not a date and time like 2026-10-14T09:00");
+    let (date, time) = s.split_once('T').ok_or_else(invalid)?;
+    let mut date = date.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (date.next(), date.next(), date.next()) else {
+        return Err(invalid());
+    };
+    let (hour, minute) = time.split_once(':').ok_or_else(invalid)?;
+    let year: i64 = year.parse().map_err(|_| invalid())?;
+    let [month, day, hour, minute]: [u32; 4] = [month, day, hour, minute]
+        .map(str::parse)
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?
+        .try_into()
+        .map_err(|_| invalid())?;
+    let days = days_from_civil(year, month, day);
+    // Out-of-range fields land on some other date
+    if civil_from_days(days) != (year, month, day) || hour > 23 || minute > 59 {
+        return Err(format!("{s} is not a valid date and time"));
+    }
+    Ok(days * 1440 + i64::from(hour) * 60 + i64::from(minute))
+}
+
+/// Format minutes since 1970-01-01 00:00 as an iCalendar date-time.
+fn format_datetime(minutes: i64) -> String {
+    let (year, month, day) = civil_from_days(minutes.div_euclid(1440));
+    let minute_of_day = minutes.rem_euclid(1440);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}00",
+        minute_of_day / 60,
+        minute_of_day % 60
+    )
+}
+
+/// Escape text for an iCalendar TEXT value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => (),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Read every result in `text`, a JSON array of results, a single one, or
+/// one per line, keeping the solved schedules.
+fn load_results(text: &str) -> Result<Vec<(String, Schedule<Session>)>, String> {
+    let mut results = Vec::new();
+    for value in serde_json::Deserializer::from_str(text).into_iter::<Value>() {
+        let values = match value.map_err(|e| e.to_string())? {
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            let result: RunResult = serde_json::from_value(value).map_err(|e| e.to_string())?;
+            let schedule = result.schedule.ok_or_else(|| {
+                format!(
+                    "instance {} has no schedule: run conference-scheduler with --emit-schedule",
+                    result.instance_id
+                )
+            })?;
+            results.push((result.instance_id, schedule));
+        }
+    }
+    Ok(results)
+}
+
+/// Write one VEVENT per scheduled session, with CRLF line endings.
+///
+/// Named places become the event location, and named time slots are noted
+/// in its description.
+fn write_calendar(
+    out: &mut impl Write,
+    results: &[(String, Schedule<Session>)],
+    timing: &Timing,
+) -> io::Result<()> {
+    let mut line = |text: &str| write!(out, "{text}\r\n");
+    line("BEGIN:VCALENDAR")?;
+    line("VERSION:2.0")?;
+    line("PRODID:-//time-scheduler//export-ical//EN")?;
+    let stamp = format_datetime(timing.stamp);
+    let slot = i64::from(timing.slot_minutes);
+    for (id, schedule) in results {
+        for (place, time, session) in schedule.iter_scheduled() {
+            let start = timing.start + time as i64 * slot;
+            line("BEGIN:VEVENT")?;
+            line(&format!(
+                "UID:{}",
+                escape(&format!("{id}-p{place}-t{time}@time-scheduler"))
+            ))?;
+            line(&format!("DTSTAMP:{stamp}Z"))?;
+            line(&format!("DTSTART:{}", format_datetime(start)))?;
+            line(&format!("DTEND:{}", format_datetime(start + slot)))?;
+            line(&format!("SUMMARY:Topic {}", session.topic))?;
+            let mut description = format!("Priority {}, instance {id}", session.priority);
+            if schedule.time_labels().is_some() {
+                description.push_str(&format!(", slot {}", schedule.time_label(time)));
+            }
+            line(&format!("DESCRIPTION:{}", escape(&description)))?;
+            let location = match schedule.place_labels() {
+                Some(labels) => labels[place].clone(),
+                None => format!("Room {place}"),
+            };
+            line(&format!("LOCATION:{}", escape(&location)))?;
+            line("END:VEVENT")?;
+        }
+    }
+    line("END:VCALENDAR")
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let text = fs::read_to_string(&args.results_file)?;
+    let mut results = load_results(&text).map_err(|e| format!("{}: {e}", args.results_file))?;
+    if let Some(id) = &args.instance {
+        results.retain(|(instance_id, _)| instance_id == id);
+        if results.is_empty() {
+            return Err(format!("no instance {id} in {}", args.results_file).into());
+        }
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let timing = Timing {
+        start: args.start,
+        slot_minutes: args.slot_minutes,
+        stamp: (now.as_secs() / 60) as i64,
+    };
+    match &args.output {
+        Some(path) => {
+            let mut file = io::BufWriter::new(fs::File::create(path)?);
+            write_calendar(&mut file, &results, &timing)?;
+            file.flush()?;
+        }
+        None => write_calendar(&mut io::stdout().lock(), &results, &timing)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(results: &[(String, Schedule<Session>)], start: &str, slot_minutes: u32) -> String {
+        let timing = Timing {
+            start: parse_datetime(start).unwrap(),
+            slot_minutes,
+            stamp: 0,
+        };
+        let mut out = Vec::new();
+        write_calendar(&mut out, results, &timing).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn sessions(n: usize) -> impl ExactSizeIterator<Item = Session> {
+        (0..n).map(|i| Session {
+            priority: 10 - i,
+            topic: i,
+        })
+    }
+
+    #[test]
+    fn calendar_is_minimally_valid() {
+        let results = vec![("talks".to_string(), Schedule::new(2, 2, sessions(3)))];
+        let ics = calendar(&results, "2026-10-14T09:00", 60);
+
+        assert!(ics.ends_with("\r\n"));
+        let lines: Vec<&str> = ics.split("\r\n").filter(|l| !l.is_empty()).collect();
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert_eq!(lines.last(), Some(&"END:VCALENDAR"));
+        assert!(lines.contains(&"VERSION:2.0"));
+        assert!(lines.iter().any(|l| l.starts_with("PRODID:")));
+
+        // One event per scheduled session, each opened and closed in turn
+        let mut open = false;
+        let mut events = 0;
+        for line in &lines {
+            match *line {
+                "BEGIN:VEVENT" => {
+                    assert!(!open);
+                    open = true;
+                    events += 1;
+                }
+                "END:VEVENT" => {
+                    assert!(open);
+                    open = false;
+                }
+                _ => assert!(!line.contains('\n') && line.contains(':')),
+            }
+        }
+        assert!(!open);
+        assert_eq!(events, 3);
+        assert_eq!(lines.iter().filter(|l| l.starts_with("UID:")).count(), 3);
+    }
+
+    #[test]
+    fn slots_map_to_start_plus_duration() {
+        // Place 1 holds session 2 at time 0, overflowing into the next day
+        let results = vec![("late".to_string(), Schedule::new(2, 2, sessions(3)))];
+        let ics = calendar(&results, "2026-12-31T22:30", 90);
+        let event: Vec<&str> = ics
+            .split("BEGIN:VEVENT\r\n")
+            .find(|e| e.contains("LOCATION:Room 1"))
+            .unwrap()
+            .lines()
+            .collect();
+        assert!(event.contains(&"DTSTART:20261231T223000"));
+        assert!(event.contains(&"DTEND:20270101T000000"));
+        assert!(event.contains(&"SUMMARY:Topic 2"));
+        assert!(event.contains(&"DESCRIPTION:Priority 8\\, instance late"));
+
+        let second = ics
+            .split("BEGIN:VEVENT\r\n")
+            .find(|e| e.contains("UID:late-p0-t1@"))
+            .unwrap();
+        assert!(second.contains("DTSTART:20270101T000000"));
+        assert!(second.contains("DTEND:20270101T013000"));
+    }
+
+    #[test]
+    fn labels_name_locations_and_slots() {
+        let schedule = Schedule::new(2, 2, sessions(3))
+            .with_place_labels(vec!["Hall".into(), "Room A; upstairs".into()])
+            .with_time_labels(vec!["Morning".into(), "Afternoon".into()]);
+        let ics = calendar(&[("named".to_string(), schedule)], "2026-10-14T09:00", 60);
+        let event = ics
+            .split("BEGIN:VEVENT\r\n")
+            .find(|e| e.contains("UID:named-p1-t0@"))
+            .unwrap();
+        assert!(event.contains("LOCATION:Room A\\; upstairs\r\n"));
+        assert!(event.contains("DESCRIPTION:Priority 8\\, instance named\\, slot Morning\r\n"));
+    }
+
+    #[test]
+    fn dates_are_checked_and_round_trip() {
+        assert_eq!(parse_datetime("1970-01-01T00:00"), Ok(0));
+        assert_eq!(
+            format_datetime(parse_datetime("2024-02-29T23:59").unwrap()),
+            "20240229T235900"
+        );
+        assert!(parse_datetime("2026-02-29T09:00").is_err());
+        assert!(parse_datetime("2026-10-14T24:00").is_err());
+        assert!(parse_datetime("2026-10-14").is_err());
+        assert!(parse_datetime("1969-12-31T23:00").unwrap() < 0);
+    }
+
+    #[test]
+    fn results_need_their_schedules() {
+        let with = r#"[{"instance_id": "a", "final_unscheduled": 0,
+            "schedule": {"slots": [[{"priority": 3, "topic": 1}, null]], "unscheduled": []}}]"#;
+        let results = load_results(with).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1.iter_scheduled().count(), 1);
+
+        // JSON lines work too
+        let lines = format!("{}\n{}", &with[1..with.len() - 1], &with[1..with.len() - 1]);
+        assert_eq!(load_results(&lines).unwrap().len(), 2);
+
+        let without = r#"[{"instance_id": "b"}]"#;
+        assert!(load_results(without)
+            .unwrap_err()
+            .contains("--emit-schedule"));
+    }
+}
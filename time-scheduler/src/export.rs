@@ -0,0 +1,222 @@
+//! Rendering solved schedules for people and other tools.
+
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::Schedule;
+
+/// Render a schedule as a boxed text grid for terminal inspection.
+///
+/// Places are rows and time slots are columns, headed by their
+/// [labels](Schedule::place_label), `p0, p1, ...` and `t0, t1, ...` unless
+/// named. Each occupied cell shows `label(activity)`; empty cells are
+/// blank. Every column is `column_width` characters wide, and longer labels
+/// are truncated with an ellipsis. Unscheduled activities are not shown.
+///
+/// # Examples
+///
+/// ```rust
+/// use time_scheduler::{export::to_ascii_grid, Schedule};
+///
+/// let schedule = Schedule::new(2, 3, vec!["keynote", "rust", "lunch-and-learn"].into_iter());
+/// let grid = to_ascii_grid(&schedule, 6, |a| a.to_string());
+/// print!("{grid}");
+///
+/// let lines: Vec<&str> = grid.lines().collect();
+/// // Borders, header and separator, then one line per place
+/// assert_eq!(lines.len(), 4 + 2);
+/// // Row header plus three time columns, each padded and bordered
+/// assert!(lines.iter().all(|l| l.chars().count() == 4 * (6 + 3) + 1));
+/// assert_eq!(lines[0], "┌────────┬────────┬────────┬────────┐");
+/// assert_eq!(lines[1], "│        │ t0     │ t1     │ t2     │");
+/// assert_eq!(lines[3], "│ p0     │ keyno… │ rust   │ lunch… │");
+/// assert_eq!(lines[4], "│ p1     │        │        │        │");
+/// ```
+pub fn to_ascii_grid<A: Clone>(
+    schedule: &Schedule<A>,
+    column_width: usize,
+    label: impl Fn(&A) -> String,
+) -> String {
+    let (nplaces, ntimes) = schedule.dimensions();
+    let slots = schedule.slots();
+
+    let rule = |left: char, mid: char, right: char| {
+        let segment = "─".repeat(column_width + 2);
+        let mut line = String::from(left);
+        for i in 0..=ntimes {
+            if i > 0 {
+                line.push(mid);
+            }
+            line.push_str(&segment);
+        }
+        line.push(right);
+        line.push('\n');
+        line
+    };
+    let row = |cells: Vec<String>| {
+        let mut line = String::from("│");
+        for cell in cells {
+            line.push(' ');
+            line.push_str(&fit(&cell, column_width));
+            line.push_str(" │");
+        }
+        line.push('\n');
+        line
+    };
+
+    let mut grid = rule('┌', '┬', '┐');
+    let header = std::iter::once(String::new()).chain((0..ntimes).map(|t| schedule.time_label(t)));
+    grid.push_str(&row(header.collect()));
+    grid.push_str(&rule('├', '┼', '┤'));
+    for p in 0..nplaces {
+        let cells = (0..ntimes).map(|t| slots[(p, t)].as_ref().map(&label).unwrap_or_default());
+        grid.push_str(&row(std::iter::once(schedule.place_label(p))
+            .chain(cells)
+            .collect()));
+    }
+    grid.push_str(&rule('└', '┴', '┘'));
+    grid
+}
+
+/// Write a schedule as CSV, for pasting into a spreadsheet.
+///
+/// The layout matches [`to_ascii_grid`]: a header row of time slot labels
+/// after an empty corner cell, then one row per place, starting with its
+/// label. Each occupied cell holds
+/// `label(activity)` and empty cells are blank. Fields containing a
+/// comma, quote or line break are quoted, and rows end in CRLF as
+/// RFC 4180 asks. Unscheduled activities are not written.
+///
+/// # Errors
+///
+/// Returns any error from writing to `w`.
+///
+/// # Examples
+///
+/// ```rust
+/// use time_scheduler::{export::write_csv, Schedule};
+///
+/// let schedule = Schedule::new(2, 3, vec!["keynote", "rust, again", "lunch"].into_iter());
+/// let mut csv = Vec::new();
+/// write_csv(&schedule, &mut csv, |a| a.to_string()).unwrap();
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     ",t0,t1,t2\r\np0,keynote,\"rust, again\",lunch\r\np1,,,\r\n"
+/// );
+/// ```
+pub fn write_csv<A: Clone, W: Write>(
+    schedule: &Schedule<A>,
+    w: &mut W,
+    label: impl Fn(&A) -> String,
+) -> io::Result<()> {
+    let (nplaces, ntimes) = schedule.dimensions();
+    let slots = schedule.slots();
+
+    let mut write_row = |cells: &mut dyn Iterator<Item = String>| -> io::Result<()> {
+        for (i, cell) in cells.enumerate() {
+            if i > 0 {
+                w.write_all(b",")?;
+            }
+            w.write_all(csv_field(&cell).as_bytes())?;
+        }
+        w.write_all(b"\r\n")
+    };
+
+    let mut header =
+        std::iter::once(String::new()).chain((0..ntimes).map(|t| schedule.time_label(t)));
+    write_row(&mut header)?;
+    for p in 0..nplaces {
+        let cells = (0..ntimes).map(|t| slots[(p, t)].as_ref().map(&label).unwrap_or_default());
+        write_row(&mut std::iter::once(schedule.place_label(p)).chain(cells))?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if it needs it, doubling any quotes inside.
+fn csv_field(text: &str) -> Cow<'_, str> {
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\"")).into()
+    } else {
+        text.into()
+    }
+}
+
+impl<A: Clone> Schedule<A> {
+    /// Render the schedule as a plain text table for debugging.
+    ///
+    /// Places are rows and time slots are columns, headed by their labels as
+    /// in [`to_ascii_grid`]. Each occupied cell shows `label(activity)` in full and
+    /// empty cells show `.`. Each column is as wide as its widest cell,
+    /// counted in characters, with two spaces between columns and no
+    /// trailing spaces. For a fixed width and borders, see
+    /// [`to_ascii_grid`]. Unscheduled activities are not shown.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(2, 3, vec!["keynote", "rust", "lunch"].into_iter());
+    /// let grid = schedule.render_grid(|a| a.to_string());
+    /// assert_eq!(
+    ///     grid,
+    ///     "    t0       t1    t2\n\
+    ///      p0  keynote  rust  lunch\n\
+    ///      p1  .        .     .\n"
+    /// );
+    /// ```
+    pub fn render_grid(&self, label: impl Fn(&A) -> String) -> String {
+        let (nplaces, ntimes) = self.dimensions();
+        let slots = self.slots();
+
+        // Column 0 holds the place labels, and row 0 the time labels
+        let mut cells = Vec::with_capacity((nplaces + 1) * (ntimes + 1));
+        cells.push(String::new());
+        cells.extend((0..ntimes).map(|t| self.time_label(t)));
+        for p in 0..nplaces {
+            cells.push(self.place_label(p));
+            cells.extend((0..ntimes).map(|t| {
+                slots[(p, t)]
+                    .as_ref()
+                    .map_or_else(|| ".".to_string(), &label)
+            }));
+        }
+        let mut widths = vec![0; ntimes + 1];
+        for row in cells.chunks(ntimes + 1) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line_len = widths.iter().sum::<usize>() + 2 * ntimes + 1;
+        let mut grid = String::with_capacity((nplaces + 1) * line_len);
+        for row in cells.chunks(ntimes + 1) {
+            let start = grid.len();
+            for (i, (cell, &width)) in row.iter().zip(&widths).enumerate() {
+                if i > 0 {
+                    grid.push_str("  ");
+                }
+                // Writing to a String cannot fail
+                let _ = write!(grid, "{cell:<width$}");
+            }
+            grid.truncate(start + grid[start..].trim_end().len());
+            grid.push('\n');
+        }
+        grid
+    }
+}
+
+/// Pad `text` to exactly `width` characters, truncating with an ellipsis.
+fn fit(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        format!("{text:<width$}")
+    } else if width == 0 {
+        String::new()
+    } else {
+        let mut truncated: String = text.chars().take(width - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+}
@@ -0,0 +1,438 @@
+//! A genetic algorithm over schedules, with an island model.
+//!
+//! Where [`Improver`](crate::Improver) walks from one schedule to the next
+//! by swaps, a [`GeneticImprover`] breeds a population of them. Each
+//! individual is a *genome*: a permutation of the schedule's movable
+//! contents over its movable locations, the slots that are neither
+//! [locked nor forbidden](crate::Constraints) followed by the unscheduled
+//! list. A child takes a stretch of one parent's genome and the rest of its
+//! genes in the order the other parent has them (order crossover), then
+//! perhaps has two of its locations swapped (swap mutation). Since every
+//! genome is a permutation, every schedule bred holds exactly the
+//! activities the starting schedule did, and fixed cells never change.
+//!
+//! For large, hard instances the population can be split into several
+//! *islands* that evolve apart, on threads of their own with the `parallel`
+//! feature. Every so often each island sends copies of its best
+//! individuals to the next island around a ring, where they replace the
+//! worst. Each island draws from its own generator, seeded from the master
+//! seed by island number, and the islands wait for each other at every
+//! migration, so a seeded search gives the same result however the threads
+//! are scheduled. Only with the feature must the activities, penalties and
+//! penalty function be safe to share between threads; see [`MaybeSync`].
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use fastrand::Rng;
+use log::debug;
+use ndarray::Array2;
+
+use crate::{MaybeSend, MaybeSync, Schedule};
+
+/// A genetic search over a schedule, configured builder-style and started
+/// with [`run`](GeneticImprover::run).
+///
+/// Created by [`Schedule::evolve`]. The defaults are:
+///
+/// * [`population`](GeneticImprover::population): 50 individuals per island
+/// * [`generations`](GeneticImprover::generations): 100
+/// * [`mutation_rate`](GeneticImprover::mutation_rate): 0.1
+/// * [`islands`](GeneticImprover::islands): 1
+/// * [`migration_interval`](GeneticImprover::migration_interval): every 10
+///   generations
+/// * [`migrants`](GeneticImprover::migrants): 1
+/// * no seed: a fresh generator seeded from the global `fastrand` one
+pub struct GeneticImprover<'a, A, F, P> {
+    schedule: &'a mut Schedule<A>,
+    penalty_fn: F,
+    population: usize,
+    generations: usize,
+    mutation_rate: f32,
+    islands: usize,
+    migration_interval: usize,
+    migrants: usize,
+    seed: Option<u64>,
+    _penalty: PhantomData<P>,
+}
+
+impl<A, F, P> GeneticImprover<'_, A, F, P>
+where
+    A: Clone + MaybeSend + MaybeSync,
+    F: Fn(&Schedule<A>) -> P + MaybeSync,
+    P: Copy + PartialOrd + Debug + MaybeSend,
+{
+    /// Set the number of individuals on each island.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is less than 2, too few to breed.
+    pub fn population(mut self, size: usize) -> Self {
+        assert!(size >= 2, "a population of {size} is too small to breed");
+        self.population = size;
+        self
+    }
+
+    /// Set the number of generations each island breeds.
+    pub fn generations(mut self, generations: usize) -> Self {
+        self.generations = generations;
+        self
+    }
+
+    /// Set the chance that a child has two of its locations swapped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not within `0.0..=1.0`.
+    pub fn mutation_rate(mut self, rate: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "mutation rate {rate} is not within 0.0..=1.0"
+        );
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Split the search into `islands` populations that evolve apart,
+    /// exchanging individuals every
+    /// [`migration_interval`](GeneticImprover::migration_interval)
+    /// generations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `islands` is 0.
+    pub fn islands(mut self, islands: usize) -> Self {
+        assert!(islands > 0, "a genetic search needs at least one island");
+        self.islands = islands;
+        self
+    }
+
+    /// Migrate between islands after every `generations` generations. An
+    /// interval of 0 keeps the islands apart for the whole search.
+    pub fn migration_interval(mut self, generations: usize) -> Self {
+        self.migration_interval = generations;
+        self
+    }
+
+    /// Set how many of its best individuals an island sends to the next
+    /// at each migration. There must be fewer migrants than the
+    /// [`population`](GeneticImprover::population), so that every island
+    /// keeps its own best individual.
+    pub fn migrants(mut self, migrants: usize) -> Self {
+        self.migrants = migrants;
+        self
+    }
+
+    /// Seed the random number generator. Each island gets its own
+    /// generator, whose seed is drawn from this master seed in island
+    /// order, so two searches with the same seed and parameters reach the
+    /// same schedule.
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Run the search, leaving the schedule in the best state any island
+    /// found.
+    ///
+    /// Every island starts from the schedule's current layout and random
+    /// permutations of it, and always keeps its best individual, so the
+    /// schedule is only ever replaced by a strictly better one.
+    /// Evaluations number about `islands × population × generations`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are as many [`migrants`](GeneticImprover::migrants)
+    /// as individuals on an island. A panic in the penalty function on any
+    /// thread is passed on to the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // The activities on the diagonal should sum small
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     (0..3).filter_map(|i| s.get_activity_at(i, i).unwrap()).sum::<usize>()
+    /// };
+    /// let mut schedule = Schedule::new(3, 3, 0..12usize);
+    /// schedule
+    ///     .evolve(penalty)
+    ///     .population(20)
+    ///     .generations(40)
+    ///     .islands(4)
+    ///     .migration_interval(5)
+    ///     .with_rng_seed(7)
+    ///     .run();
+    /// assert_eq!(penalty(&schedule), 0 + 1 + 2);
+    /// ```
+    pub fn run(self) {
+        assert!(
+            self.migrants < self.population,
+            "{} migrants would replace an island's whole population of {}",
+            self.migrants,
+            self.population
+        );
+        let genes = Genes::new(self.schedule);
+        if genes.contents.len() < 2 {
+            return;
+        }
+        let penalty_fn = &self.penalty_fn;
+        let evaluate = |genome: Vec<usize>| Individual {
+            penalty: penalty_fn(&genes.decode(&genome)),
+            genome,
+        };
+        let start = evaluate((0..genes.contents.len()).collect());
+
+        let mut master_rng = match self.seed {
+            Some(seed) => Rng::with_seed(seed),
+            None => Rng::new(),
+        };
+        let mut islands: Vec<Island<P>> = (0..self.islands)
+            .map(|_| Island::new(start.clone(), self.population, master_rng.u64(..)))
+            .collect();
+        for_each_island(&mut islands, |island| island.populate(&evaluate));
+
+        let interval = match self.migration_interval {
+            0 => self.generations,
+            n => n.min(self.generations),
+        };
+        let mut bred = 0;
+        while bred < self.generations {
+            let epoch = interval.min(self.generations - bred);
+            for_each_island(&mut islands, |island| {
+                for _ in 0..epoch {
+                    island.breed(self.mutation_rate, &evaluate);
+                }
+            });
+            bred += epoch;
+            if bred < self.generations && islands.len() > 1 {
+                migrate(&mut islands, self.migrants);
+                debug!("generation {bred}: migrated {} per island", self.migrants);
+            }
+        }
+
+        // Ties go to the lowest-numbered island
+        let best = islands
+            .into_iter()
+            .filter_map(|island| island.population.into_iter().next())
+            .reduce(|best, other| {
+                if other.penalty < best.penalty {
+                    other
+                } else {
+                    best
+                }
+            })
+            .filter(|best| best.penalty < start.penalty);
+        match best {
+            Some(best) => {
+                debug!("genetic search finished with penalty {:?}", best.penalty);
+                *self.schedule = genes.decode(&best.genome);
+            }
+            None => debug!("genetic search found nothing better than the start"),
+        }
+    }
+}
+
+impl<A: Clone> Schedule<A> {
+    /// Create a [`GeneticImprover`] for this schedule with the given
+    /// penalty function, lower being better.
+    ///
+    /// A penalty may be a `Result<_, `[`Infeasible`](crate::Infeasible)`>`,
+    /// as with [`improve_feasible`](Schedule::improve_feasible): any
+    /// feasible individual then ranks above any infeasible one.
+    pub fn evolve<F, P>(&mut self, penalty_fn: F) -> GeneticImprover<'_, A, F, P>
+    where
+        F: Fn(&Schedule<A>) -> P,
+        P: Copy + PartialOrd + Debug,
+    {
+        GeneticImprover {
+            schedule: self,
+            penalty_fn,
+            population: 50,
+            generations: 100,
+            mutation_rate: 0.1,
+            islands: 1,
+            migration_interval: 10,
+            migrants: 1,
+            seed: None,
+            _penalty: PhantomData,
+        }
+    }
+}
+
+/// What a genome is a permutation of, and how to turn one back into a
+/// schedule.
+struct Genes<A> {
+    /// The schedule with its movable locations emptied
+    template: Schedule<A>,
+    /// Which slots are locked or forbidden
+    fixed: Array2<bool>,
+    /// The starting contents of the movable locations, in order
+    contents: Vec<Option<A>>,
+}
+
+impl<A: Clone> Genes<A> {
+    fn new(schedule: &Schedule<A>) -> Self {
+        let mut template = schedule.clone();
+        let fixed = template.fixed_cells();
+        let mut contents: Vec<Option<A>> = template
+            .slots
+            .iter_mut()
+            .zip(&fixed)
+            .filter(|(_, &fixed)| !fixed)
+            .map(|(slot, _)| slot.take())
+            .collect();
+        contents.append(&mut template.unscheduled);
+        Self {
+            template,
+            fixed,
+            contents,
+        }
+    }
+
+    /// The schedule with genome location `k` holding `contents[genome[k]]`.
+    fn decode(&self, genome: &[usize]) -> Schedule<A> {
+        let mut schedule = self.template.clone();
+        let mut genes = genome.iter().map(|&gene| self.contents[gene].clone());
+        for (slot, _) in schedule
+            .slots
+            .iter_mut()
+            .zip(&self.fixed)
+            .filter(|(_, &fixed)| !fixed)
+        {
+            *slot = genes.next().flatten();
+        }
+        schedule.unscheduled = genes.collect();
+        schedule
+    }
+}
+
+/// A genome and its penalty.
+#[derive(Clone)]
+struct Individual<P> {
+    genome: Vec<usize>,
+    penalty: P,
+}
+
+/// One population, kept best first.
+struct Island<P> {
+    population: Vec<Individual<P>>,
+    /// Size the population is bred back up to
+    size: usize,
+    rng: Rng,
+}
+
+impl<P: Copy + PartialOrd> Island<P> {
+    fn new(start: Individual<P>, size: usize, seed: u64) -> Self {
+        Self {
+            population: vec![start],
+            size,
+            rng: Rng::with_seed(seed),
+        }
+    }
+
+    /// Fill the population with random permutations of the start.
+    fn populate(&mut self, evaluate: &impl Fn(Vec<usize>) -> Individual<P>) {
+        while self.population.len() < self.size {
+            let mut genome = self.population[0].genome.clone();
+            self.rng.shuffle(&mut genome);
+            self.population.push(evaluate(genome));
+        }
+        self.rank();
+    }
+
+    /// Replace the population with the next generation, keeping the best
+    /// individual and breeding the rest.
+    fn breed(&mut self, mutation_rate: f32, evaluate: &impl Fn(Vec<usize>) -> Individual<P>) {
+        let mut next = Vec::with_capacity(self.size);
+        next.push(self.population[0].clone());
+        while next.len() < self.size {
+            let (first, second) = (self.tournament(), self.tournament());
+            let mut child = order_crossover(
+                &self.population[first].genome,
+                &self.population[second].genome,
+                &mut self.rng,
+            );
+            if self.rng.f32() < mutation_rate {
+                let n = child.len();
+                child.swap(self.rng.usize(..n), self.rng.usize(..n));
+            }
+            next.push(evaluate(child));
+        }
+        self.population = next;
+        self.rank();
+    }
+
+    /// Pick the better of two random individuals, by index.
+    fn tournament(&mut self) -> usize {
+        let n = self.population.len();
+        self.rng.usize(..n).min(self.rng.usize(..n))
+    }
+
+    /// Sort the population best first, keeping the order of equals.
+    fn rank(&mut self) {
+        self.population
+            .sort_by(|a, b| a.penalty.partial_cmp(&b.penalty).unwrap_or(Ordering::Equal));
+    }
+}
+
+/// A child holding `first`'s genes in a random stretch of locations and
+/// the rest in the order `second` has them.
+fn order_crossover(first: &[usize], second: &[usize], rng: &mut Rng) -> Vec<usize> {
+    let n = first.len();
+    let (mut start, mut end) = (rng.usize(..=n), rng.usize(..=n));
+    if start > end {
+        std::mem::swap(&mut start, &mut end);
+    }
+    let mut taken = vec![false; n];
+    for &gene in &first[start..end] {
+        taken[gene] = true;
+    }
+    let mut rest = second.iter().copied().filter(|&gene| !taken[gene]);
+    let mut child: Vec<usize> = rest.by_ref().take(start).collect();
+    child.extend_from_slice(&first[start..end]);
+    child.extend(rest);
+    child
+}
+
+/// Send copies of each island's best `migrants` individuals to the next
+/// island around the ring, in place of its worst.
+fn migrate<P: Copy + PartialOrd>(islands: &mut [Island<P>], migrants: usize) {
+    let emigrants: Vec<Vec<Individual<P>>> = islands
+        .iter()
+        .map(|island| island.population[..migrants].to_vec())
+        .collect();
+    let n = islands.len();
+    for (i, arrivals) in emigrants.into_iter().enumerate() {
+        let island = &mut islands[(i + 1) % n];
+        island.population.truncate(island.size - migrants);
+        island.population.extend(arrivals);
+        island.rank();
+    }
+}
+
+/// Apply `f` to every island, on scoped threads.
+#[cfg(feature = "parallel")]
+fn for_each_island<T: Send>(islands: &mut [T], f: impl Fn(&mut T) + Sync) {
+    let nthreads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = islands.len().div_ceil(nthreads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = islands
+            .chunks_mut(chunk)
+            .map(|chunk| scope.spawn(|| chunk.iter_mut().for_each(&f)))
+            .collect();
+        for worker in workers {
+            worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        }
+    });
+}
+
+/// Apply `f` to every island in turn.
+#[cfg(not(feature = "parallel"))]
+fn for_each_island<T>(islands: &mut [T], f: impl Fn(&mut T)) {
+    islands.iter_mut().for_each(f);
+}
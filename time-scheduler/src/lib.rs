@@ -38,6 +38,10 @@
 //!         Activity { priority: 8, topic: 2 },
 //!         // ... more activities
 //!     ],
+//!     buffer_times: 0,
+//!     constraints: Default::default(),
+//!     place_labels: None,
+//!     time_labels: None,
 //! };
 //!
 //! // Create an initial schedule
@@ -64,18 +68,96 @@
 //! - **Flexible Penalty Functions**: Define custom evaluation criteria
 //! - **Local Search Improvement**: Hill-climbing with configurable parameters
 //! - **Multi-restart Support**: Escape local optima with random restarts
+//! - **Parallel Restarts**: [`Improver::run_parallel`] runs restarts on several threads
 //! - **Noise Moves**: Explore solution space with probabilistic moves
+//! - **Genetic Search**: [`genetic::GeneticImprover`] breeds schedules on islands that trade their best
+//! - **Acceptance Rules**: [`Acceptance`] runs simulated annealing, great deluge or late acceptance
+//! - **Hard Constraints**: Penalties returning `Result<_, Infeasible>` keep the search feasible
+//! - **Fixed Cells**: [`Constraints`] lock cells in place or keep them empty
+//! - **Availability**: [`Improver::allowed_slots`] keeps each activity out of slots it may not use
+//! - **Place Capacities**: [`multi::MultiSchedule`] lets a place hold several activities at once
 //! - **Bounds Checking**: Safe access to schedule slots with error handling
 //!
+//! ## Logging
+//!
+//! The improvement loop reports progress through the [`log`](https://docs.rs/log)
+//! crate: restarts, timeouts and convergence at `debug` level, and every accepted
+//! greedy move at `trace` level. Install any `log` backend (such as `env_logger`)
+//! to see them; without one the calls cost next to nothing.
+//!
 //! ---
 //!
 //! *This documentation was generated by Claude Code (Sonnet 4)*
 
-use ndarray::Array2;
+use fastrand::Rng;
+use log::{debug, trace, warn};
+use ndarray::{Array2, ArrayViewMut2, Axis};
 use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Debug;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::{Add, Sub};
+use std::sync::{Mutex, PoisonError};
 use std::time::{Duration, Instant};
 use thiserror::Error;
 
+pub mod export;
+pub mod genetic;
+pub mod multi;
+
+/// Default swap budget for one improvement run.
+///
+/// This is the budget used when [`Improver::max_swaps`] is not set and no
+/// timeout bounds the run: `10 * ntotal` swaps, where
+/// `ntotal = nplaces * ntimes + nunscheduled` is the number of locations.
+/// Each greedy swap scans all `O(ntotal^2)` location pairs, so a linear
+/// number of swaps keeps the default run cubic. Greedy descent usually
+/// converges well within this; noisy search gets several passes over
+/// every location. The arithmetic saturates at `usize::MAX`.
+///
+/// # Examples
+///
+/// ```rust
+/// use time_scheduler::default_swap_budget;
+///
+/// assert_eq!(default_swap_budget(3, 7, 4), 250);
+/// assert_eq!(default_swap_budget(usize::MAX, 2, 0), usize::MAX);
+/// assert_eq!(default_swap_budget(1 << 40, 1 << 30, 0), usize::MAX);
+/// ```
+pub fn default_swap_budget(nplaces: usize, ntimes: usize, nunscheduled: usize) -> usize {
+    nplaces
+        .saturating_mul(ntimes)
+        .saturating_add(nunscheduled)
+        .saturating_mul(10)
+}
+
+/// [`Send`] with the `parallel` feature, which may move values between
+/// threads, and no requirement at all without it.
+#[cfg(feature = "parallel")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "parallel")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+/// [`Send`] with the `parallel` feature, which may move values between
+/// threads, and no requirement at all without it.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// [`Sync`] with the `parallel` feature, which may share values between
+/// threads, and no requirement at all without it.
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "parallel")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+/// [`Sync`] with the `parallel` feature, which may share values between
+/// threads, and no requirement at all without it.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> MaybeSync for T {}
+
 /// Adaptive timeout checker that measures swaps-per-second and adjusts checking frequency.
 struct TimeoutChecker {
     timeout_duration: Duration,
@@ -89,7 +171,9 @@ impl TimeoutChecker {
         Self {
             timeout_duration,
             start_time: Instant::now(),
-            next_check: 10, // Start with 10 swaps to get initial estimate
+            // Check before the first swap, so an expired timeout stops the
+            // run before it changes anything
+            next_check: 0,
             swaps_per_sec: None,
         }
     }
@@ -101,9 +185,14 @@ impl TimeoutChecker {
         }
 
         let elapsed = self.start_time.elapsed();
-        if elapsed > self.timeout_duration {
+        if elapsed >= self.timeout_duration {
             return true;
         }
+        if swap_iter == 0 {
+            // Wait for 10 swaps to get an initial estimate
+            self.next_check = 10;
+            return false;
+        }
 
         // Update swaps-per-second estimate
         self.swaps_per_sec = Some(swap_iter as f64 / elapsed.as_secs_f64());
@@ -113,15 +202,15 @@ impl TimeoutChecker {
         if let Some(sps) = self.swaps_per_sec {
             let estimated_remaining_swaps = (sps * remaining_time).max(0.0) as usize;
 
-            // Set next check point
+            // Set next check point; a huge budget must not wrap it around
             if estimated_remaining_swaps <= 10 {
-                self.next_check = swap_iter + 1; // Single-step when close to end
+                self.next_check = swap_iter.saturating_add(1); // Single-step when close to end
             } else {
-                self.next_check = swap_iter + (estimated_remaining_swaps / 2).max(1);
+                self.next_check = swap_iter.saturating_add((estimated_remaining_swaps / 2).max(1));
             }
         } else {
             // Fallback if calculation fails
-            self.next_check = swap_iter + 100;
+            self.next_check = swap_iter.saturating_add(100);
         }
 
         false
@@ -132,7 +221,7 @@ impl TimeoutChecker {
 ///
 /// This error is returned when attempting to access a schedule slot with
 /// invalid place or time coordinates.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum BoundsError {
     /// Place index is out of bounds.
     ///
@@ -147,8 +236,183 @@ pub enum BoundsError {
     /// in the schedule.
     #[error("Time index {0} is out of bounds")]
     Time(usize),
+
+    /// Unscheduled list index is out of bounds.
+    ///
+    /// The index provided is greater than or equal to
+    /// [`Schedule::unscheduled_len`].
+    #[error("Unscheduled index {0} is out of bounds")]
+    Unscheduled(usize),
+}
+
+/// Error type for [`Schedule::move_activity`] and [`Schedule::apply_moves`].
+#[derive(Debug, Error)]
+pub enum MoveError {
+    /// A cell is out of bounds.
+    #[error(transparent)]
+    Bounds(#[from] BoundsError),
+
+    /// There is no activity in the source cell to move.
+    #[error("no activity to move at {0:?}")]
+    EmptySource(Cell),
+
+    /// The destination cell already holds an activity.
+    #[error("destination {0:?} is already occupied")]
+    Occupied(Cell),
+}
+
+/// Error type for scheduling problems that cannot be set up.
+///
+/// Returned by [`Schedule::try_new`] and [`SchedulingInstance::validate`],
+/// and when deserializing a [`Schedule`] or [`SchedulingInstance`],
+/// typically for malformed files.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InstanceError {
+    /// The schedule grid is too large to allocate.
+    ///
+    /// The number of places times the number of time slots overflows, or the
+    /// grid would exceed the maximum allocation size.
+    #[error("a {nplaces}x{ntimes} schedule grid is too large")]
+    TooLarge {
+        /// Number of places requested
+        nplaces: usize,
+        /// Number of time slots requested
+        ntimes: usize,
+    },
+
+    /// The rows of a schedule grid have different lengths.
+    ///
+    /// Every place must have the same number of time slots as the first.
+    #[error("place {place} has {len} time slots, expected {expected}")]
+    RaggedRows {
+        /// Index of the first place with the wrong length
+        place: usize,
+        /// Number of time slots it has
+        len: usize,
+        /// Number of time slots of the first place
+        expected: usize,
+    },
+
+    /// A constraint names a cell outside the schedule grid.
+    #[error("constrained cell ({place}, {time}) is outside the grid")]
+    ConstraintOutOfBounds {
+        /// Place index of the cell
+        place: usize,
+        /// Time index of the cell
+        time: usize,
+    },
+
+    /// An instance file declares a format version this library does not
+    /// know, typically one newer than [`INSTANCE_FORMAT_VERSION`].
+    #[error(
+        "instance format version {version} is not supported \
+         (this library reads up to version {INSTANCE_FORMAT_VERSION})"
+    )]
+    UnsupportedVersion {
+        /// Version given in the file
+        version: u32,
+    },
+
+    /// An instance has no places, so nothing can be scheduled.
+    #[error("the instance has no places")]
+    NoPlaces,
+
+    /// An instance has no time slots, so nothing can be scheduled.
+    #[error("the instance has no time slots")]
+    NoTimes,
+
+    /// An instance has no activities to schedule.
+    #[error("the instance has no activities")]
+    NoActivities,
+
+    /// There is not exactly one place label per place.
+    #[error("{len} place labels for {expected} places")]
+    PlaceLabels {
+        /// Number of labels given
+        len: usize,
+        /// Number of places
+        expected: usize,
+    },
+
+    /// There is not exactly one time label per time slot.
+    #[error("{len} time labels for {expected} time slots")]
+    TimeLabels {
+        /// Number of labels given
+        len: usize,
+        /// Number of time slots
+        expected: usize,
+    },
+}
+
+/// Check that each given label list has one label per place or time slot.
+fn check_labels(
+    place_labels: Option<&[String]>,
+    time_labels: Option<&[String]>,
+    nplaces: usize,
+    ntimes: usize,
+) -> Result<(), InstanceError> {
+    match (place_labels, time_labels) {
+        (Some(labels), _) if labels.len() != nplaces => Err(InstanceError::PlaceLabels {
+            len: labels.len(),
+            expected: nplaces,
+        }),
+        (_, Some(labels)) if labels.len() != ntimes => Err(InstanceError::TimeLabels {
+            len: labels.len(),
+            expected: ntimes,
+        }),
+        _ => Ok(()),
+    }
 }
 
+/// Something unusual about an instance that still leaves it solvable.
+///
+/// Returned by [`SchedulingInstance::warnings`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InstanceWarning {
+    /// There are more activities than usable slots, so some must go
+    /// unscheduled however the search goes.
+    ///
+    /// Usable slots are those outside the buffer time slots that are not
+    /// forbidden.
+    #[error(
+        "{activities} activities for {slots} usable slots: \
+         at least {} will be unscheduled",
+        .activities - .slots
+    )]
+    Overfull {
+        /// Number of activities
+        activities: usize,
+        /// Number of usable slots
+        slots: usize,
+    },
+}
+
+/// Check that an `nplaces` by `ntimes` grid of `Option<A>` can be allocated,
+/// returning the number of slots.
+fn grid_size<A>(nplaces: usize, ntimes: usize) -> Result<usize, InstanceError> {
+    let too_large = InstanceError::TooLarge { nplaces, ntimes };
+    let nslots = nplaces.checked_mul(ntimes).ok_or(too_large.clone())?;
+    let nbytes = nslots
+        .checked_mul(std::mem::size_of::<Option<A>>())
+        .ok_or(too_large.clone())?;
+    if nbytes > isize::MAX as usize {
+        return Err(too_large);
+    }
+    Ok(nslots)
+}
+
+/// Marker for a schedule that violates hard constraints.
+///
+/// Penalty functions used with [`Schedule::improve_feasible`] return
+/// `Result<S, Infeasible>`. Because `Ok` orders before `Err`, every feasible
+/// score is better than every infeasible one. The contained count should be
+/// the number of violated constraints (or any other measure of how far the
+/// schedule is from feasibility): among infeasible schedules, fewer violations
+/// are better, which lets the search climb out of an infeasible start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Error)]
+#[error("schedule violates {0} hard constraint(s)")]
+pub struct Infeasible(pub usize);
+
 /// A scheduling problem instance containing activities and grid dimensions.
 ///
 /// This structure represents a complete scheduling problem: a set of activities
@@ -177,9 +441,38 @@ pub enum BoundsError {
 ///         Task { name: "Meeting A".to_string(), duration: 1 },
 ///         Task { name: "Meeting B".to_string(), duration: 2 },
 ///     ],
+///     buffer_times: 1, // Keep the last slot free for overflow
+///     constraints: Default::default(),
+///     place_labels: Some(vec!["Hall".into(), "Room A".into(), "Room B".into()]),
+///     time_labels: None,
 /// };
 /// ```
+///
+/// # Format versions
+///
+/// A serialized instance may carry a `"version"` number. A missing version
+/// means version 1, the current shape, and deserializing version 0 or one
+/// newer than [`INSTANCE_FORMAT_VERSION`] fails with
+/// [`InstanceError::UnsupportedVersion`] rather than misreading fields
+/// whose meaning may have changed.
+///
+/// ```rust
+/// use time_scheduler::SchedulingInstance;
+///
+/// let json = r#"{"id": "talks", "nplaces": 1, "ntimes": 2, "activities": [7]}"#;
+/// let instance: SchedulingInstance<u32> = serde_json::from_str(json).unwrap();
+/// assert_eq!(instance.activities, [7]);
+/// assert_eq!(instance.place_labels, None);
+///
+/// let future = json.replace('{', r#"{"version": 2, "#);
+/// let err = serde_json::from_str::<SchedulingInstance<u32>>(&future).unwrap_err();
+/// assert!(err.to_string().contains("version 2 is not supported"));
+/// ```
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(
+    try_from = "InstanceRepr<A>",
+    bound(deserialize = "A: Deserialize<'de>")
+)]
 pub struct SchedulingInstance<A> {
     /// Unique identifier for this problem instance
     pub id: String,
@@ -192,18 +485,336 @@ pub struct SchedulingInstance<A> {
 
     /// Activities that need to be scheduled
     pub activities: Vec<A>,
+
+    /// Number of trailing time slots kept as an overflow buffer.
+    ///
+    /// Empty buffer slots are not counted by [`Schedule::empty_slots_count`].
+    /// Defaults to 0 when missing from serialized instances.
+    #[serde(default)]
+    pub buffer_times: usize,
+
+    /// Hard constraints on where activities may go.
+    ///
+    /// Missing from serialized instances means no constraints.
+    #[serde(default, skip_serializing_if = "Constraints::is_empty")]
+    pub constraints: Constraints,
+
+    /// Display names of the places, such as `"Room A"`, one per place.
+    ///
+    /// Missing from serialized instances means places are shown by index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub place_labels: Option<Vec<String>>,
+
+    /// Display names of the time slots, such as `"9:00 AM"`, one per time
+    /// slot.
+    ///
+    /// Missing from serialized instances means time slots are shown by
+    /// index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_labels: Option<Vec<String>>,
+}
+
+/// Newest [`SchedulingInstance`] file format this library reads.
+pub const INSTANCE_FORMAT_VERSION: u32 = 1;
+
+/// On-disk shape of a [`SchedulingInstance`], before its version is
+/// checked.
+#[derive(Deserialize)]
+#[serde(rename = "SchedulingInstance")]
+struct InstanceRepr<A> {
+    #[serde(default)]
+    version: Option<u32>,
+    id: String,
+    nplaces: usize,
+    ntimes: usize,
+    activities: Vec<A>,
+    #[serde(default)]
+    buffer_times: usize,
+    #[serde(default)]
+    constraints: Constraints,
+    #[serde(default)]
+    place_labels: Option<Vec<String>>,
+    #[serde(default)]
+    time_labels: Option<Vec<String>>,
+}
+
+impl<A> TryFrom<InstanceRepr<A>> for SchedulingInstance<A> {
+    type Error = InstanceError;
+
+    fn try_from(repr: InstanceRepr<A>) -> Result<Self, InstanceError> {
+        let version = repr.version.unwrap_or(1);
+        if !(1..=INSTANCE_FORMAT_VERSION).contains(&version) {
+            return Err(InstanceError::UnsupportedVersion { version });
+        }
+        Ok(SchedulingInstance {
+            id: repr.id,
+            nplaces: repr.nplaces,
+            ntimes: repr.ntimes,
+            activities: repr.activities,
+            buffer_times: repr.buffer_times,
+            constraints: repr.constraints,
+            place_labels: repr.place_labels,
+            time_labels: repr.time_labels,
+        })
+    }
+}
+
+impl<A> SchedulingInstance<A> {
+    /// Check that this instance describes a problem that can be scheduled.
+    ///
+    /// Instances read from files may carry absurd dimensions; checking them
+    /// up front gives a clear error where [`Schedule::new`] would panic or
+    /// quietly build a degenerate schedule. Instances that are merely
+    /// unusual pass, and [`warnings`](SchedulingInstance::warnings) reports
+    /// on them.
+    ///
+    /// # Errors
+    ///
+    /// Returns, in the order checked:
+    ///
+    /// * [`InstanceError::NoPlaces`] or [`InstanceError::NoTimes`] if the
+    ///   grid has no cells;
+    /// * [`InstanceError::TooLarge`] if the grid, together with the
+    ///   activities, is too large to represent;
+    /// * [`InstanceError::PlaceLabels`] or [`InstanceError::TimeLabels`] if
+    ///   there are labels, but not one per place or time slot;
+    /// * [`InstanceError::ConstraintOutOfBounds`] if a constraint names a
+    ///   cell outside the grid;
+    /// * [`InstanceError::NoActivities`] if there is nothing to schedule.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{InstanceError, SchedulingInstance};
+    ///
+    /// let instance = SchedulingInstance {
+    ///     id: "huge".to_string(),
+    ///     nplaces: usize::MAX,
+    ///     ntimes: 2,
+    ///     activities: vec![1, 2, 3],
+    ///     buffer_times: 0,
+    ///     constraints: Default::default(),
+    ///     place_labels: None,
+    ///     time_labels: None,
+    /// };
+    /// assert!(matches!(instance.validate(), Err(InstanceError::TooLarge { .. })));
+    ///
+    /// let empty = SchedulingInstance { nplaces: 0, ..instance };
+    /// assert_eq!(empty.validate(), Err(InstanceError::NoPlaces));
+    /// ```
+    pub fn validate(&self) -> Result<(), InstanceError> {
+        if self.nplaces == 0 {
+            return Err(InstanceError::NoPlaces);
+        }
+        if self.ntimes == 0 {
+            return Err(InstanceError::NoTimes);
+        }
+        let nslots = grid_size::<A>(self.nplaces, self.ntimes)?;
+        nslots
+            .checked_add(self.activities.len())
+            .ok_or(InstanceError::TooLarge {
+                nplaces: self.nplaces,
+                ntimes: self.ntimes,
+            })?;
+        check_labels(
+            self.place_labels.as_deref(),
+            self.time_labels.as_deref(),
+            self.nplaces,
+            self.ntimes,
+        )?;
+        self.constraints.check(self.nplaces, self.ntimes)?;
+        if self.activities.is_empty() {
+            return Err(InstanceError::NoActivities);
+        }
+        Ok(())
+    }
+
+    /// Report anything unusual about this instance that still lets it be
+    /// solved, for showing to whoever wrote it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{InstanceWarning, SchedulingInstance};
+    ///
+    /// let instance = SchedulingInstance {
+    ///     id: "talks".to_string(),
+    ///     nplaces: 2,
+    ///     ntimes: 3,
+    ///     activities: (0..8).collect(),
+    ///     buffer_times: 1,
+    ///     constraints: Default::default(),
+    ///     place_labels: None,
+    ///     time_labels: None,
+    /// };
+    /// assert_eq!(
+    ///     instance.warnings(),
+    ///     [InstanceWarning::Overfull { activities: 8, slots: 4 }]
+    /// );
+    /// assert_eq!(
+    ///     instance.warnings()[0].to_string(),
+    ///     "8 activities for 4 usable slots: at least 4 will be unscheduled"
+    /// );
+    /// ```
+    pub fn warnings(&self) -> Vec<InstanceWarning> {
+        let slots = self.usable_slots();
+        let activities = self.activities.len();
+        let mut warnings = Vec::new();
+        if activities > slots {
+            warnings.push(InstanceWarning::Overfull { activities, slots });
+        }
+        warnings
+    }
+
+    /// Slots outside the buffer time slots that are not forbidden.
+    fn usable_slots(&self) -> usize {
+        let usable_times = self.ntimes.saturating_sub(self.buffer_times);
+        let forbidden = self
+            .constraints
+            .forbidden
+            .iter()
+            .filter(|&&(place, time)| place < self.nplaces && time < usable_times)
+            .count();
+        self.nplaces
+            .saturating_mul(usable_times)
+            .saturating_sub(forbidden)
+    }
+
+    /// A cheap estimate of how hard this instance is to schedule, for
+    /// handing more search budget to the hard ones.
+    ///
+    /// The score is `fill × (1 + concentration + spread)`, where:
+    ///
+    /// * `fill` is the number of activities divided by the usable slots:
+    ///   slots outside the [`buffer_times`](SchedulingInstance::buffer_times)
+    ///   that are not [forbidden](Constraints::forbidden). Above 1 some
+    ///   activities must go unscheduled.
+    /// * `concentration` is the sum of the squared shares of each topic
+    ///   given by `topic`, from near 0 for many evenly used topics up to 1
+    ///   when every activity shares one topic.
+    /// * `spread` is the coefficient of variation (standard deviation over
+    ///   mean) of the weights given by `priority`, or 0 when they average 0.
+    ///
+    /// An instance without activities scores 0, and one with activities but
+    /// no usable slots scores infinity. Scores only compare instances; they
+    /// have no unit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::SchedulingInstance;
+    ///
+    /// // Activities are (topic, priority) pairs
+    /// let instance = |nplaces, activities: Vec<(usize, f32)>| SchedulingInstance {
+    ///     id: "talks".to_string(),
+    ///     nplaces,
+    ///     ntimes: 4,
+    ///     activities,
+    ///     buffer_times: 0,
+    ///     constraints: Default::default(),
+    ///     place_labels: None,
+    ///     time_labels: None,
+    /// };
+    ///
+    /// let packed = instance(2, vec![(0, 1.0); 8]);
+    /// let sparse = instance(4, (0..4).map(|t| (t, 1.0)).collect());
+    /// let score = |i: &SchedulingInstance<(usize, f32)>| i.difficulty(|a| a.0, |a| a.1);
+    /// assert!(score(&packed) > score(&sparse));
+    /// assert_eq!(score(&packed), 2.0);
+    /// ```
+    pub fn difficulty(&self, topic: impl Fn(&A) -> usize, priority: impl Fn(&A) -> f32) -> f32 {
+        let n = self.activities.len();
+        if n == 0 {
+            return 0.0;
+        }
+        let capacity = self.usable_slots();
+        if capacity == 0 {
+            return f32::INFINITY;
+        }
+        let fill = n as f32 / capacity as f32;
+
+        let mut topic_counts = BTreeMap::new();
+        for activity in &self.activities {
+            *topic_counts.entry(topic(activity)).or_insert(0usize) += 1;
+        }
+        let concentration = topic_counts
+            .values()
+            .map(|&count| (count as f32 / n as f32).powi(2))
+            .sum::<f32>();
+
+        let priorities: Vec<f32> = self.activities.iter().map(priority).collect();
+        let mean = priorities.iter().sum::<f32>() / n as f32;
+        let spread = if mean == 0.0 {
+            0.0
+        } else {
+            let variance = priorities.iter().map(|p| (p - mean).powi(2)).sum::<f32>() / n as f32;
+            variance.sqrt() / mean.abs()
+        };
+
+        fill * (1.0 + concentration + spread)
+    }
+}
+
+/// Hard constraints on where a [`Schedule`]'s activities may go.
+///
+/// Attach constraints with [`Schedule::with_constraints`], or give them in
+/// an instance's `"constraints"` object. The built-in search never swaps a
+/// constrained cell, so they hold throughout improvement without any help
+/// from the penalty function. Constraints that depend on the activities
+/// themselves, such as per-topic caps, belong in the penalty function.
+///
+/// # Examples
+///
+/// ```rust
+/// use time_scheduler::Constraints;
+///
+/// let json = r#"{"locked": [[0, 0]], "forbidden": [[1, 2], [1, 3]]}"#;
+/// let constraints: Constraints = serde_json::from_str(json).unwrap();
+/// assert_eq!(constraints.locked, [(0, 0)]);
+/// assert_eq!(serde_json::to_string(&constraints).unwrap(), json.replace(' ', ""));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Constraints {
+    /// `(place, time)` cells whose contents stay put, full or empty
+    pub locked: Vec<(usize, usize)>,
+
+    /// `(place, time)` cells that must stay empty, such as a room that is
+    /// unavailable at some time
+    pub forbidden: Vec<(usize, usize)>,
+}
+
+impl Constraints {
+    /// Whether there are no constraints at all.
+    pub fn is_empty(&self) -> bool {
+        self.locked.is_empty() && self.forbidden.is_empty()
+    }
+
+    /// Check that every constrained cell lies in an `nplaces` by `ntimes` grid.
+    fn check(&self, nplaces: usize, ntimes: usize) -> Result<(), InstanceError> {
+        match self
+            .locked
+            .iter()
+            .chain(&self.forbidden)
+            .find(|&&(p, t)| p >= nplaces || t >= ntimes)
+        {
+            Some(&(place, time)) => Err(InstanceError::ConstraintOutOfBounds { place, time }),
+            None => Ok(()),
+        }
+    }
 }
 
-/// Internal representation of a location in the schedule.
+/// A location in a [`Schedule`] that can hold an activity.
 ///
-/// This enum is used internally by the optimization algorithm to represent
-/// either a slot in the 2D grid or a position in the unscheduled activities list.
-#[derive(Debug, Clone, Copy)]
-enum Loc {
-    /// A slot in the 2D schedule grid at (place, time)
-    S(usize, usize),
-    /// An index in the unscheduled activities vector
-    U(usize),
+/// Used with [`Schedule::swap`] and [`Schedule::swap_unchecked`] to move
+/// activities between grid slots and the unscheduled list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cell {
+    /// A slot in the 2D schedule grid at `(place, time)`
+    Slot(usize, usize),
+    /// A position in the unscheduled list, below
+    /// [`Schedule::unscheduled_len`]
+    Unscheduled(usize),
 }
 
 /// A schedule representing the assignment of activities to time slots and places.
@@ -243,13 +854,178 @@ enum Loc {
 /// let unscheduled_count = schedule.get_unscheduled_activities().count();
 /// println!("Unscheduled: {}", unscheduled_count);
 /// ```
-#[derive(Debug, Clone)]
+///
+/// # Serialization
+///
+/// Schedules serialize as an object with `slots`, one array per place
+/// holding that place's time slots (`null` for empty ones), the
+/// `unscheduled` activities, and `buffer_times`:
+///
+/// ```json
+/// {"slots": [[1, 2], [3, null]], "unscheduled": [], "buffer_times": 0}
+/// ```
+///
+/// Non-empty [`Constraints`] are written as `constraints`. Deserializing
+/// rejects rows of different lengths, and a missing `buffer_times`
+/// defaults to 0. Empty entries the search leaves in the unscheduled list
+/// are not written, so only a schedule without them reads back equal to
+/// the original.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    into = "ScheduleRepr<A>",
+    try_from = "ScheduleRepr<A>",
+    bound(
+        serialize = "A: Clone + Serialize",
+        deserialize = "A: Clone + Deserialize<'de>"
+    )
+)]
 pub struct Schedule<A> {
     /// 2D array of schedule slots, indexed by (place, time)
     slots: Array2<Option<A>>,
 
     /// Activities that haven't been assigned to a slot yet
     unscheduled: Vec<Option<A>>,
+
+    /// Number of trailing time slots that may stay empty without counting
+    buffer_times: usize,
+
+    /// Cells the search leaves alone, sorted and deduplicated
+    constraints: Constraints,
+
+    /// Display names of the places, one per place, if given
+    place_labels: Option<Vec<String>>,
+
+    /// Display names of the time slots, one per time slot, if given
+    time_labels: Option<Vec<String>>,
+
+    /// Activities in `slots` and `unscheduled`, kept up to date by every
+    /// edit that can change it
+    nactivities: usize,
+}
+
+/// On-disk shape of a [`Schedule`]: nested rows, outer index place.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Schedule")]
+struct ScheduleRepr<A> {
+    slots: Vec<Vec<Option<A>>>,
+    unscheduled: Vec<A>,
+    #[serde(default)]
+    buffer_times: usize,
+    #[serde(default, skip_serializing_if = "Constraints::is_empty")]
+    constraints: Constraints,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    place_labels: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_labels: Option<Vec<String>>,
+}
+
+impl<A: Clone> From<Schedule<A>> for ScheduleRepr<A> {
+    fn from(schedule: Schedule<A>) -> Self {
+        let slots = schedule
+            .slots
+            .rows()
+            .into_iter()
+            .map(|row| row.to_vec())
+            .collect();
+        Self {
+            slots,
+            unscheduled: schedule.unscheduled.into_iter().flatten().collect(),
+            buffer_times: schedule.buffer_times,
+            constraints: schedule.constraints,
+            place_labels: schedule.place_labels,
+            time_labels: schedule.time_labels,
+        }
+    }
+}
+
+impl<A: Clone> TryFrom<ScheduleRepr<A>> for Schedule<A> {
+    type Error = InstanceError;
+
+    fn try_from(repr: ScheduleRepr<A>) -> Result<Self, InstanceError> {
+        let nplaces = repr.slots.len();
+        let ntimes = repr.slots.first().map_or(0, Vec::len);
+        if let Some((place, row)) = repr
+            .slots
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != ntimes)
+        {
+            return Err(InstanceError::RaggedRows {
+                place,
+                len: row.len(),
+                expected: ntimes,
+            });
+        }
+        repr.constraints.check(nplaces, ntimes)?;
+        check_labels(
+            repr.place_labels.as_deref(),
+            repr.time_labels.as_deref(),
+            nplaces,
+            ntimes,
+        )?;
+        let cells = repr.slots.into_iter().flatten().collect();
+        let slots = Array2::from_shape_vec((nplaces, ntimes), cells)
+            .map_err(|_| InstanceError::TooLarge { nplaces, ntimes })?;
+        let mut schedule = Schedule {
+            slots,
+            unscheduled: repr.unscheduled.into_iter().map(Some).collect(),
+            buffer_times: 0,
+            constraints: Constraints::default(),
+            place_labels: repr.place_labels,
+            time_labels: repr.time_labels,
+            nactivities: 0,
+        };
+        schedule.recount();
+        Ok(schedule
+            .with_buffer_times(repr.buffer_times)
+            .with_constraints(repr.constraints))
+    }
+}
+
+/// Build a schedule from a grid written as one row of time slots per place.
+///
+/// `rows[p][t]` is the activity at place `p` and time `t`. There are no
+/// unscheduled activities and no buffer time slots. This is handy for test
+/// fixtures and hand-authored schedules.
+///
+/// # Errors
+///
+/// Returns [`InstanceError::RaggedRows`] if the rows differ in length.
+///
+/// # Examples
+///
+/// ```rust
+/// use time_scheduler::{InstanceError, Schedule};
+///
+/// let schedule = Schedule::try_from(vec![
+///     vec![Some("keynote"), None],
+///     vec![Some("rust"), Some("lunch")],
+/// ])
+/// .unwrap();
+/// assert_eq!(schedule.dimensions(), (2, 2));
+/// assert_eq!(schedule.get_activity_at(1, 1).unwrap(), Some(&"lunch"));
+/// assert_eq!(schedule.unscheduled_len(), 0);
+///
+/// let ragged = Schedule::try_from(vec![vec![Some(1), None], vec![Some(2)]]);
+/// assert_eq!(
+///     ragged.err(),
+///     Some(InstanceError::RaggedRows { place: 1, len: 1, expected: 2 })
+/// );
+/// ```
+impl<A: Clone> TryFrom<Vec<Vec<Option<A>>>> for Schedule<A> {
+    type Error = InstanceError;
+
+    fn try_from(rows: Vec<Vec<Option<A>>>) -> Result<Self, InstanceError> {
+        ScheduleRepr {
+            slots: rows,
+            unscheduled: Vec::new(),
+            buffer_times: 0,
+            constraints: Constraints::default(),
+            place_labels: None,
+            time_labels: None,
+        }
+        .try_into()
+    }
 }
 
 /// Builder for configuring schedule improvement parameters.
@@ -257,6 +1033,26 @@ pub struct Schedule<A> {
 /// Provides a fluent API for setting improvement parameters with sensible defaults.
 /// Use [`Schedule::improve`] to create an improver instance.
 ///
+/// # Defaults
+///
+/// A setter that is never called leaves its default in place:
+///
+/// * [`max_swaps`](Improver::max_swaps): [`default_swap_budget`] per run,
+///   or no limit when a [`timeout`](Improver::timeout) is set
+/// * [`noise_prob`](Improver::noise_prob): 0, pure hill climbing that
+///   stops at the first local optimum
+/// * [`restarts`](Improver::restarts): a single run, no restarts
+/// * [`timeout`](Improver::timeout) and
+///   [`soft_deadline`](Improver::soft_deadline): none
+/// * [`with_rng_seed`](Improver::with_rng_seed): a fresh random seed, so
+///   runs with noise or restarts differ
+/// * [`scan_order`](Improver::scan_order): [`ScanOrder::Lexicographic`],
+///   applying the best swap of each scan
+/// * [`restart_policy`](Improver::restart_policy):
+///   [`RestartPolicy::KeepBest`], and
+///   [`restart_shuffle`](Improver::restart_shuffle):
+///   [`RestartShuffle::Uniform`]
+///
 /// # Examples
 ///
 /// ```rust
@@ -277,48 +1073,423 @@ pub struct Schedule<A> {
 ///     .restarts(10)
 ///     .run();
 /// ```
-pub struct Improver<'a, A, F> {
+pub struct Improver<'a, A, F, P> {
     schedule: &'a mut Schedule<A>,
     penalty_fn: F,
+    feasible: fn(&P) -> bool,
+    config: ImproveConfig,
+    on_progress: Option<ProgressCallback<'a, A, P>>,
+    restarts: Restarts<'a, A, P>,
+    swap_costs: Option<SwapCostOps<P>>,
+    /// Energy of a penalty, for [`Improver::acceptance`]
+    energy: Option<fn(&P) -> f64>,
+    moves: Moves<'a, A, P>,
+}
+
+type ProgressCallback<'a, A, P> = Box<dyn FnMut(&Progress<'_, A, P>) + 'a>;
+
+type RestartCallback<'a, P> = Box<dyn FnMut(usize, P) -> bool + 'a>;
+
+/// Penalty change from swapping two cells, for [`Improver::penalty_delta`].
+#[cfg(feature = "parallel")]
+type DeltaFn<'a, A, P> = Box<dyn Fn(&Schedule<A>, Cell, Cell) -> P + Send + Sync + 'a>;
+
+/// Penalty change from swapping two cells, for [`Improver::penalty_delta`].
+#[cfg(not(feature = "parallel"))]
+type DeltaFn<'a, A, P> = Box<dyn Fn(&Schedule<A>, Cell, Cell) -> P + 'a>;
+
+/// A caller's [`DeltaFn`], with the addition to apply its changes.
+struct PenaltyDelta<'a, A, P> {
+    delta: DeltaFn<'a, A, P>,
+    add: fn(P, P) -> P,
+}
+
+/// Which slots an activity may occupy, for [`Improver::allowed_slots`].
+#[cfg(feature = "parallel")]
+type AllowedFn<'a, A> = Box<dyn Fn(&A, usize, usize) -> bool + Send + Sync + 'a>;
+
+/// Which slots an activity may occupy, for [`Improver::allowed_slots`].
+#[cfg(not(feature = "parallel"))]
+type AllowedFn<'a, A> = Box<dyn Fn(&A, usize, usize) -> bool + 'a>;
+
+/// What the caller knows about the swap neighborhood.
+struct Moves<'a, A, P> {
+    penalty_delta: Option<PenaltyDelta<'a, A, P>>,
+    allowed: Option<AllowedFn<'a, A>>,
+}
+
+impl<A: Clone, P> Moves<'_, A, P> {
+    /// Whether swapping `a` and `b` leaves each activity somewhere it may be.
+    fn permit(&self, schedule: &Schedule<A>, a: Cell, b: Cell) -> bool {
+        let Some(allowed) = &self.allowed else {
+            return true;
+        };
+        let fits = |activity: Option<&A>, cell| match (activity, cell) {
+            (Some(activity), Cell::Slot(p, t)) => allowed(activity, p, t),
+            _ => true,
+        };
+        fits(schedule.occupant(a), b) && fits(schedule.occupant(b), a)
+    }
+}
+
+/// What an [`Improver`] does between runs.
+struct Restarts<'a, A, P> {
+    shuffle: RestartShuffle<'a, A>,
+    on_restart: Option<RestartCallback<'a, P>>,
+}
+
+/// One objective of a [`Schedule::improve_lexicographic`] ranking.
+pub type Objective<'a, A, P> = dyn Fn(&Schedule<A>) -> P + 'a;
+
+/// Where one run of a profiled search spent its time.
+///
+/// Recorded by [`Improver::profile`] and read from
+/// [`Steps::run_profiles`]. Evaluation is timed a phase at a time, not
+/// call by call: a whole greedy scan counts as evaluating, including the
+/// trial swaps it makes and undoes. The rest of the run, mostly applying
+/// the chosen swaps and bookkeeping, is what remains.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Time spent evaluating moves: greedy scans, noise moves and
+    /// re-evaluations of the current schedule
+    pub evaluating: Duration,
+    /// Time spent reshuffling the schedule before a restart
+    pub shuffling: Duration,
+    /// Wall-clock time of the whole run
+    pub total: Duration,
+}
+
+impl Profile {
+    /// Time spent outside evaluation and reshuffling: applying swaps and
+    /// the search's own bookkeeping.
+    pub fn swapping(&self) -> Duration {
+        self.total.saturating_sub(self.evaluating + self.shuffling)
+    }
+}
+
+/// Search state reported to an [`Improver::on_progress`] callback.
+#[derive(Debug)]
+pub struct Progress<'s, A, P> {
+    /// The best schedule found so far.
+    pub schedule: &'s Schedule<A>,
+    /// The penalty of [`schedule`](Progress::schedule).
+    pub penalty: P,
+    /// Time since the improvement started.
+    pub elapsed: Duration,
+    /// Whether the [soft deadline](Improver::soft_deadline) has passed, so
+    /// that [`schedule`](Progress::schedule) is ready to be shown.
+    pub soft_deadline_passed: bool,
+}
+
+/// Delivers [`Progress`] reports during an improvement.
+struct Observer<'a, A, P> {
+    callback: Option<ProgressCallback<'a, A, P>>,
+    start: Instant,
+    soft_deadline: Option<Duration>,
+    soft_deadline_passed: bool,
+    /// Best penalty reported so far, across all runs
+    reported: Option<P>,
+    /// Copy of the best schedule, kept only until the soft deadline passes
+    best: Option<Schedule<A>>,
+}
+
+impl<'a, A: Clone, P: Copy + PartialOrd> Observer<'a, A, P> {
+    fn new(callback: Option<ProgressCallback<'a, A, P>>, soft_deadline: Option<Duration>) -> Self {
+        Self {
+            callback,
+            start: Instant::now(),
+            soft_deadline,
+            soft_deadline_passed: false,
+            reported: None,
+            best: None,
+        }
+    }
+
+    /// Report `schedule` if it beats everything reported so far.
+    fn improved(&mut self, schedule: &Schedule<A>, penalty: P) {
+        // Announce the deadline first so no report after it claims otherwise
+        self.poll();
+        let Some(callback) = &mut self.callback else {
+            return;
+        };
+        if let Some(best) = self.reported {
+            if penalty.partial_cmp(&best) != Some(Ordering::Less) {
+                return;
+            }
+        }
+        self.reported = Some(penalty);
+        if self.soft_deadline.is_some() && !self.soft_deadline_passed {
+            self.best = Some(schedule.clone());
+        }
+        callback(&Progress {
+            schedule,
+            penalty,
+            elapsed: self.start.elapsed(),
+            soft_deadline_passed: self.soft_deadline_passed,
+        });
+    }
+
+    /// Report the best schedule again once the soft deadline has passed.
+    fn poll(&mut self) {
+        let Some(deadline) = self.soft_deadline else {
+            return;
+        };
+        let elapsed = self.start.elapsed();
+        if self.soft_deadline_passed || elapsed < deadline {
+            return;
+        }
+        self.soft_deadline_passed = true;
+        debug!("soft deadline passed after {elapsed:?}");
+        if let (Some(callback), Some(schedule), Some(penalty)) =
+            (&mut self.callback, self.best.take(), self.reported)
+        {
+            callback(&Progress {
+                schedule: &schedule,
+                penalty,
+                elapsed,
+                soft_deadline_passed: true,
+            });
+        }
+    }
+}
+
+/// Search parameters collected by an [`Improver`].
+#[derive(Debug, Clone, Default)]
+struct ImproveConfig {
     max_swaps: Option<usize>,
-    noise: bool,
+    noise_prob: f32,
     restarts: Option<usize>,
     timeout: Option<Duration>,
     proportional_restarts: bool,
+    seed: Option<u64>,
+    target_scheduled: Option<usize>,
+    restart_policy: RestartPolicy,
+    scan_order: ScanOrder,
+    first_improvement: bool,
+    tabu_tenure: usize,
+    acceptance: Option<Acceptance>,
+    soft_deadline: Option<Duration>,
+    profile: bool,
+    fill_only: bool,
+    or_moves: bool,
+    /// Cells locked for this search only, from [`Improver::pin`]
+    pinned: Vec<(usize, usize)>,
 }
 
-impl<'a, A: Clone, F, P> Improver<'a, A, F>
-where
-    F: Fn(&Schedule<A>) -> P,
-    P: Copy + PartialOrd,
-{
-    fn new(schedule: &'a mut Schedule<A>, penalty_fn: F) -> Self {
-        Self {
-            schedule,
-            penalty_fn,
-            max_swaps: None,
-            noise: false,
-            restarts: None,
-            timeout: None,
-            proportional_restarts: false,
+impl ImproveConfig {
+    /// The parameters of each run: the swap budget resolved, warning about
+    /// any fallback, and budgets divided for proportional restarts.
+    fn per_run(&self, (nplaces, ntimes): (usize, usize), nunscheduled: usize) -> Self {
+        let num_restarts = self.restarts.unwrap_or(0);
+
+        let max_swaps = match (self.max_swaps, self.timeout) {
+            (Some(n), _) => n,
+            (None, Some(timeout)) => {
+                warn!("no swap budget set, running until the {timeout:?} timeout");
+                usize::MAX
+            }
+            (None, None) => {
+                let n = default_swap_budget(nplaces, ntimes, nunscheduled);
+                warn!("no swap budget set, defaulting to {n} swaps per run");
+                n
+            }
+        };
+        let unending = self.noise_prob > 0.0 || self.tabu_tenure > 0 || self.acceptance.is_some();
+        if max_swaps == usize::MAX && self.timeout.is_none() && unending {
+            warn!("swap budget of usize::MAX without a timeout: the search will not stop");
+        }
+
+        // Calculate per-run resources if proportional restarts are used
+        let proportional = self.proportional_restarts && num_restarts > 1;
+        ImproveConfig {
+            timeout: if proportional {
+                self.timeout.map(|t| {
+                    let nanos = t.as_nanos() / num_restarts as u128;
+                    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+                })
+            } else {
+                self.timeout
+            },
+            // An unlimited budget stays unlimited, and a small one still
+            // leaves every run at least one swap
+            max_swaps: if proportional && max_swaps != usize::MAX {
+                Some((max_swaps / num_restarts).max(max_swaps.min(1)))
+            } else {
+                Some(max_swaps)
+            },
+            ..self.clone()
         }
     }
+}
 
-    /// Set the maximum number of swap attempts per improvement run.
-    ///
-    /// If not specified, defaults to `5 * (total_locations)^2`. Note that pure
-    /// greedy search (without noise) will terminate early when no improvement
-    /// is found, making this limit primarily relevant for noisy search.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
+/// Order in which the greedy step visits candidate swaps.
+///
+/// The greedy step applies the best swap it finds, so the order only
+/// decides which of several equally good swaps wins. Set with
+/// [`Improver::scan_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ScanOrder {
+    /// Location pairs in [`Schedule::all_cells`] order; the earliest of
+    /// several equally good swaps wins.
+    #[default]
+    Lexicographic,
+    /// Location pairs in reverse [`Schedule::all_cells`] order.
+    Reversed,
+    /// A fresh random permutation of the locations at every greedy step,
+    /// drawn from the improver's random number generator.
+    Shuffled,
+}
+
+/// When a sampling search keeps a random swap that makes things worse.
+///
+/// Set with [`Improver::acceptance`], which measures each schedule by an
+/// *energy*: a number standing for its penalty, lower being better. Every
+/// rule keeps a swap that leaves the energy no higher.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Acceptance {
+    /// Simulated annealing: keep a swap raising the energy by `d` with
+    /// probability `exp(-d / temperature)`. The temperature starts at
+    /// `initial_temperature`, which must be positive, and is multiplied by
+    /// `cooling`, within `0.0..=1.0` but not 0, after every swap tried.
+    Annealing {
+        initial_temperature: f64,
+        cooling: f64,
+    },
+    /// Great deluge: keep a swap whose energy is at most the water level.
+    /// The level starts at the initial energy and falls by `rain`, which
+    /// must not be negative, after every swap tried.
+    GreatDeluge { rain: f64 },
+    /// Late acceptance hill climbing: keep a swap whose energy is at most
+    /// that of the current schedule `history` swaps ago. `history` must be
+    /// at least 1; a history of 1 is plain hill climbing on random swaps.
+    Late { history: usize },
+}
+
+impl Acceptance {
+    /// Panic unless the rule's parameters are in range.
+    fn check(&self) {
+        match *self {
+            Acceptance::Annealing {
+                initial_temperature,
+                cooling,
+            } => {
+                assert!(
+                    initial_temperature > 0.0 && initial_temperature.is_finite(),
+                    "initial temperature {initial_temperature} is not positive"
+                );
+                assert!(
+                    cooling > 0.0 && cooling <= 1.0,
+                    "cooling factor {cooling} is not within 0.0..=1.0, excluding 0"
+                );
+            }
+            Acceptance::GreatDeluge { rain } => {
+                assert!(
+                    rain >= 0.0 && rain.is_finite(),
+                    "rain speed {rain} is negative"
+                );
+            }
+            Acceptance::Late { history } => {
+                assert!(history > 0, "late acceptance needs a history of at least 1");
+            }
+        }
+    }
+}
+
+/// Which result an [`Improver`] keeps across restarts.
+///
+/// Set with [`Improver::restart_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RestartPolicy {
+    /// Keep the best result of all runs; a later run replaces it only if
+    /// strictly better.
+    #[default]
+    KeepBest,
+    /// Keep the best result of all runs, letting a later run replace it
+    /// when equally good.
+    AcceptEqual,
+    /// Keep whatever the final run ends with, even if an earlier run did
+    /// better: a random walk over restarts.
+    Continue,
+}
+
+/// How an [`Improver`] rearranges the schedule before each restart.
+///
+/// Set with [`Improver::restart_shuffle`].
+#[derive(Default)]
+pub enum RestartShuffle<'a, A> {
+    /// A uniformly random layout, from [`Schedule::shuffle`].
+    #[default]
+    Uniform,
+    /// A random layout that spreads each topic across the time slots, from
+    /// [`Schedule::shuffle_balanced`] with this topic function.
+    Balanced(Box<dyn Fn(&A) -> usize + 'a>),
+}
+
+impl<'a, A> RestartShuffle<'a, A> {
+    /// Balance restarts by the topic `topic` gives each activity.
+    pub fn balanced(topic: impl Fn(&A) -> usize + 'a) -> Self {
+        RestartShuffle::Balanced(Box::new(topic))
+    }
+}
+
+impl<A: Clone> RestartShuffle<'_, A> {
+    fn apply(&self, schedule: &mut Schedule<A>, rng: &mut Rng) {
+        match self {
+            RestartShuffle::Uniform => schedule.shuffle(rng),
+            RestartShuffle::Balanced(topic) => schedule.shuffle_balanced(rng, topic),
+        }
+    }
+}
+
+impl<'a, A: Clone, F, P> Improver<'a, A, F, P>
+where
+    F: Fn(&Schedule<A>) -> P,
+    P: Copy + PartialOrd + Debug,
+{
+    fn new(schedule: &'a mut Schedule<A>, penalty_fn: F, feasible: fn(&P) -> bool) -> Self {
+        Self {
+            schedule,
+            penalty_fn,
+            feasible,
+            config: ImproveConfig::default(),
+            on_progress: None,
+            restarts: Restarts {
+                shuffle: RestartShuffle::Uniform,
+                on_restart: None,
+            },
+            swap_costs: None,
+            energy: None,
+            moves: Moves {
+                penalty_delta: None,
+                allowed: None,
+            },
+        }
+    }
+
+    /// Set the maximum number of swap attempts per improvement run.
+    ///
+    /// If not specified and no [`timeout`](Improver::timeout) is set, defaults
+    /// to [`default_swap_budget`], `10 * total_locations` swaps; each greedy
+    /// swap scans every pair of locations, so this is cubic work overall.
+    /// With a timeout and no explicit budget, the run is bounded by the
+    /// timeout alone. Either fallback is reported with `log::warn!`. Note
+    /// that pure greedy search (without noise) will terminate early when no
+    /// improvement is found, making this limit primarily relevant for noisy
+    /// search.
+    ///
+    /// Any budget is valid, up to `usize::MAX`, which means no limit: pair
+    /// it with a timeout, as noisy search would otherwise run forever. That
+    /// combination is also reported with `log::warn!`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
     /// # use time_scheduler::Schedule;
     /// let mut schedule = Schedule::new(2, 2, std::iter::empty::<i32>());
     /// schedule.improve(|_| (0usize, 0.0f32)).max_swaps(1000).run();
     /// ```
     pub fn max_swaps(mut self, max_swaps: usize) -> Self {
-        self.max_swaps = Some(max_swaps);
+        self.config.max_swaps = Some(max_swaps);
         self
     }
 
@@ -335,8 +1506,54 @@ where
     /// let mut schedule = Schedule::new(2, 2, std::iter::empty::<i32>());
     /// schedule.improve(|_| (0usize, 0.0f32)).with_noise().run();
     /// ```
-    pub fn with_noise(mut self) -> Self {
-        self.noise = true;
+    ///
+    /// This is shorthand for [`noise_prob(0.5)`](Improver::noise_prob).
+    pub fn with_noise(self) -> Self {
+        self.noise_prob(0.5)
+    }
+
+    /// Set the probability of taking a random noise move at each swap.
+    ///
+    /// At each swap the search makes a random move with probability `prob`,
+    /// and otherwise applies the best improving swap. With `0.0` the search is
+    /// purely greedy and stops at the first local optimum; with `1.0` it only
+    /// makes random moves. The best solution seen is still the one returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prob` is not within `0.0..=1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// use std::cell::Cell;
+    ///
+    /// // Only random moves: one penalty evaluation per swap, plus the initial one
+    /// let evaluations = Cell::new(0);
+    /// let penalty = |s: &Schedule<i32>| {
+    ///     evaluations.set(evaluations.get() + 1);
+    ///     s.empty_slots_count()
+    /// };
+    /// let mut schedule = Schedule::new(2, 2, vec![1, 2, 3].into_iter());
+    /// schedule.improve(penalty).noise_prob(1.0).max_swaps(20).run();
+    /// assert_eq!(evaluations.get(), 21);
+    ///
+    /// // No noise at all: identical to plain greedy search
+    /// let penalty = |s: &Schedule<i32>| -s.get_activity_at(0, 0).unwrap().copied().unwrap_or(0);
+    /// let mut greedy = Schedule::new(2, 2, vec![1, 2, 3, 4, 5].into_iter());
+    /// let mut zero = greedy.clone();
+    /// greedy.improve(penalty).with_rng_seed(3).run();
+    /// zero.improve(penalty).noise_prob(0.0).with_rng_seed(3).run();
+    /// assert_eq!(greedy.slots(), zero.slots());
+    /// assert_eq!(greedy.get_activity_at(0, 0).unwrap(), Some(&5));
+    /// ```
+    pub fn noise_prob(mut self, prob: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&prob),
+            "noise probability {prob} is not within 0.0..=1.0"
+        );
+        self.config.noise_prob = prob;
         self
     }
 
@@ -344,8 +1561,15 @@ where
     ///
     /// Values of 0 or 1 result in a single run with no restarts. Values ≥ 2
     /// perform additional restarts with random reshuffling. Each restart begins
-    /// with a random reshuffling of the current schedule, then runs a full
-    /// improvement process. The best solution across all runs is returned.
+    /// with a random reshuffling of the starting schedule, or of the previous
+    /// run's result under [`RestartPolicy::Continue`], then runs a full
+    /// improvement process. The best solution across all runs is returned,
+    /// unless a different [`restart_policy`](Improver::restart_policy) is set.
+    ///
+    /// The initial run always starts from the schedule's current layout and is
+    /// never reshuffled, so an already-optimized (warm-started) schedule is
+    /// only ever replaced by a strictly better one.
+    /// [`Steps::restart_penalties`] reports how each run finished.
     ///
     /// # Examples
     ///
@@ -354,384 +1578,713 @@ where
     /// let mut schedule = Schedule::new(2, 2, std::iter::empty::<i32>());
     /// schedule.improve(|_| (0usize, 0.0f32)).restarts(5).run();
     /// ```
+    ///
+    /// Warm-starting from a previous optimum keeps it:
+    ///
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// // Prefer larger activities in earlier slots
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     s.slots().indexed_iter()
+    ///         .filter_map(|((_, t), a)| a.map(|a| a * t))
+    ///         .sum::<usize>()
+    /// };
+    ///
+    /// let mut schedule = Schedule::new(2, 3, 0..6);
+    /// schedule.improve(penalty).run();
+    /// let optimum = schedule.clone();
+    ///
+    /// schedule.improve(penalty).with_noise().restarts(0).run();
+    /// assert_eq!(schedule.slots(), optimum.slots());
+    /// ```
     pub fn restarts(mut self, restarts: usize) -> Self {
-        self.restarts = Some(restarts);
+        self.config.restarts = Some(restarts);
         self
     }
 
-    /// Set the total number of runs with proportional resource division.
+    /// Choose which run's result the improver keeps across restarts.
     ///
-    /// This method provides fair comparison between different restart strategies
-    /// by dividing the total swap and timeout budgets across all runs. For example, if
-    /// `max_swaps(1000).restarts_proportional(5)` is used, each of the 5 runs
-    /// will get 200 swaps instead of 1000 each. Similarly for timeouts.
+    /// Defaults to [`RestartPolicy::KeepBest`].
     ///
-    /// If `max_swaps` was not explicitly set, the default budget is calculated
-    /// first, then divided across restarts.
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::cell::RefCell;
+    /// use time_scheduler::{RestartPolicy, Schedule};
+    ///
+    /// // Record the last layout the penalty function saw
+    /// let last_seen = RefCell::new(None);
+    /// let penalty = |s: &Schedule<i32>| {
+    ///     *last_seen.borrow_mut() = Some(s.slots().clone());
+    ///     0
+    /// };
+    ///
+    /// // Every layout is equally good, so KeepBest keeps the first run's
+    /// let initial = Schedule::new(2, 2, 0..6);
+    /// let mut schedule = initial.clone();
+    /// schedule.improve(penalty).restarts(4).with_rng_seed(9).run();
+    /// assert_eq!(schedule.slots(), initial.slots());
+    ///
+    /// // Continue ends on the final restart's layout
+    /// let mut schedule = initial.clone();
+    /// schedule
+    ///     .improve(penalty)
+    ///     .restarts(4)
+    ///     .restart_policy(RestartPolicy::Continue)
+    ///     .with_rng_seed(9)
+    ///     .run();
+    /// assert_eq!(Some(schedule.slots().clone()), *last_seen.borrow());
+    /// assert_ne!(schedule.slots(), initial.slots());
+    /// ```
+    pub fn restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.config.restart_policy = policy;
+        self
+    }
+
+    /// Choose how restarts rearrange the schedule before searching again.
+    ///
+    /// Defaults to [`RestartShuffle::Uniform`]. A uniform layout can pile
+    /// one topic into a single time slot by chance; a balanced one gives
+    /// restarts a better starting point when same-topic clashes are costly.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// # use time_scheduler::Schedule;
-    /// let mut schedule = Schedule::new(2, 2, std::iter::empty::<i32>());
-    /// // 1000 total swaps divided across 5 runs = 200 swaps per run
-    /// schedule.improve(|_| (0usize, 0.0f32)).max_swaps(1000).restarts_proportional(5).run();
+    /// use time_scheduler::{RestartShuffle, Schedule};
+    ///
+    /// // Topic is the activity modulo 3; clashes within a time slot cost 1
+    /// let clashes = |s: &Schedule<usize>| {
+    ///     let mut n = 0;
+    ///     for column in s.slots().columns() {
+    ///         let topics: Vec<usize> = column.iter().flatten().map(|a| a % 3).collect();
+    ///         for (i, a) in topics.iter().enumerate() {
+    ///             n += topics[i + 1..].iter().filter(|&b| b == a).count();
+    ///         }
+    ///     }
+    ///     n
+    /// };
+    ///
+    /// let mut schedule = Schedule::new(3, 3, 0..9usize);
+    /// schedule
+    ///     .improve(clashes)
+    ///     .restarts(3)
+    ///     .restart_shuffle(RestartShuffle::balanced(|a: &usize| a % 3))
+    ///     .with_rng_seed(2)
+    ///     .run();
+    /// assert_eq!(clashes(&schedule), 0);
     /// ```
-    pub fn restarts_proportional(mut self, restarts: usize) -> Self {
-        self.restarts = Some(restarts);
-        self.proportional_restarts = true;
+    pub fn restart_shuffle(mut self, shuffle: RestartShuffle<'a, A>) -> Self {
+        self.restarts.shuffle = shuffle;
         self
     }
 
-    /// Set a runtime timeout for the improvement process.
+    /// Call `callback` before each restart, with the restart number
+    /// (counting from 1) and the best penalty of the runs so far.
     ///
-    /// Uses an adaptive timeout checking algorithm that measures swaps-per-second
-    /// and adjusts checking frequency dynamically. Starts with 10 swaps to estimate
-    /// performance, then checks at intervals of half the estimated remaining swaps.
-    /// When within 10 swaps of completion, checks every swap for maximum accuracy.
+    /// The callback returns whether to go on: returning `false` skips the
+    /// remaining restarts, leaving the result the
+    /// [`restart_policy`](Improver::restart_policy) has kept so far. Use it to
+    /// log how much restarts help, or to stop once they stop paying off.
     ///
     /// # Examples
     ///
     /// ```rust
     /// # use time_scheduler::Schedule;
-    /// # use std::time::Duration;
-    /// let mut schedule = Schedule::new(2, 2, std::iter::empty::<i32>());
-    /// // Limit improvement to 5 seconds maximum
-    /// schedule.improve(|_| (0usize, 0.0f32)).timeout(Duration::from_secs(5)).run();
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     s.slots().indexed_iter()
+    ///         .filter_map(|((_, t), a)| a.map(|a| a * t))
+    ///         .sum::<usize>()
+    /// };
+    ///
+    /// // Stop restarting as soon as a run fails to beat an earlier one
+    /// let mut seen = Vec::new();
+    /// let mut schedule = Schedule::new(2, 3, 0..6);
+    /// schedule
+    ///     .improve(penalty)
+    ///     .restarts(10)
+    ///     .with_rng_seed(4)
+    ///     .on_restart(|restart, best| {
+    ///         seen.push((restart, best));
+    ///         seen.len() < 2 || seen[seen.len() - 2].1 > best
+    ///     })
+    ///     .run();
+    /// assert!(seen.len() < 10);
+    /// assert_eq!(seen[0].0, 1);
     /// ```
-    pub fn timeout(mut self, duration: Duration) -> Self {
-        self.timeout = Some(duration);
+    pub fn on_restart(mut self, callback: impl FnMut(usize, P) -> bool + 'a) -> Self {
+        self.restarts.on_restart = Some(Box::new(callback));
         self
     }
 
-    /// Run the improvement with the configured parameters.
+    /// Choose the order in which the greedy step scans candidate swaps.
     ///
-    /// This consumes the improver and applies the improvement to the schedule.
-    /// The schedule will be left in the best state found during improvement.
-    pub fn run(self) {
-        self.schedule.improve_run(
-            self.penalty_fn,
-            self.max_swaps,
-            self.noise,
-            self.restarts,
-            self.timeout,
-            self.proportional_restarts,
-        );
+    /// Defaults to [`ScanOrder::Lexicographic`]. A shuffled order breaks
+    /// ties between equally good swaps randomly, a cheap source of
+    /// diversification across restarts; it is reproducible with
+    /// [`with_rng_seed`](Improver::with_rng_seed).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{ScanOrder, Schedule};
+    ///
+    /// // Only the number of activities in the first time slot matters,
+    /// // so there are many equally good swaps to choose between
+    /// let penalty = |s: &Schedule<u32>| (0..3).filter(|&p| s.get_activity_at(p, 0).unwrap().is_none()).count();
+    /// let initial = Schedule::new(3, 3, 0..5);
+    ///
+    /// let solve = |order| {
+    ///     let mut schedule = initial.clone();
+    ///     schedule.improve(penalty).scan_order(order).with_rng_seed(8).run();
+    ///     schedule
+    /// };
+    /// let (first, second) = (solve(ScanOrder::Shuffled), solve(ScanOrder::Shuffled));
+    /// assert_eq!(first.slots(), second.slots());
+    /// assert_eq!(penalty(&first), 0);
+    ///
+    /// // Lexicographic is the default order
+    /// let mut default = initial.clone();
+    /// default.improve(penalty).with_rng_seed(8).run();
+    /// assert_eq!(solve(ScanOrder::Lexicographic).slots(), default.slots());
+    /// ```
+    pub fn scan_order(mut self, order: ScanOrder) -> Self {
+        self.config.scan_order = order;
+        self
     }
-}
 
-impl<A: Clone> Schedule<A> {
-    /// Create a new schedule with the given dimensions and activities.
+    /// Apply the first improving swap found instead of the best one.
     ///
-    /// Activities are initially placed in the schedule grid in row-major order.
-    /// If there are more activities than grid slots, the excess activities
-    /// remain unscheduled. If there are fewer activities than slots, some
-    /// slots will be empty.
+    /// By default each greedy step scans every candidate swap and applies
+    /// the best. With first-improvement the scan stops at the first
+    /// strictly improving swap, which usually needs far fewer penalty
+    /// evaluations to converge. Combine with
+    /// [`ScanOrder::Shuffled`] to avoid favoring low-index cells.
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `nplaces` - Number of places (columns) in the schedule grid
-    /// * `ntimes` - Number of time slots (rows) in the schedule grid  
-    /// * `activities` - Iterator of activities to be scheduled
+    /// ```rust
+    /// # use time_scheduler::{ScanOrder, Schedule};
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     s.slots().indexed_iter()
+    ///         .filter_map(|((_, t), a)| a.map(|a| a * t))
+    ///         .sum::<usize>()
+    /// };
+    /// let mut schedule = Schedule::new(3, 4, 0..12);
+    /// schedule
+    ///     .improve(penalty)
+    ///     .first_improvement()
+    ///     .scan_order(ScanOrder::Shuffled)
+    ///     .with_rng_seed(4)
+    ///     .run();
+    /// ```
+    pub fn first_improvement(mut self) -> Self {
+        self.config.first_improvement = true;
+        self
+    }
+
+    /// Search with a tabu list of the last `tenure` swaps.
+    ///
+    /// Instead of stopping at a local optimum, each greedy step applies the
+    /// best swap on offer even when it makes the penalty worse, so a run
+    /// only ends when its swap budget or timeout does. To keep the search
+    /// from stepping straight back, a pair of locations swapped within the
+    /// last `tenure` steps may not be swapped again, unless that would
+    /// beat the run's best penalty so far. The run still finishes on the
+    /// best schedule it found. With
+    /// [`improve_feasible`](Schedule::improve_feasible), a run that has
+    /// reached a feasible schedule never steps out of the feasible region,
+    /// just as noise moves don't. A `tenure` of 0, the default, is plain
+    /// hill climbing.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use time_scheduler::Schedule;
+    /// # use time_scheduler::Schedule;
+    /// // From [0, 1, 2], every swap looks worse, but two swaps away lies
+    /// // the best order of all
+    /// let penalty = |s: &Schedule<usize>| match s.row(0).unwrap()[..] {
+    ///     [Some(0), Some(1), Some(2)] => 5,
+    ///     [Some(1), Some(2), Some(0)] => 0,
+    ///     [Some(2), Some(0), Some(1)] => 8,
+    ///     _ => 10,
+    /// };
     ///
-    /// #[derive(Clone)]
-    /// struct Task(String);
+    /// let mut greedy = Schedule::new(1, 3, 0..3);
+    /// greedy.improve(penalty).run();
+    /// assert_eq!(penalty(&greedy), 5);
     ///
-    /// let tasks = vec![Task("A".to_string()), Task("B".to_string())];
-    /// let schedule = Schedule::new(2, 2, tasks.into_iter());
-    /// // Creates a 2x2 grid with tasks A and B scheduled, 2 empty slots
+    /// let mut tabu = Schedule::new(1, 3, 0..3);
+    /// tabu.improve(penalty).tabu(2).max_swaps(10).run();
+    /// assert_eq!(penalty(&tabu), 0);
     /// ```
-    pub fn new<I>(nplaces: usize, ntimes: usize, activities: I) -> Self
-    where
-        I: Iterator<Item = A>,
-    {
-        let mut acts = activities.fuse();
-
-        let mut slots = Array2::from_elem((nplaces, ntimes), None);
-        for x in &mut slots {
-            if let Some(a) = acts.next() {
-                *x = Some(a)
-            } else {
-                break;
-            }
-        }
-
-        let unscheduled = acts.map(Some).collect();
-
-        Self { slots, unscheduled }
+    pub fn tabu(mut self, tenure: usize) -> Self {
+        self.config.tabu_tenure = tenure;
+        self
     }
 
-    /// Get the activity scheduled at a specific place and time.
-    ///
-    /// Returns `Ok(Some(activity))` if there's an activity at the given coordinates,
-    /// `Ok(None)` if the slot is empty, or `Err(BoundsError)` if the coordinates
-    /// are out of bounds.
-    ///
-    /// # Arguments
+    /// Sample random swaps, kept or undone by an acceptance rule, instead
+    /// of scanning for the best swap.
     ///
-    /// * `place` - The place index (0-based)
-    /// * `time` - The time slot index (0-based)
+    /// Each step tries one random swap, as a noise move does, and keeps it
+    /// if `rule` accepts the change in `energy`, a number standing for the
+    /// penalty with lower being better. This gives simulated annealing,
+    /// great deluge and late acceptance hill climbing; see [`Acceptance`].
+    /// A swap into an infeasible state is never kept. A run ends only when
+    /// its swap budget or timeout does, and finishes on the best schedule
+    /// it found. Noise, tabu, the scan order and first improvement play no
+    /// part in a sampling search.
     ///
-    /// # Errors
+    /// # Panics
     ///
-    /// Returns [`BoundsError::Place`] if `place >= nplaces` or [`BoundsError::Time`]
-    /// if `time >= ntimes`.
+    /// Panics if the rule's parameters are out of the ranges given on
+    /// [`Acceptance`].
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use time_scheduler::{Schedule, BoundsError};
-    ///
-    /// #[derive(Clone)]
-    /// struct Task(i32);
+    /// use time_scheduler::{Acceptance, Schedule};
     ///
-    /// let tasks = vec![Task(1), Task(2)];
-    /// let schedule = Schedule::new(2, 2, tasks.into_iter());
-    ///
-    /// match schedule.get_activity_at(0, 0) {
-    ///     Ok(Some(task)) => println!("Task: {}", task.0),
-    ///     Ok(None) => println!("Empty slot"),
-    ///     Err(BoundsError::Place(p)) => println!("Invalid place: {}", p),
-    ///     Err(BoundsError::Time(t)) => println!("Invalid time: {}", t),
-    /// }
+    /// let lateness = |s: &Schedule<usize>| {
+    ///     s.iter_scheduled().map(|(_, t, &a)| a * t).sum::<usize>()
+    /// };
+    /// let mut schedule = Schedule::new(2, 3, 0..6);
+    /// let start = lateness(&schedule);
+    /// schedule
+    ///     .improve(lateness)
+    ///     .acceptance(Acceptance::Late { history: 5 }, |&p| p as f64)
+    ///     .max_swaps(500)
+    ///     .with_rng_seed(1)
+    ///     .run();
+    /// assert!(lateness(&schedule) < start);
     /// ```
-    pub fn get_activity_at(&self, place: usize, time: usize) -> Result<Option<&A>, BoundsError> {
-        let (nplaces, ntimes) = self.slots.dim();
-        if place >= nplaces {
-            return Err(BoundsError::Place(place));
-        }
-        if time >= ntimes {
-            return Err(BoundsError::Time(time));
-        }
-        Ok(self.slots[(place, time)].as_ref())
+    pub fn acceptance(mut self, rule: Acceptance, energy: fn(&P) -> f64) -> Self {
+        rule.check();
+        self.config.acceptance = Some(rule);
+        self.energy = Some(energy);
+        self
     }
 
-    /// Get an iterator over all unscheduled activities.
+    /// Only decide which unscheduled activities fill the empty slots,
+    /// leaving every scheduled activity where it is.
     ///
-    /// Returns an iterator that yields references to activities that haven't
-    /// been assigned to any time slot yet.
+    /// The slots holding an activity when the search starts are treated as
+    /// [locked](Constraints::locked) for its duration, so moves and restart
+    /// shuffles only exchange activities between the empty slots and the
+    /// unscheduled list. This suits late-stage planning, when the grid is
+    /// settled and only the waitlist is open, and makes each greedy step
+    /// far cheaper. The schedule's own constraints are back in place once
+    /// the search ends.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use time_scheduler::Schedule;
-    ///
-    /// #[derive(Clone)]
-    /// struct Task { priority: usize }
+    /// # use time_scheduler::Schedule;
+    /// // Larger activities are worth scheduling, wherever they go
+    /// let penalty = |s: &Schedule<usize>| s.get_unscheduled_activities().sum::<usize>();
     ///
-    /// let tasks = vec![Task { priority: 1 }, Task { priority: 2 }];
-    /// let schedule = Schedule::new(1, 1, tasks.into_iter()); // Only 1 slot
+    /// // One slot is empty, and 4 and 5 are waiting for it
+    /// let json = r#"{"slots": [[3, null], [1, 2]], "unscheduled": [4, 5]}"#;
+    /// let initial: Schedule<usize> = serde_json::from_str(json).unwrap();
     ///
-    /// let unscheduled_count = schedule.get_unscheduled_activities().count();
-    /// println!("Unscheduled: {}", unscheduled_count); // Should be 1
+    /// let mut schedule = initial.clone();
+    /// schedule.improve(penalty).fill_only().run();
+    /// assert_eq!(schedule.get_activity_at(0, 1).unwrap(), Some(&5));
+    /// assert_eq!(schedule.row(1).unwrap(), [Some(1), Some(2)]);
+    /// assert_eq!(penalty(&schedule), 4);
+    /// assert_eq!(schedule.constraints(), initial.constraints());
     ///
-    /// let total_priority: usize = schedule.get_unscheduled_activities()
-    ///     .map(|task| task.priority)
-    ///     .sum();
+    /// // Left free, the search would also trade the scheduled 1 for 4
+    /// let mut free = initial.clone();
+    /// free.improve(penalty).run();
+    /// assert_eq!(penalty(&free), 1);
     /// ```
-    pub fn get_unscheduled_activities(&self) -> impl Iterator<Item = &A> {
-        self.unscheduled.iter().filter_map(|opt| opt.as_ref())
+    pub fn fill_only(mut self) -> Self {
+        self.config.fill_only = true;
+        self
     }
 
-    /// Get the dimensions of the schedule grid.
+    /// Keep whatever occupies the slot at `place` and `time` there for the
+    /// whole search.
     ///
-    /// Returns a tuple `(nplaces, ntimes)` representing the number of places
-    /// and time slots in the schedule.
+    /// A pinned slot is treated as [locked](Constraints::locked) while the
+    /// search runs: no swap involves it, and restart shuffles leave it
+    /// alone, so a keynote fixed to a room and time stays put while
+    /// everything else is optimized around it. Pin as many slots as
+    /// needed. Unlike [`Schedule::with_constraints`], pinning does not
+    /// change the schedule's own constraints, which are as before once the
+    /// search ends.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use time_scheduler::Schedule;
+    /// # use time_scheduler::Schedule;
+    /// // Larger activities want earlier slots
+    /// let lateness = |s: &Schedule<usize>| s.iter_scheduled().map(|(_, t, &a)| a * t).sum::<usize>();
     ///
-    /// let schedule = Schedule::new(3, 5, std::iter::empty::<i32>());
-    /// let (places, times) = schedule.dimensions();
-    /// assert_eq!(places, 3);
-    /// assert_eq!(times, 5);
+    /// // The keynote, 1, is fixed to the last slot
+    /// let mut schedule = Schedule::new(1, 3, [1, 2, 3].into_iter());
+    /// schedule.set_activity_at(0, 2, 1).unwrap();
+    /// schedule.set_activity_at(0, 0, 2).unwrap();
+    /// schedule.set_activity_at(0, 1, 3).unwrap();
+    /// schedule.improve(lateness).pin(0, 2).with_noise().run();
+    /// assert_eq!(schedule.row(0).unwrap(), [Some(3), Some(2), Some(1)]);
+    /// assert!(schedule.constraints().is_empty());
     /// ```
-    pub fn dimensions(&self) -> (usize, usize) {
-        self.slots.dim()
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot is outside the schedule.
+    pub fn pin(mut self, place: usize, time: usize) -> Self {
+        if let Err(e) = self.schedule.check_cell(Cell::Slot(place, time)) {
+            panic!("cannot pin ({place}, {time}): {e}");
+        }
+        self.config.pinned.push((place, time));
+        self
     }
 
-    /// Count the number of empty slots in the schedule.
+    /// Also consider moving any scheduled activity out of the grid on its
+    /// own, leaving its slot empty.
     ///
-    /// Returns the total number of schedule slots that don't currently
-    /// have an activity assigned to them.
+    /// Every move swaps the contents of two locations. Moving an activity
+    /// into an empty slot is such a swap already, but taking one off the
+    /// grid needs an empty entry in the unscheduled list to swap with, and
+    /// normally there is none: a full unscheduled list can only trade
+    /// activities with the grid one for one. With or-moves the list is
+    /// padded with enough empty entries for every scheduled activity to
+    /// leave, so a schedule whose best layout has empty slots can reach
+    /// it. The padding adds one candidate swap per slot and scheduled
+    /// activity to each greedy step, and stays in the schedule afterwards,
+    /// as counted by [`unscheduled_len`](Schedule::unscheduled_len).
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use time_scheduler::Schedule;
+    /// # use time_scheduler::Schedule;
+    /// // Activity 1 costs more scheduled than left out
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     let placed: usize = s.slots().iter().flatten().map(|&a| 10 * a).sum();
+    ///     let missed = s.get_unscheduled_activities().count();
+    ///     placed + missed
+    /// };
     ///
-    /// let schedule = Schedule::new(2, 2, vec![1, 2].into_iter()); // 4 slots, 2 activities
-    /// assert_eq!(schedule.empty_slots_count(), 2);
+    /// let mut swaps = Schedule::new(1, 2, 0..2);
+    /// swaps.improve(penalty).run();
+    /// assert_eq!(penalty(&swaps), 10);
+    ///
+    /// let mut or_moves = Schedule::new(1, 2, 0..2);
+    /// or_moves.improve(penalty).or_moves().run();
+    /// assert_eq!(penalty(&or_moves), 1);
+    /// assert_eq!(or_moves.empty_slots_count(), 1);
     /// ```
-    pub fn empty_slots_count(&self) -> usize {
-        self.slots.iter().filter(|opt| opt.is_none()).count()
+    pub fn or_moves(mut self) -> Self {
+        self.config.or_moves = true;
+        self
     }
 
-    /// Get direct access to the internal slots array.
+    /// Only let the search put an activity in a slot `allowed` accepts.
     ///
-    /// This method provides access to the underlying 2D array for advanced
-    /// penalty function implementations that need to examine the schedule
-    /// structure directly. The array is indexed as `slots[(place, time)]`.
+    /// `allowed(activity, place, time)` says whether `activity` may be
+    /// scheduled at `place` and `time`, as for a speaker who is only
+    /// available at some times. This is a hard constraint on the
+    /// neighborhood, not a penalty: swaps that would move an activity
+    /// into a slot it may not use are never tried, so the penalty function
+    /// is not even asked about them. The unscheduled list accepts anything.
+    ///
+    /// Activities that the initial layout or a restart shuffle leaves in
+    /// such a slot are moved to the unscheduled list before each run
+    /// begins, so what the search returns always honors `allowed`.
+    /// [Locked](Constraints::locked) cells are left as they are. Since `A`
+    /// is generic, `allowed` picks out activities by whatever identifies
+    /// them, such as a name or a position in the original instance.
+    /// With the `parallel` feature `allowed` must be [`Send`] and
+    /// [`Sync`], so that the restarts of
+    /// [`run_parallel`](Improver::run_parallel) can share it.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use time_scheduler::Schedule;
-    /// use ndarray::Axis;
-    ///
-    /// #[derive(Clone)]
-    /// struct Task { id: usize }
-    ///
-    /// let tasks = vec![Task { id: 1 }, Task { id: 2 }];
-    /// let schedule = Schedule::new(2, 2, tasks.into_iter());
-    ///
-    /// // Count activities in each time slot
-    /// for (time, row) in schedule.slots().axis_iter(Axis(1)).enumerate() {
-    ///     let count = row.iter().filter(|slot| slot.is_some()).count();
-    ///     println!("Time {}: {} activities", time, count);
-    /// }
+    /// # use time_scheduler::Schedule;
+    /// // Lower activities want earlier slots, but 0 is unavailable at time 0
+    /// let lateness = |s: &Schedule<usize>| {
+    ///     let placed: usize = s.iter_scheduled().map(|(_, t, &a)| (3 - a) * t).sum();
+    ///     placed + 100 * s.get_unscheduled_activities().count()
+    /// };
+    /// let mut schedule = Schedule::new(1, 3, 0..3);
+    /// schedule
+    ///     .improve(lateness)
+    ///     .allowed_slots(|&a, _, t| !(a == 0 && t == 0))
+    ///     .run();
+    /// assert_eq!(schedule.get_activity_at(0, 0), Ok(Some(&1)));
+    /// assert_eq!(schedule.get_activity_at(0, 1), Ok(Some(&0)));
     /// ```
-    pub fn slots(&self) -> &Array2<Option<A>> {
-        &self.slots
+    pub fn allowed_slots(
+        mut self,
+        allowed: impl Fn(&A, usize, usize) -> bool + MaybeSend + MaybeSync + 'a,
+    ) -> Self {
+        self.moves.allowed = Some(Box::new(allowed));
+        self
     }
 
-    fn reshuffle(&mut self) {
-        use fastrand::usize as random_usize;
-
-        // Collect all activities from both slots and unscheduled
-        let mut all_activities = Vec::new();
-
-        // Collect from slots
-        for slot in self.slots.iter_mut() {
-            if let Some(activity) = slot.take() {
-                all_activities.push(activity);
-            }
-        }
-
-        // Collect from unscheduled
-        for unscheduled_slot in self.unscheduled.iter_mut() {
-            if let Some(activity) = unscheduled_slot.take() {
-                all_activities.push(activity);
-            }
-        }
-
-        // Shuffle the activities
-        for i in (1..all_activities.len()).rev() {
-            let j = random_usize(0..=i);
-            all_activities.swap(i, j);
-        }
-
-        // Redistribute activities: fill slots first, then unscheduled
-        let mut activity_iter = all_activities.into_iter();
-
-        // Fill slots
-        for slot in self.slots.iter_mut() {
-            if let Some(activity) = activity_iter.next() {
-                *slot = Some(activity);
-            }
-        }
-
-        // Fill unscheduled
-        for unscheduled_slot in self.unscheduled.iter_mut() {
-            if let Some(activity) = activity_iter.next() {
-                *unscheduled_slot = Some(activity);
-            }
-        }
+    /// Set the total number of runs with proportional resource division.
+    ///
+    /// This method provides fair comparison between different restart strategies
+    /// by dividing the total swap and timeout budgets across all runs. For example, if
+    /// `max_swaps(1000).restarts_proportional(5)` is used, each of the 5 runs
+    /// will get 200 swaps instead of 1000 each. Similarly for timeouts.
+    ///
+    /// If `max_swaps` was not explicitly set, the default budget is calculated
+    /// first, then divided across restarts. The division rounds down, but
+    /// never below one swap per run, so a budget smaller than the number of
+    /// runs gives each run a single swap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// let mut schedule = Schedule::new(2, 2, std::iter::empty::<i32>());
+    /// // 1000 total swaps divided across 5 runs = 200 swaps per run
+    /// schedule.improve(|_| (0usize, 0.0f32)).max_swaps(1000).restarts_proportional(5).run();
+    /// ```
+    pub fn restarts_proportional(mut self, restarts: usize) -> Self {
+        self.config.restarts = Some(restarts);
+        self.config.proportional_restarts = true;
+        self
     }
 
-    fn swap_locations(&mut self, loc1: Loc, loc2: Loc) {
-        use Loc::*;
+    /// Set a runtime timeout for the improvement process.
+    ///
+    /// Uses an adaptive timeout checking algorithm that measures swaps-per-second
+    /// and adjusts checking frequency dynamically. Starts with 10 swaps to estimate
+    /// performance, then checks at intervals of half the estimated remaining swaps.
+    /// When within 10 swaps of completion, checks every swap for maximum accuracy.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// # use std::time::Duration;
+    /// let mut schedule = Schedule::new(2, 2, std::iter::empty::<i32>());
+    /// // Limit improvement to 5 seconds maximum
+    /// schedule.improve(|_| (0usize, 0.0f32)).timeout(Duration::from_secs(5)).run();
+    /// ```
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.config.timeout = Some(duration);
+        self
+    }
 
-        let activity1 = match loc1 {
-            S(p, t) => self.slots[(p, t)].take(),
-            U(i) => self.unscheduled[i].take(),
-        };
-        let activity2 = match loc2 {
-            S(p, t) => self.slots[(p, t)].take(),
-            U(i) => self.unscheduled[i].take(),
-        };
+    /// Seed the random number generator used for noise moves and restarts.
+    ///
+    /// Two improvements of identical schedules with the same seed and
+    /// parameters produce identical results. Each run (the initial run and
+    /// every restart) gets its own generator whose seed is derived
+    /// deterministically from this master seed, so a run's randomness does
+    /// not depend on how much randomness earlier runs consumed.
+    ///
+    /// Without a seed, a fresh generator is seeded from the global `fastrand`
+    /// generator.
+    ///
+    /// Note that a timeout makes results depend on machine speed, so seeded
+    /// runs are only reproducible when bounded by swap count alone.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     s.slots().indexed_iter()
+    ///         .filter_map(|((p, t), a)| a.map(|a| (a * (p + 1) * (t + 2)) % 7))
+    ///         .sum::<usize>()
+    /// };
+    ///
+    /// let mut first = Schedule::new(3, 3, 0..12);
+    /// let mut second = first.clone();
+    /// first.improve(penalty).with_noise().restarts(3).with_rng_seed(42).run();
+    /// second.improve(penalty).with_noise().restarts(3).with_rng_seed(42).run();
+    ///
+    /// assert_eq!(first.slots(), second.slots());
+    /// assert_eq!(penalty(&first), penalty(&second));
+    /// ```
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
 
-        match loc1 {
-            S(p, t) => self.slots[(p, t)] = activity2,
-            U(i) => self.unscheduled[i] = activity2,
-        }
-        match loc2 {
-            S(p, t) => self.slots[(p, t)] = activity1,
-            U(i) => self.unscheduled[i] = activity1,
-        }
+    /// Make room for the search to schedule as few as `k` activities.
+    ///
+    /// Moves only ever exchange the contents of two locations, so an activity
+    /// can leave the grid only by trading places with an empty entry in the
+    /// unscheduled list. This setter adds enough empty unscheduled entries
+    /// that the grid can be thinned down to `k` activities.
+    ///
+    /// That is all it does: the library only makes the move possible, and
+    /// does not change how the penalty function sees unscheduled
+    /// activities. Any charge the penalty makes for an unscheduled activity
+    /// still applies to the surplus left out on purpose, so a penalty for
+    /// this mode should charge for the distance from `k`, as
+    /// [`Schedule::distance_from_target`] measures it, ahead of anything
+    /// that decides *which* activities to keep.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// // Keep exactly two of the four activities, preferring the largest
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     let missed: usize = s.get_unscheduled_activities().sum();
+    ///     (s.distance_from_target(2), missed)
+    /// };
+    ///
+    /// let mut schedule = Schedule::new(2, 2, 1..=4usize);
+    /// schedule.improve(penalty).target_scheduled(2).run();
+    ///
+    /// let mut dropped: Vec<usize> = schedule.get_unscheduled_activities().copied().collect();
+    /// dropped.sort();
+    /// assert_eq!(dropped, vec![1, 2]);
+    /// ```
+    pub fn target_scheduled(mut self, k: usize) -> Self {
+        self.config.target_scheduled = Some(k);
+        self
     }
 
-    /// Create an improver for this schedule with the given penalty function.
+    /// Call `callback` whenever the search finds a new best schedule.
     ///
-    /// Returns an [`Improver`] that can be configured with various parameters
-    /// before running the improvement.
+    /// Reports come from inside the search, so the callback should be quick;
+    /// it can clone the schedule to keep it. Across restarts only schedules
+    /// better than every earlier report are passed on. See also
+    /// [`soft_deadline`](Improver::soft_deadline).
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `penalty_fn` - Function that evaluates schedule quality. Lower scores are better.
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// let mut penalties = Vec::new();
+    /// let mut schedule = Schedule::new(1, 3, vec![1, 2, 3].into_iter());
+    /// schedule
+    ///     .improve(|s: &Schedule<usize>| s.get_activity_at(0, 0).unwrap().map_or(9, |&a| 3 - a))
+    ///     .on_progress(|progress| penalties.push(progress.penalty))
+    ///     .run();
+    /// assert_eq!(penalties, [2, 0]);
+    /// ```
+    pub fn on_progress(mut self, callback: impl FnMut(&Progress<'_, A, P>) + 'a) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Announce a usable solution after `deadline`, but keep improving.
+    ///
+    /// Once `deadline` has passed, the next [`on_progress`](Improver::on_progress)
+    /// report repeats the best schedule so far with
+    /// [`soft_deadline_passed`](Progress::soft_deadline_passed) set, and all
+    /// later reports have it set too. The search itself carries on until its
+    /// swap budget or hard [`timeout`](Improver::timeout) runs out, so an
+    /// interactive caller can show an early answer and update it afterwards.
+    /// If the search finishes before the deadline, there is no such report.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use time_scheduler::Schedule;
+    /// # use std::time::Duration;
+    /// # use time_scheduler::Schedule;
+    /// let mut early = None;
+    /// let mut schedule = Schedule::new(3, 3, 0..9usize);
+    /// schedule
+    ///     .improve(|s: &Schedule<usize>| s.get_activity_at(0, 0).unwrap().copied())
+    ///     .with_noise()
+    ///     .with_rng_seed(1)
+    ///     .timeout(Duration::from_millis(50))
+    ///     .soft_deadline(Duration::from_millis(10))
+    ///     .on_progress(|progress| {
+    ///         if progress.soft_deadline_passed && early.is_none() {
+    ///             early = Some(progress.elapsed);
+    ///         }
+    ///     })
+    ///     .run();
+    /// assert!(early.unwrap() >= Duration::from_millis(10));
+    /// ```
+    pub fn soft_deadline(mut self, deadline: Duration) -> Self {
+        self.config.soft_deadline = Some(deadline);
+        self
+    }
+
+    /// Time where each run spends its wall-clock time.
     ///
-    /// #[derive(Clone)]
-    /// struct Meeting { priority: usize }
+    /// Each step's evaluation is timed as a whole, along with each
+    /// restart's reshuffle, so profiling reads the clock a few times per
+    /// step rather than per penalty call; read the totals per run from
+    /// [`Steps::run_profiles`]. Without this call nothing is timed.
     ///
-    /// let mut schedule = Schedule::new(3, 5, vec![Meeting { priority: 10 }].into_iter());
+    /// # Examples
     ///
-    /// // Simple improvement
-    /// schedule.improve(|s: &Schedule<Meeting>| {
-    ///     let unscheduled_count = s.get_unscheduled_activities().count() + s.empty_slots_count();
-    ///     let priority_penalty = s.get_unscheduled_activities().map(|m| m.priority as f32).sum::<f32>();
-    ///     (unscheduled_count, priority_penalty)
-    /// }).run();
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// let mut schedule = Schedule::new(3, 3, 0..12usize);
+    /// let mut steps = schedule
+    ///     .improve(|s: &Schedule<usize>| s.get_unscheduled_activities().sum::<usize>())
+    ///     .restarts(2)
+    ///     .profile()
+    ///     .steps();
+    /// steps.by_ref().for_each(drop);
     ///
-    /// // With custom parameters
-    /// schedule.improve(|s: &Schedule<Meeting>| {
-    ///     let unscheduled_count = s.get_unscheduled_activities().count() + s.empty_slots_count();
-    ///     let priority_penalty = s.get_unscheduled_activities().map(|m| m.priority as f32).sum::<f32>();
-    ///     (unscheduled_count, priority_penalty)
-    /// }).max_swaps(2000).with_noise().restarts(3).run();
+    /// let profiles = steps.run_profiles();
+    /// assert_eq!(profiles.len(), 2);
+    /// assert!(profiles.iter().all(|p| p.evaluating + p.shuffling <= p.total));
     /// ```
-    pub fn improve<F, P>(&mut self, penalty_fn: F) -> Improver<A, F>
-    where
-        F: Fn(&Schedule<A>) -> P,
-        P: Copy + PartialOrd,
-    {
-        Improver::new(self, penalty_fn)
+    pub fn profile(mut self) -> Self {
+        self.config.profile = true;
+        self
     }
-}
 
-impl<A: Clone> Schedule<A> {
-    /// Run a complete improvement process with optional restarts.
+    /// Drive the improvement step by step instead of all at once.
     ///
-    /// This method uses a hill-climbing algorithm to improve the schedule by
-    /// trying swaps between different locations and keeping beneficial changes.
-    /// You provide a penalty function that evaluates the quality of the schedule.
+    /// The returned [`Steps`] iterator advances the search only when asked,
+    /// yielding the best penalty so far each time a run finds a new best
+    /// schedule. The caller decides when to stop and can interleave other
+    /// work; exhausting the iterator gives the same result as
+    /// [`run`](Improver::run).
     ///
-    /// The algorithm works by:
-    /// 1. Trying all possible swaps between locations (schedule slots and unscheduled list)
-    /// 2. Keeping the swap that gives the best improvement
-    /// 3. Repeating until no improvement is found or max swaps reached
-    /// 4. Optionally restarting with a random reshuffle to escape local optima
+    /// # Examples
     ///
-    /// # Arguments
+    /// ```rust
+    /// # use time_scheduler::Schedule;
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     s.slots().indexed_iter()
+    ///         .filter_map(|((_, t), a)| a.map(|a| a * t))
+    ///         .sum::<usize>()
+    /// };
+    /// let mut schedule = Schedule::new(2, 3, 0..6);
+    /// let mut steps = schedule.improve(penalty).with_rng_seed(1).steps();
+    ///
+    /// // Each step improves on the last
+    /// let mut last = usize::MAX;
+    /// while let Some(best) = steps.next() {
+    ///     assert!(best < last);
+    ///     assert_eq!(penalty(steps.schedule()), best);
+    ///     last = best;
+    /// }
+    /// ```
+    pub fn steps(self) -> Steps<'a, A, F, P> {
+        let search = Search::new(self.schedule, &self.config, self.swap_costs, self.energy);
+        Steps {
+            schedule: self.schedule,
+            penalty_fn: self.penalty_fn,
+            feasible: self.feasible,
+            observer: Observer::new(self.on_progress, self.config.soft_deadline),
+            restarts: self.restarts,
+            moves: self.moves,
+            search,
+        }
+    }
+
+    /// Run the improvement with the configured parameters.
+    ///
+    /// This consumes the improver and applies the improvement to the schedule.
+    /// The schedule will be left in the best state found during improvement.
     ///
-    /// * `penalty_fn` - Function that takes a schedule reference and returns a penalty score.
-    ///   Lower scores are better, with 0.0 representing a perfect solution.
-    /// * `nswaps` - Maximum number of swap iterations per restart. If `None`,
-    ///   defaults to `2 * (total_locations)^3`
-    /// * `noise` - If `true`, occasionally makes random moves instead of greedy ones
-    /// * `restarts` - Number of restarts with random reshuffling. If `None` or `0`,
-    ///   no restarts are performed
+    /// Each run is a hill-climbing search: it tries every swap between two
+    /// locations (schedule slots and the unscheduled list), keeps the one
+    /// that improves the penalty most, and repeats until no swap improves
+    /// or the swap budget runs out. Restarts begin again from a random
+    /// reshuffle to escape local optima.
     ///
     /// # Examples
     ///
@@ -759,175 +2312,3481 @@ impl<A: Clone> Schedule<A> {
     ///     let priority_penalty: f32 = schedule.get_unscheduled_activities()
     ///         .map(|m| m.priority as f32)
     ///         .sum();
-    ///     
+    ///
     ///     (unscheduled_count, priority_penalty)
     /// };
     ///
     /// // Improve with 5 restarts and noise
     /// schedule.improve(penalty_fn).with_noise().restarts(5).run();
     /// ```
-    fn improve_run<F, P>(
-        &mut self,
-        penalty_fn: F,
-        nswaps: Option<usize>,
-        noise: bool,
-        restarts: Option<usize>,
-        timeout: Option<Duration>,
-        proportional_restarts: bool,
-    ) where
-        F: Fn(&Schedule<A>) -> P,
-        P: Copy + PartialOrd,
-    {
-        let num_restarts = restarts.unwrap_or(0);
-
-        // Calculate per-run resources if proportional restarts are used
-        let per_run_timeout = if proportional_restarts && timeout.is_some() && num_restarts > 1 {
-            timeout.map(|t| Duration::from_nanos(t.as_nanos() as u64 / num_restarts as u64))
-        } else {
-            timeout
-        };
+    pub fn run(self) {
+        for _ in self.steps() {}
+    }
+}
 
-        let per_run_nswaps = if proportional_restarts && num_restarts > 1 {
-            nswaps.map(|n| n / num_restarts)
+impl<A, F, P> Improver<'_, A, F, P>
+where
+    A: Clone + MaybeSend + MaybeSync,
+    F: Fn(&Schedule<A>) -> P + MaybeSync,
+    P: Copy + PartialOrd + Debug + MaybeSend,
+{
+    /// Run the improvement like [`run`](Improver::run), but with the
+    /// restarts spread across threads.
+    ///
+    /// The runs of a search with [restarts](Improver::restarts) are
+    /// independent: each reshuffles the starting schedule with its own
+    /// generator, whose seed comes from the master seed by run number.
+    /// Here they run at once on up to
+    /// [`std::thread::available_parallelism`] scoped threads, each on a
+    /// clone of the schedule, and the best result is kept as the
+    /// [restart policy](Improver::restart_policy) says, weighing the runs
+    /// in order. A seeded parallel search therefore ends on exactly the
+    /// schedule a seeded [`run`](Improver::run) does, however the threads
+    /// are scheduled.
+    ///
+    /// Threads are used only with the `parallel` feature, on by default;
+    /// without it the runs take turns on the calling thread.
+    ///
+    /// Settings that follow the search as it goes fall back to the
+    /// sequential [`run`](Improver::run), with a warning:
+    /// [progress](Improver::on_progress) and
+    /// [restart](Improver::on_restart) callbacks,
+    /// [`RestartPolicy::Continue`], [profiling](Improver::profile), a
+    /// and a [balanced shuffle](Improver::restart_shuffle). A search
+    /// without restarts simply runs.
+    ///
+    /// # Panics
+    ///
+    /// A panic in the penalty function on any thread is passed on to the
+    /// caller.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // The activities in cells (p, t) with p == t should sum small
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     (0..3).filter_map(|i| s.get_activity_at(i, i).unwrap()).sum::<usize>()
+    /// };
+    /// let start = Schedule::new(3, 3, 0..12usize);
+    /// let mut parallel = start.clone();
+    /// parallel
+    ///     .improve(penalty)
+    ///     .max_swaps(100)
+    ///     .restarts(8)
+    ///     .with_rng_seed(3)
+    ///     .run_parallel();
+    /// assert_eq!(penalty(&parallel), 0 + 1 + 2);
+    ///
+    /// let mut sequential = start.clone();
+    /// sequential
+    ///     .improve(penalty)
+    ///     .max_swaps(100)
+    ///     .restarts(8)
+    ///     .with_rng_seed(3)
+    ///     .run();
+    /// assert_eq!(parallel, sequential);
+    /// ```
+    pub fn run_parallel(self) {
+        let nruns = self.config.restarts.unwrap_or(0).max(1);
+        let fallback = if self.config.restart_policy == RestartPolicy::Continue {
+            Some("each restart continues from the last")
+        } else if self.config.profile {
+            Some("profiling")
+        } else if self.on_progress.is_some() {
+            Some("a progress callback")
+        } else if self.restarts.on_restart.is_some() {
+            Some("a restart callback")
+        } else if matches!(self.restarts.shuffle, RestartShuffle::Balanced(_)) {
+            Some("a balanced restart shuffle")
         } else {
-            nswaps
+            None
         };
-
-        // Single run case: optimize once and return (nrestarts <= 1 means no restarts)
-        if num_restarts <= 1 {
-            self.improve_single(&penalty_fn, per_run_nswaps, noise, per_run_timeout);
+        if let Some(reason) = fallback.filter(|_| nruns > 1) {
+            warn!("running restarts in order instead of in parallel: {reason}");
+        }
+        if nruns == 1 || fallback.is_some() {
+            self.run();
             return;
         }
 
-        // Run first optimization and track as initial best
-        self.improve_single(&penalty_fn, per_run_nswaps, noise, per_run_timeout);
-        let mut best_penalty = penalty_fn(self);
-        let mut best_schedule = self.clone();
+        // Resolve the per-run budgets once, so any warning is given once
+        self.schedule.reserve_for(&self.config);
+        let dim = self.schedule.slots.dim();
+        let run_config = self.config.per_run(dim, self.schedule.unscheduled.len());
 
-        // Try additional restarts with reshuffling (num_restarts - 1 since we already did the initial run)
-        for _ in 0..(num_restarts - 1) {
-            self.reshuffle();
-            self.improve_single(&penalty_fn, per_run_nswaps, noise, per_run_timeout);
-            let current_penalty = penalty_fn(self);
+        let start: &Schedule<A> = self.schedule;
+        let penalty_fn = &self.penalty_fn;
+        let feasible = self.feasible;
+        let swap_costs = self.swap_costs;
+        let energy = self.energy;
+        let moves = &self.moves;
+        let one_run = |restart: usize| {
+            let mut schedule = start.clone();
+            let config = run_config.clone();
+            let mut search = Search::with_run_config(&mut schedule, config, swap_costs, energy)
+                .only_run(restart);
+            let mut observer = Observer::new(None, None);
+            let mut restarts = Restarts {
+                shuffle: RestartShuffle::Uniform,
+                on_restart: None,
+            };
+            while search
+                .next_improvement(
+                    &mut schedule,
+                    penalty_fn,
+                    feasible,
+                    &mut observer,
+                    &mut restarts,
+                    moves,
+                )
+                .is_some()
+            {}
+            let penalty = penalty_fn(&schedule);
+            debug!("restart {restart} finished with penalty {penalty:?}");
+            (penalty, schedule)
+        };
 
-            // Update best if this restart found a better solution
-            if current_penalty < best_penalty {
-                best_penalty = current_penalty;
-                best_schedule = self.clone();
-            }
+        // Weigh the runs in order, as the sequential search would
+        let accept_equal = self.config.restart_policy == RestartPolicy::AcceptEqual;
+        let best = all_runs(nruns, one_run)
+            .into_iter()
+            .reduce(|best, run| {
+                let better = if accept_equal {
+                    run.0 <= best.0
+                } else {
+                    run.0 < best.0
+                };
+                if better {
+                    run
+                } else {
+                    best
+                }
+            })
+            .map(|(_, schedule)| schedule);
+        if let Some(best) = best {
+            *self.schedule = best;
         }
-
-        // Restore the best solution found across all runs
-        *self = best_schedule;
     }
+}
 
-    fn improve_single<F, P>(
-        &mut self,
-        penalty_fn: &F,
-        nswaps: Option<usize>,
-        noise: bool,
-        timeout: Option<Duration>,
-    ) where
-        F: Fn(&Schedule<A>) -> P,
-        P: Copy + PartialOrd,
-    {
-        use fastrand::usize as random_usize;
-        use Loc::*;
-
-        // Setup: calculate dimensions and generate all possible locations
-        let (nplaces, ntimes) = self.slots.dim();
-        let nunscheduled = self.unscheduled.len();
-        let ntotal = nplaces * ntimes + nunscheduled;
-
-        // Early return for empty schedules (no optimization possible)
-        if ntotal == 0 {
-            return;
-        }
-        // Default nswaps: 5 * ntotal^2
-        // Rationale: Allows multiple cycles of greedy descent + noise escape.
-        // Greedy search terminates naturally, but noisy search needs enough iterations
-        // to escape local optima multiple times. Quadratic scaling accounts for the
-        // roughly O(ntotal^2) possible swaps per iteration and allows ~5 exploration cycles.
-        let nswaps = nswaps.unwrap_or(5 * ntotal * ntotal);
+/// The results of `one_run` for runs `0..nruns`, in order, computed on
+/// scoped threads.
+#[cfg(feature = "parallel")]
+fn all_runs<T: Send>(nruns: usize, one_run: impl Fn(usize) -> T + Sync) -> Vec<T> {
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
-        let all_locations: Vec<Loc> = (0..nplaces)
-            .flat_map(|p| (0..ntimes).map(move |t| S(p, t)))
-            .chain((0..nunscheduled).map(U))
+    let next = AtomicUsize::new(0);
+    let nthreads = std::thread::available_parallelism().map_or(1, |n| n.get().min(nruns));
+    let mut results: Vec<(usize, T)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..nthreads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut finished = Vec::new();
+                    loop {
+                        let restart = next.fetch_add(1, AtomicOrdering::Relaxed);
+                        if restart >= nruns {
+                            return finished;
+                        }
+                        finished.push((restart, one_run(restart)));
+                    }
+                })
+            })
             .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+    results.sort_by_key(|&(restart, _)| restart);
+    results.into_iter().map(|(_, result)| result).collect()
+}
 
-        // Initialize best solution tracking for this single run
-        let mut best_penalty = penalty_fn(self);
-        let mut best_schedule = self.clone();
-        let mut penalty = best_penalty;
-
-        // Initialize timeout checker if timeout is specified
-        let mut timeout_checker = timeout.map(TimeoutChecker::new);
-
-        // Main optimization loop: try up to nswaps improvements
-        for swap_iter in 0..nswaps {
-            // Check for timeout
-            if let Some(ref mut checker) = timeout_checker {
-                if checker.should_timeout(swap_iter) {
-                    break;
-                }
-            }
-            // Noise move: random swap that may disimprove (escape local optima)
-            if noise && random_usize(0..2) == 0 {
-                let i = random_usize(0..(nplaces * ntimes)); // Always pick from scheduled slots
-                let mut j = random_usize(0..(ntotal - 1)); // Pick from reduced range
-                if j >= i {
-                    j += 1; // Skip over i to avoid self-swap
-                }
-
-                self.swap_locations(all_locations[i], all_locations[j]);
-                let new_penalty = penalty_fn(self);
-
-                // Always accept noise moves (even if they disimprove)
-                penalty = new_penalty;
+/// The results of `one_run` for runs `0..nruns`, in order, computed on the
+/// calling thread.
+#[cfg(not(feature = "parallel"))]
+fn all_runs<T>(nruns: usize, one_run: impl Fn(usize) -> T) -> Vec<T> {
+    (0..nruns).map(one_run).collect()
+}
 
-                // Update best if this noise move happened to improve
-                if new_penalty < best_penalty {
-                    best_penalty = new_penalty;
-                    best_schedule = self.clone();
-                }
-                continue;
+impl<'a, A, F, P> Improver<'a, A, F, P>
+where
+    A: Clone + Hash,
+    F: Fn(&Schedule<A>) -> P,
+    P: Copy + PartialOrd + Debug,
+{
+    /// Remember the penalties of up to `capacity` recently seen schedules.
+    ///
+    /// Schedules are keyed by their [`fingerprint`](Schedule::fingerprint),
+    /// and the penalty function is skipped for any schedule already in the
+    /// cache, evicting the least recently used entry when full. This pays
+    /// off when the search keeps revisiting states, as restarts on small
+    /// instances do, at the cost of hashing every schedule evaluated.
+    /// A `capacity` of 0 disables the cache. The restarts of
+    /// [`run_parallel`](Improver::run_parallel) share one cache.
+    ///
+    /// The penalty function must depend only on the schedule, and two
+    /// different schedules with the same 64-bit fingerprint would share a
+    /// penalty, though that is vanishingly unlikely.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::cell::Cell;
+    /// # use time_scheduler::Schedule;
+    /// let calls = Cell::new(0);
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     calls.set(calls.get() + 1);
+    ///     s.get_activity_at(0, 0).unwrap().copied()
+    /// };
+    /// let mut schedule = Schedule::new(2, 2, 0..4usize);
+    /// schedule.improve(penalty).memoize(100).restarts(10).with_rng_seed(1).run();
+    /// // Only 4! = 24 distinct layouts exist, and most runs revisit them
+    /// assert!(calls.get() <= 24);
+    /// ```
+    pub fn memoize(self, capacity: usize) -> Improver<'a, A, impl Fn(&Schedule<A>) -> P, P> {
+        let penalty_fn = self.penalty_fn;
+        // Behind a lock, so the restarts of run_parallel can share it
+        let cache = Mutex::new(PenaltyCache::new(capacity));
+        let memoized = move |schedule: &Schedule<A>| {
+            if capacity == 0 {
+                return penalty_fn(schedule);
             }
-
-            // Greedy move: find the best improving swap among all possibilities
-            let mut cur_best = None;
-            let mut cur_penalty = penalty;
-            for i in 0..(nplaces * ntimes) {
-                // Only iterate over scheduled slots
-                for j in i + 1..ntotal {
-                    // j can be any slot after i
-                    // U-U swaps automatically avoided since i is always scheduled
-
-                    self.swap_locations(all_locations[i], all_locations[j]);
-                    let new_penalty = penalty_fn(self);
-                    if cur_penalty > new_penalty {
-                        cur_best = Some((i, j));
-                        cur_penalty = new_penalty;
-                    }
-                    self.swap_locations(all_locations[j], all_locations[i]);
-                }
+            let key = schedule.fingerprint();
+            let cached = cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(key);
+            if let Some(penalty) = cached {
+                return penalty;
             }
-            // Apply the best greedy move if one was found
-            if let Some((i, j)) = cur_best {
-                self.swap_locations(all_locations[i], all_locations[j]);
-                penalty = cur_penalty;
+            let penalty = penalty_fn(schedule);
+            cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(key, penalty);
+            penalty
+        };
+        Improver {
+            schedule: self.schedule,
+            penalty_fn: memoized,
+            feasible: self.feasible,
+            config: self.config,
+            on_progress: self.on_progress,
+            restarts: self.restarts,
+            swap_costs: self.swap_costs,
+            energy: self.energy,
+            moves: self.moves,
+        }
+    }
+}
 
-                // Update best if this greedy move improved our overall best
-                if penalty < best_penalty {
-                    best_penalty = penalty;
-                    best_schedule = self.clone();
+impl<'a, A, F, P> Improver<'a, A, F, P>
+where
+    A: Clone,
+    F: Fn(&Schedule<A>) -> P,
+    P: Copy + PartialOrd + Debug + Add<Output = P> + Sub<Output = P>,
+{
+    /// Reuse the penalty change of each candidate swap across greedy steps.
+    ///
+    /// Normally every greedy step evaluates the penalty of every candidate
+    /// swap afresh, although applying one swap leaves most candidates as
+    /// they were. With this cache a step only re-evaluates the swaps that
+    /// involve a location in one of the time slots just swapped into or
+    /// out of, and scores the rest as the current penalty plus the change
+    /// recorded earlier.
+    ///
+    /// The cache is exact for a penalty that sums terms each depending on
+    /// one time slot's contents, or on one unscheduled activity: a cost per
+    /// activity per slot, say, or topic clashes within a time slot. With an
+    /// integer penalty of that kind the search picks exactly the moves it
+    /// would without the cache, while floating-point penalties may differ
+    /// by rounding when two swaps are nearly equally good. A penalty that
+    /// relates time slots to each other, such as a speaker's talks in
+    /// consecutive slots, leaves cached scores stale. The swap a step
+    /// settles on is always checked against the full
+    /// penalty before it is made, and the step rescanned if its score was
+    /// wrong, so the search never moves on a stale score; but a better swap
+    /// hidden behind one can be missed. Unsigned penalty types work too, and
+    /// the cache holds one entry per pair of locations.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::cell::Cell;
+    /// # use time_scheduler::Schedule;
+    /// let calls = Cell::new(0);
+    /// // Every activity prefers an early slot, larger ones more so
+    /// let lateness = |s: &Schedule<i64>| {
+    ///     calls.set(calls.get() + 1);
+    ///     s.slots().indexed_iter().filter_map(|((_, t), a)| a.map(|a| a * t as i64)).sum::<i64>()
+    /// };
+    /// let initial = Schedule::new(2, 4, 0..8);
+    ///
+    /// let mut plain = initial.clone();
+    /// plain.improve(lateness).run();
+    /// let uncached = calls.replace(0);
+    ///
+    /// let mut cached = initial.clone();
+    /// cached.improve(lateness).cache_swap_costs().run();
+    /// assert_eq!(cached, plain);
+    /// assert!(calls.get() < uncached);
+    /// ```
+    pub fn cache_swap_costs(mut self) -> Self {
+        self.swap_costs = Some(SwapCostOps {
+            add: |a, b| a + b,
+            sub: |a, b| a - b,
+        });
+        self
+    }
+
+    /// Score candidate swaps with `delta` instead of the full penalty.
+    ///
+    /// `delta(schedule, a, b)` returns how much the penalty would change
+    /// if the contents of cells `a` and `b` were swapped, so that
+    /// `penalty(after) == penalty(schedule) + delta(schedule, a, b)`.
+    /// Most penalties only look at a swap's two time slots and two places,
+    /// so the change can be found far faster than by evaluating the whole
+    /// schedule for every one of the quadratically many candidate swaps.
+    /// Use [`Schedule::activity_in`] to see what each cell holds.
+    ///
+    /// The full penalty still scores noise moves, and is evaluated once
+    /// after each greedy move so rounding in the deltas never accumulates.
+    /// A `delta` that disagrees with the penalty misleads the search but
+    /// never corrupts the schedule. Combined with
+    /// [`cache_swap_costs`](Improver::cache_swap_costs), `delta` fills the
+    /// cache in place of the full penalty. With the `parallel` feature
+    /// `delta` must be [`Send`] and [`Sync`], as for
+    /// [`allowed_slots`](Improver::allowed_slots).
+    ///
+    /// The delta is a closure given here rather than a trait the penalty
+    /// implements, because penalties are plain closures: a trait would
+    /// need a named penalty type for every caller, and its default method
+    /// for penalties without a delta would do just what leaving this unset
+    /// does, evaluating the full penalty for each candidate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::cell::Cell as Counter;
+    /// # use time_scheduler::{Cell, Schedule};
+    /// let calls = Counter::new(0);
+    /// // Every activity prefers an early slot, larger ones more so
+    /// let lateness = |s: &Schedule<i64>| {
+    ///     calls.set(calls.get() + 1);
+    ///     s.slots().indexed_iter().filter_map(|((_, t), a)| a.map(|a| a * t as i64)).sum::<i64>()
+    /// };
+    /// // A slot's contribution to that, taken from what it holds
+    /// let cost = |cell: Cell, holding: Option<&i64>| match cell {
+    ///     Cell::Slot(_, t) => holding.map_or(0, |a| a * t as i64),
+    ///     Cell::Unscheduled(_) => 0,
+    /// };
+    /// let delta = |s: &Schedule<i64>, a: Cell, b: Cell| {
+    ///     let (in_a, in_b) = (s.activity_in(a).unwrap(), s.activity_in(b).unwrap());
+    ///     cost(a, in_b) + cost(b, in_a) - cost(a, in_a) - cost(b, in_b)
+    /// };
+    /// let initial = Schedule::new(2, 4, 0..8);
+    ///
+    /// let mut plain = initial.clone();
+    /// plain.improve(lateness).run();
+    /// let full = calls.replace(0);
+    ///
+    /// let mut local = initial.clone();
+    /// local.improve(lateness).penalty_delta(delta).run();
+    /// assert_eq!(local, plain);
+    /// assert!(calls.get() * 10 < full);
+    /// ```
+    pub fn penalty_delta(
+        mut self,
+        delta: impl Fn(&Schedule<A>, Cell, Cell) -> P + MaybeSend + MaybeSync + 'a,
+    ) -> Self {
+        self.moves.penalty_delta = Some(PenaltyDelta {
+            delta: Box::new(delta),
+            add: |a, b| a + b,
+        });
+        self
+    }
+}
+
+/// Least-recently-used map from schedule fingerprints to penalties.
+struct PenaltyCache<P> {
+    capacity: usize,
+    /// Penalty and last-use stamp of each cached fingerprint
+    entries: HashMap<u64, (P, u64)>,
+    /// Uses in stamp order; stale when the entry's stamp has moved on
+    uses: VecDeque<(u64, u64)>,
+    clock: u64,
+}
+
+impl<P: Copy> PenaltyCache<P> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            uses: VecDeque::new(),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, key: u64) -> Option<P> {
+        let (penalty, stamp) = self.entries.get_mut(&key)?;
+        self.clock += 1;
+        *stamp = self.clock;
+        let penalty = *penalty;
+        self.record_use(key);
+        Some(penalty)
+    }
+
+    fn insert(&mut self, key: u64, penalty: P) {
+        self.clock += 1;
+        self.entries.insert(key, (penalty, self.clock));
+        self.record_use(key);
+        while self.entries.len() > self.capacity {
+            let Some((oldest, stamp)) = self.uses.pop_front() else {
+                break;
+            };
+            if self.entries.get(&oldest).is_some_and(|&(_, s)| s == stamp) {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    fn record_use(&mut self, key: u64) {
+        self.uses.push_back((key, self.clock));
+        // Drop stale uses once they outnumber the live ones
+        if self.uses.len() > 2 * self.capacity + 16 {
+            let entries = &self.entries;
+            self.uses
+                .retain(|(key, stamp)| entries.get(key).is_some_and(|&(_, s)| s == *stamp));
+        }
+    }
+}
+
+impl<A: Clone> Schedule<A> {
+    /// Create a new schedule with the given dimensions and activities.
+    ///
+    /// Activities are initially placed in the schedule grid in row-major order.
+    /// If there are more activities than grid slots, the excess activities
+    /// remain unscheduled. If there are fewer activities than slots, some
+    /// slots will be empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `nplaces` - Number of places (columns) in the schedule grid
+    /// * `ntimes` - Number of time slots (rows) in the schedule grid  
+    /// * `activities` - Iterator of activities to be scheduled
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// #[derive(Clone)]
+    /// struct Task(String);
+    ///
+    /// let tasks = vec![Task("A".to_string()), Task("B".to_string())];
+    /// let schedule = Schedule::new(2, 2, tasks.into_iter());
+    /// // Creates a 2x2 grid with tasks A and B scheduled, 2 empty slots
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid is too large to allocate; use
+    /// [`try_new`](Schedule::try_new) to get an error instead.
+    pub fn new<I>(nplaces: usize, ntimes: usize, activities: I) -> Self
+    where
+        I: Iterator<Item = A>,
+    {
+        match Self::try_new(nplaces, ntimes, activities) {
+            Ok(schedule) => schedule,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Create a new schedule, checking that the grid can be allocated.
+    ///
+    /// This is [`new`](Schedule::new) for untrusted dimensions, such as
+    /// those read from an instance file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InstanceError::TooLarge`] if `nplaces * ntimes` overflows or
+    /// the grid would exceed the maximum allocation size.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{InstanceError, Schedule};
+    ///
+    /// let schedule = Schedule::try_new(2, 2, vec![1, 2].into_iter()).unwrap();
+    /// assert_eq!(schedule.dimensions(), (2, 2));
+    ///
+    /// let err = Schedule::try_new(usize::MAX, 2, vec![1, 2].into_iter());
+    /// assert_eq!(
+    ///     err.err(),
+    ///     Some(InstanceError::TooLarge { nplaces: usize::MAX, ntimes: 2 })
+    /// );
+    /// ```
+    pub fn try_new<I>(nplaces: usize, ntimes: usize, activities: I) -> Result<Self, InstanceError>
+    where
+        I: Iterator<Item = A>,
+    {
+        Self::build(nplaces, ntimes, 0, activities)
+    }
+
+    /// Create a new schedule, preallocating room for `nactivities` activities.
+    ///
+    /// This behaves like [`new`](Schedule::new), but reserves the unscheduled
+    /// overflow (`nactivities - nplaces * ntimes`) up front. Use it when the
+    /// activity iterator can't report its own length, such as after a
+    /// `filter`, and far more activities than slots are expected. The hint
+    /// only affects allocation: a wrong hint is never an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let even = (0..10_000).filter(|n| n % 2 == 0);
+    /// let schedule = Schedule::with_capacity_hint(10, 10, 5_000, even);
+    /// assert_eq!(schedule.get_unscheduled_activities().count(), 4_900);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid is too large to allocate, like
+    /// [`new`](Schedule::new).
+    pub fn with_capacity_hint<I>(
+        nplaces: usize,
+        ntimes: usize,
+        nactivities: usize,
+        activities: I,
+    ) -> Self
+    where
+        I: Iterator<Item = A>,
+    {
+        match Self::build(nplaces, ntimes, nactivities, activities) {
+            Ok(schedule) => schedule,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    fn build<I>(
+        nplaces: usize,
+        ntimes: usize,
+        nactivities: usize,
+        activities: I,
+    ) -> Result<Self, InstanceError>
+    where
+        I: Iterator<Item = A>,
+    {
+        let ncells = grid_size::<A>(nplaces, ntimes)?;
+        let mut acts = activities.fuse();
+
+        let mut slots = Array2::from_elem((nplaces, ntimes), None);
+        for x in &mut slots {
+            if let Some(a) = acts.next() {
+                *x = Some(a)
+            } else {
+                break;
+            }
+        }
+
+        let overflow = nactivities.saturating_sub(ncells).max(acts.size_hint().0);
+        let mut unscheduled = Vec::with_capacity(overflow);
+        unscheduled.extend(acts.map(Some));
+
+        let mut schedule = Self {
+            slots,
+            unscheduled,
+            buffer_times: 0,
+            constraints: Constraints::default(),
+            place_labels: None,
+            time_labels: None,
+            nactivities: 0,
+        };
+        schedule.recount();
+        Ok(schedule)
+    }
+
+    /// Mark the last `buffer_times` time slots as an overflow buffer.
+    ///
+    /// Buffer slots can still hold activities, but an empty buffer slot is not
+    /// counted by [`empty_slots_count`](Schedule::empty_slots_count), so
+    /// penalty functions built on it don't push activities into the buffer.
+    /// Values larger than the number of time slots are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // 2 places x 3 times with the last time slot kept as buffer
+    /// let schedule = Schedule::new(2, 3, vec![1, 2, 3, 4].into_iter()).with_buffer_times(1);
+    ///
+    /// // Activities fill (0,0), (0,1), (0,2), (1,0): only (1,1) is a counted hole
+    /// assert_eq!(schedule.empty_slots_count(), 1);
+    /// assert_eq!(schedule.buffer_times(), 1);
+    /// ```
+    pub fn with_buffer_times(mut self, buffer_times: usize) -> Self {
+        self.buffer_times = buffer_times.min(self.slots.dim().1);
+        self
+    }
+
+    /// Get the number of trailing time slots kept as an overflow buffer.
+    ///
+    /// Buffer time slots are the indices `ntimes - buffer_times()..ntimes`.
+    pub fn buffer_times(&self) -> usize {
+        self.buffer_times
+    }
+
+    /// Attach hard constraints, replacing any earlier ones.
+    ///
+    /// Activities in forbidden cells move to the unscheduled list, and
+    /// locked cells keep whatever they hold now. From then on the built-in
+    /// search and [`shuffle`](Schedule::shuffle) leave constrained cells
+    /// alone, and empty forbidden cells are not counted by
+    /// [`empty_slots_count`](Schedule::empty_slots_count). Direct edits such
+    /// as [`swap`](Schedule::swap) are not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Constraints, Schedule};
+    ///
+    /// // Keep 0 where it starts and nothing at all in (1, 1)
+    /// let constraints = Constraints { locked: vec![(0, 0)], forbidden: vec![(1, 1)] };
+    /// let mut schedule = Schedule::new(2, 2, 0..4).with_constraints(constraints);
+    /// assert_eq!(schedule.get_unscheduled_activities().collect::<Vec<_>>(), [&3]);
+    ///
+    /// // Larger activities want earlier slots, but 0 stays locked in
+    /// let earliness = |s: &Schedule<usize>| {
+    ///     s.iter_scheduled().map(|(_, t, &a)| a * (2 - t)).sum::<usize>()
+    /// };
+    /// schedule.improve(earliness).run();
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap(), Some(&0));
+    /// assert_eq!(schedule.get_activity_at(1, 1).unwrap(), None);
+    /// assert_eq!(schedule.empty_slots_count(), 0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a constrained cell lies outside the grid.
+    pub fn with_constraints(mut self, mut constraints: Constraints) -> Self {
+        let (nplaces, ntimes) = self.slots.dim();
+        if let Err(e) = constraints.check(nplaces, ntimes) {
+            panic!("{e}");
+        }
+        for cells in [&mut constraints.locked, &mut constraints.forbidden] {
+            cells.sort_unstable();
+            cells.dedup();
+        }
+        for &cell in &constraints.forbidden {
+            if let Some(activity) = self.slots[cell].take() {
+                self.unscheduled.push(Some(activity));
+            }
+        }
+        self.constraints = constraints;
+        self
+    }
+
+    /// Get the hard constraints attached to this schedule.
+    pub fn constraints(&self) -> &Constraints {
+        &self.constraints
+    }
+
+    /// Name the places, one label per place, for display and export.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(2, 1, vec![1, 2].into_iter())
+    ///     .with_place_labels(vec!["Hall".into(), "Room A".into()]);
+    /// assert_eq!(schedule.place_label(1), "Room A");
+    /// assert_eq!(schedule.time_label(0), "t0");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not exactly one label per place.
+    pub fn with_place_labels(mut self, labels: Vec<String>) -> Self {
+        let (nplaces, ntimes) = self.slots.dim();
+        if let Err(e) = check_labels(Some(&labels), None, nplaces, ntimes) {
+            panic!("{e}");
+        }
+        self.place_labels = Some(labels);
+        self
+    }
+
+    /// Name the time slots, one label per time slot, for display and
+    /// export.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not exactly one label per time slot.
+    pub fn with_time_labels(mut self, labels: Vec<String>) -> Self {
+        let (nplaces, ntimes) = self.slots.dim();
+        if let Err(e) = check_labels(None, Some(&labels), nplaces, ntimes) {
+            panic!("{e}");
+        }
+        self.time_labels = Some(labels);
+        self
+    }
+
+    /// Get the place labels, if the places are named.
+    pub fn place_labels(&self) -> Option<&[String]> {
+        self.place_labels.as_deref()
+    }
+
+    /// Get the time slot labels, if the time slots are named.
+    pub fn time_labels(&self) -> Option<&[String]> {
+        self.time_labels.as_deref()
+    }
+
+    /// The display name of a place: its label, or `p` and its index.
+    pub fn place_label(&self, place: usize) -> String {
+        match &self.place_labels {
+            Some(labels) => labels[place].clone(),
+            None => format!("p{place}"),
+        }
+    }
+
+    /// The display name of a time slot: its label, or `t` and its index.
+    pub fn time_label(&self, time: usize) -> String {
+        match &self.time_labels {
+            Some(labels) => labels[time].clone(),
+            None => format!("t{time}"),
+        }
+    }
+
+    /// The cells the search may swap: unconstrained slots in place-major
+    /// order, then the unscheduled positions.
+    fn movable_cells(&self) -> Vec<Cell> {
+        let fixed = self.fixed_cells();
+        self.all_cells()
+            .filter(|&cell| !matches!(cell, Cell::Slot(p, t) if fixed[(p, t)]))
+            .collect()
+    }
+
+    /// Mark the cells the search must leave alone.
+    fn fixed_cells(&self) -> Array2<bool> {
+        let mut fixed = Array2::from_elem(self.slots.dim(), false);
+        for &cell in self
+            .constraints
+            .locked
+            .iter()
+            .chain(&self.constraints.forbidden)
+        {
+            fixed[cell] = true;
+        }
+        fixed
+    }
+
+    /// Get the activity scheduled at a specific place and time.
+    ///
+    /// Returns `Ok(Some(activity))` if there's an activity at the given coordinates,
+    /// `Ok(None)` if the slot is empty, or `Err(BoundsError)` if the coordinates
+    /// are out of bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `place` - The place index (0-based)
+    /// * `time` - The time slot index (0-based)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] if `place >= nplaces` or [`BoundsError::Time`]
+    /// if `time >= ntimes`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Schedule, BoundsError};
+    ///
+    /// #[derive(Clone)]
+    /// struct Task(i32);
+    ///
+    /// let tasks = vec![Task(1), Task(2)];
+    /// let schedule = Schedule::new(2, 2, tasks.into_iter());
+    ///
+    /// match schedule.get_activity_at(0, 0) {
+    ///     Ok(Some(task)) => println!("Task: {}", task.0),
+    ///     Ok(None) => println!("Empty slot"),
+    ///     Err(BoundsError::Place(p)) => println!("Invalid place: {}", p),
+    ///     Err(BoundsError::Time(t)) => println!("Invalid time: {}", t),
+    ///     Err(e) => println!("{e}"),
+    /// }
+    /// ```
+    pub fn get_activity_at(&self, place: usize, time: usize) -> Result<Option<&A>, BoundsError> {
+        let (nplaces, ntimes) = self.slots.dim();
+        if place >= nplaces {
+            return Err(BoundsError::Place(place));
+        }
+        if time >= ntimes {
+            return Err(BoundsError::Time(time));
+        }
+        Ok(self.slots[(place, time)].as_ref())
+    }
+
+    /// Put an activity in a slot, returning the one it held before.
+    ///
+    /// This is for building a schedule by hand or repairing one with
+    /// domain knowledge. The previous activity is handed back rather than
+    /// moved to the unscheduled list, so keeping track of it is up to the
+    /// caller, as with [`with_slots_mut`](Schedule::with_slots_mut).
+    /// [Locked](Constraints::locked) slots can be set too.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if `(place, time)` is out of bounds, as for
+    /// [`get_activity_at`](Schedule::get_activity_at). The schedule is
+    /// left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{BoundsError, Schedule};
+    ///
+    /// let mut schedule = Schedule::new(1, 2, vec![1].into_iter());
+    /// assert_eq!(schedule.set_activity_at(0, 1, 2), Ok(None));
+    ///
+    /// // Overwriting an occupied slot returns its old activity
+    /// assert_eq!(schedule.set_activity_at(0, 0, 3), Ok(Some(1)));
+    /// assert_eq!(schedule.row(0).unwrap(), [Some(3), Some(2)]);
+    ///
+    /// assert_eq!(schedule.set_activity_at(1, 0, 4), Err(BoundsError::Place(1)));
+    /// assert_eq!(schedule.set_activity_at(0, 2, 4), Err(BoundsError::Time(2)));
+    /// assert_eq!(schedule.row(0).unwrap(), [Some(3), Some(2)]);
+    /// ```
+    pub fn set_activity_at(
+        &mut self,
+        place: usize,
+        time: usize,
+        activity: A,
+    ) -> Result<Option<A>, BoundsError> {
+        self.check_cell(Cell::Slot(place, time))?;
+        let replaced = self.slots[(place, time)].replace(activity);
+        if replaced.is_none() {
+            self.nactivities += 1;
+        }
+        Ok(replaced)
+    }
+
+    /// Remove the activity from a slot, leaving it empty.
+    ///
+    /// As with [`set_activity_at`](Schedule::set_activity_at), the removed
+    /// activity goes to the caller, not to the unscheduled list.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if `(place, time)` is out of bounds, as for
+    /// [`get_activity_at`](Schedule::get_activity_at).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{BoundsError, Schedule};
+    ///
+    /// let mut schedule = Schedule::new(2, 1, vec![1].into_iter());
+    /// assert_eq!(schedule.take_activity_at(0, 0), Ok(Some(1)));
+    /// assert_eq!(schedule.take_activity_at(0, 0), Ok(None));
+    /// assert_eq!(schedule.empty_slots_count(), 2);
+    ///
+    /// assert_eq!(schedule.take_activity_at(2, 0), Err(BoundsError::Place(2)));
+    /// assert_eq!(schedule.take_activity_at(0, 1), Err(BoundsError::Time(1)));
+    /// ```
+    pub fn take_activity_at(
+        &mut self,
+        place: usize,
+        time: usize,
+    ) -> Result<Option<A>, BoundsError> {
+        self.check_cell(Cell::Slot(place, time))?;
+        let taken = self.slots[(place, time)].take();
+        if taken.is_some() {
+            self.nactivities -= 1;
+        }
+        Ok(taken)
+    }
+
+    /// Get the activity in a cell of the grid or the unscheduled list.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if `cell` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, Schedule};
+    ///
+    /// let schedule = Schedule::new(1, 2, vec![1, 2, 3].into_iter());
+    /// assert_eq!(schedule.activity_in(Cell::Slot(0, 1)).unwrap(), Some(&2));
+    /// assert_eq!(schedule.activity_in(Cell::Unscheduled(0)).unwrap(), Some(&3));
+    /// assert!(schedule.activity_in(Cell::Unscheduled(1)).is_err());
+    /// ```
+    pub fn activity_in(&self, cell: Cell) -> Result<Option<&A>, BoundsError> {
+        self.check_cell(cell)?;
+        Ok(self.occupant(cell))
+    }
+
+    /// Get the activities just before and after a slot in the same place.
+    ///
+    /// Returns `(previous, next)`: the activities at `(place, time - 1)` and
+    /// `(place, time + 1)`. Either is `None` if that slot is empty or lies
+    /// beyond the edge of the schedule.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if `(place, time)` itself is out of bounds,
+    /// as for [`get_activity_at`](Schedule::get_activity_at).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // Place 0 holds 1, 2, 3 in times 0, 1, 2
+    /// let schedule = Schedule::new(1, 3, vec![1, 2, 3].into_iter());
+    /// assert_eq!(schedule.neighbors_in_time(0, 0).unwrap(), (None, Some(&2)));
+    /// assert_eq!(schedule.neighbors_in_time(0, 1).unwrap(), (Some(&1), Some(&3)));
+    /// assert_eq!(schedule.neighbors_in_time(0, 2).unwrap(), (Some(&2), None));
+    /// assert!(schedule.neighbors_in_time(0, 3).is_err());
+    /// ```
+    pub fn neighbors_in_time(
+        &self,
+        place: usize,
+        time: usize,
+    ) -> Result<(Option<&A>, Option<&A>), BoundsError> {
+        self.get_activity_at(place, time)?;
+        let previous = time.checked_sub(1).and_then(|t| self.slot(place, t));
+        Ok((previous, self.slot(place, time + 1)))
+    }
+
+    /// Get the activities in the neighboring places at the same time.
+    ///
+    /// Returns `(previous, next)`: the activities at `(place - 1, time)` and
+    /// `(place + 1, time)`. Either is `None` if that slot is empty or lies
+    /// beyond the edge of the schedule.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if `(place, time)` itself is out of bounds,
+    /// as for [`get_activity_at`](Schedule::get_activity_at).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // Time 0 holds 1, 2, 3 in places 0, 1, 2
+    /// let schedule = Schedule::new(3, 1, vec![1, 2, 3].into_iter());
+    /// assert_eq!(schedule.neighbors_in_place(0, 0).unwrap(), (None, Some(&2)));
+    /// assert_eq!(schedule.neighbors_in_place(2, 0).unwrap(), (Some(&2), None));
+    /// ```
+    pub fn neighbors_in_place(
+        &self,
+        place: usize,
+        time: usize,
+    ) -> Result<(Option<&A>, Option<&A>), BoundsError> {
+        self.get_activity_at(place, time)?;
+        let previous = place.checked_sub(1).and_then(|p| self.slot(p, time));
+        Ok((previous, self.slot(place + 1, time)))
+    }
+
+    /// Copy out the activities at one place, in time order.
+    ///
+    /// The copy holds no borrow on the schedule, so downstream code need
+    /// not deal in `ndarray` views.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] if `place >= nplaces`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{BoundsError, Schedule};
+    ///
+    /// // Place 0 holds 1 and 2, place 1 holds 3
+    /// let schedule = Schedule::new(2, 2, vec![1, 2, 3].into_iter());
+    /// assert_eq!(schedule.row(0).unwrap(), [Some(1), Some(2)]);
+    /// assert_eq!(schedule.row(1).unwrap(), [Some(3), None]);
+    /// assert!(matches!(schedule.row(2), Err(BoundsError::Place(2))));
+    /// ```
+    pub fn row(&self, place: usize) -> Result<Vec<Option<A>>, BoundsError> {
+        if place >= self.slots.nrows() {
+            return Err(BoundsError::Place(place));
+        }
+        Ok(self.slots.row(place).to_vec())
+    }
+
+    /// Copy out the activities at one time, in place order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Time`] if `time >= ntimes`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{BoundsError, Schedule};
+    ///
+    /// let schedule = Schedule::new(2, 2, vec![1, 2, 3].into_iter());
+    /// assert_eq!(schedule.column(0).unwrap(), [Some(1), Some(3)]);
+    /// assert_eq!(schedule.column(1).unwrap(), [Some(2), None]);
+    /// assert!(matches!(schedule.column(2), Err(BoundsError::Time(2))));
+    /// ```
+    pub fn column(&self, time: usize) -> Result<Vec<Option<A>>, BoundsError> {
+        if time >= self.slots.ncols() {
+            return Err(BoundsError::Time(time));
+        }
+        Ok(self.slots.column(time).to_vec())
+    }
+
+    /// The activity at `(place, time)`, or `None` if empty or out of bounds.
+    fn slot(&self, place: usize, time: usize) -> Option<&A> {
+        self.slots.get((place, time))?.as_ref()
+    }
+
+    /// Get an iterator over all unscheduled activities.
+    ///
+    /// Returns an iterator that yields references to activities that haven't
+    /// been assigned to any time slot yet.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// #[derive(Clone)]
+    /// struct Task { priority: usize }
+    ///
+    /// let tasks = vec![Task { priority: 1 }, Task { priority: 2 }];
+    /// let schedule = Schedule::new(1, 1, tasks.into_iter()); // Only 1 slot
+    ///
+    /// let unscheduled_count = schedule.get_unscheduled_activities().count();
+    /// println!("Unscheduled: {}", unscheduled_count); // Should be 1
+    ///
+    /// let total_priority: usize = schedule.get_unscheduled_activities()
+    ///     .map(|task| task.priority)
+    ///     .sum();
+    /// ```
+    pub fn get_unscheduled_activities(&self) -> impl Iterator<Item = &A> {
+        self.unscheduled.iter().filter_map(|opt| opt.as_ref())
+    }
+
+    /// Keep only the unscheduled activities matching a predicate.
+    ///
+    /// Unscheduled activities for which `f` returns `false` are dropped from
+    /// the schedule entirely. Empty entries left behind in the unscheduled
+    /// list by earlier improvement runs are removed as well. Scheduled
+    /// activities are not affected.
+    ///
+    /// This is useful for trimming a problem before re-optimizing it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// #[derive(Clone)]
+    /// struct Task { priority: usize }
+    ///
+    /// let tasks = (1..=6).map(|priority| Task { priority });
+    /// let mut schedule = Schedule::new(1, 2, tasks); // Priorities 3-6 unscheduled
+    ///
+    /// schedule.retain_unscheduled(|task| task.priority >= 5);
+    ///
+    /// let kept: Vec<usize> = schedule.get_unscheduled_activities().map(|t| t.priority).collect();
+    /// assert_eq!(kept, vec![5, 6]);
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap().unwrap().priority, 1);
+    /// assert_eq!(schedule.get_activity_at(0, 1).unwrap().unwrap().priority, 2);
+    /// ```
+    pub fn retain_unscheduled(&mut self, mut f: impl FnMut(&A) -> bool) {
+        let mut dropped = 0;
+        self.unscheduled.retain(|opt| match opt {
+            Some(activity) if f(activity) => true,
+            Some(_) => {
+                dropped += 1;
+                false
+            }
+            None => false,
+        });
+        self.nactivities -= dropped;
+    }
+
+    /// Get the dimensions of the schedule grid.
+    ///
+    /// Returns a tuple `(nplaces, ntimes)` representing the number of places
+    /// and time slots in the schedule.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(3, 5, std::iter::empty::<i32>());
+    /// let (places, times) = schedule.dimensions();
+    /// assert_eq!(places, 3);
+    /// assert_eq!(times, 5);
+    /// ```
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.slots.dim()
+    }
+
+    /// Count the number of empty slots in the schedule.
+    ///
+    /// Returns the total number of schedule slots that don't currently
+    /// have an activity assigned to them. Empty slots in the buffer time
+    /// slots (see [`with_buffer_times`](Schedule::with_buffer_times)) are
+    /// not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(2, 2, vec![1, 2].into_iter()); // 4 slots, 2 activities
+    /// assert_eq!(schedule.empty_slots_count(), 2);
+    /// ```
+    pub fn empty_slots_count(&self) -> usize {
+        let ntimes = self.slots.dim().1;
+        let nbuffered = ntimes - self.buffer_times;
+        let holes = self
+            .slots
+            .indexed_iter()
+            .filter(|&((_, t), opt)| t < nbuffered && opt.is_none())
+            .count();
+        let forbidden = self
+            .constraints
+            .forbidden
+            .iter()
+            .filter(|&&(p, t)| t < nbuffered && self.slots[(p, t)].is_none())
+            .count();
+        holes - forbidden
+    }
+
+    /// How many activities the grid is away from holding exactly `k`: the
+    /// number to unschedule or schedule to get there.
+    ///
+    /// This is the term a penalty should charge first under
+    /// [`Improver::target_scheduled`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(2, 2, 0..3); // 3 of 4 slots filled
+    /// assert_eq!(schedule.distance_from_target(3), 0);
+    /// assert_eq!(schedule.distance_from_target(1), 2);
+    /// assert_eq!(schedule.distance_from_target(4), 1);
+    /// ```
+    pub fn distance_from_target(&self, k: usize) -> usize {
+        self.slots.iter().flatten().count().abs_diff(k)
+    }
+
+    /// Get direct access to the internal slots array.
+    ///
+    /// This method provides access to the underlying 2D array for advanced
+    /// penalty function implementations that need to examine the schedule
+    /// structure directly. The array is indexed as `slots[(place, time)]`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    /// use ndarray::Axis;
+    ///
+    /// #[derive(Clone)]
+    /// struct Task { id: usize }
+    ///
+    /// let tasks = vec![Task { id: 1 }, Task { id: 2 }];
+    /// let schedule = Schedule::new(2, 2, tasks.into_iter());
+    ///
+    /// // Count activities in each time slot
+    /// for (time, row) in schedule.slots().axis_iter(Axis(1)).enumerate() {
+    ///     let count = row.iter().filter(|slot| slot.is_some()).count();
+    ///     println!("Time {}: {} activities", time, count);
+    /// }
+    /// ```
+    pub fn slots(&self) -> &Array2<Option<A>> {
+        &self.slots
+    }
+
+    /// Iterate over every slot as `(place, time, activity)`.
+    ///
+    /// Slots come in place-major order: all of place 0's times in order,
+    /// then place 1's, and so on, with `None` for the empty ones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(2, 2, vec!['a', 'b', 'c'].into_iter());
+    /// let slots: Vec<_> = schedule.iter_slots().collect();
+    /// assert_eq!(
+    ///     slots,
+    ///     [(0, 0, Some(&'a')), (0, 1, Some(&'b')), (1, 0, Some(&'c')), (1, 1, None)]
+    /// );
+    /// ```
+    pub fn iter_slots(&self) -> impl Iterator<Item = (usize, usize, Option<&A>)> {
+        self.slots
+            .indexed_iter()
+            .map(|((place, time), slot)| (place, time, slot.as_ref()))
+    }
+
+    /// Iterate over the scheduled activities as `(place, time, activity)`,
+    /// skipping empty slots.
+    ///
+    /// The order is that of [`iter_slots`](Schedule::iter_slots).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let mut schedule = Schedule::new(2, 2, vec!['a', 'b', 'c'].into_iter());
+    /// schedule.take_activity_at(0, 1).unwrap();
+    /// let scheduled: Vec<_> = schedule.iter_scheduled().collect();
+    /// assert_eq!(scheduled, [(0, 0, &'a'), (1, 0, &'c')]);
+    /// ```
+    pub fn iter_scheduled(&self) -> impl Iterator<Item = (usize, usize, &A)> {
+        self.iter_slots()
+            .filter_map(|(place, time, slot)| slot.map(|activity| (place, time, activity)))
+    }
+
+    /// Mutate the slots array directly.
+    ///
+    /// The closure receives a mutable view of the grid, indexed as
+    /// `slots[(place, time)]`, for building custom moves. The view cannot
+    /// change the grid's dimensions. Keeping each activity in exactly one
+    /// location is the caller's responsibility: activities dropped or
+    /// duplicated through the view stay dropped or duplicated. Everything the
+    /// schedule reports about itself, such as [`empty_slots_count`], is
+    /// derived from the grid and so reflects the changes once the closure
+    /// returns; [`total_activities`] is counted afresh then, in one pass
+    /// over the schedule.
+    ///
+    /// [`empty_slots_count`]: Schedule::empty_slots_count
+    /// [`total_activities`]: Schedule::total_activities
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let mut schedule = Schedule::new(2, 2, vec![1, 2, 3].into_iter());
+    /// assert_eq!(schedule.empty_slots_count(), 1);
+    ///
+    /// // Swap the first place's two time slots and clear the last cell
+    /// let cleared = schedule.with_slots_mut(|mut slots| {
+    ///     slots.swap((0, 0), (0, 1));
+    ///     slots[(1, 0)].take()
+    /// });
+    ///
+    /// assert_eq!(cleared, Some(3));
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap(), Some(&2));
+    /// assert_eq!(schedule.empty_slots_count(), 2);
+    /// ```
+    pub fn with_slots_mut<R>(&mut self, f: impl FnOnce(ArrayViewMut2<'_, Option<A>>) -> R) -> R {
+        let result = f(self.slots.view_mut());
+        self.recount();
+        result
+    }
+
+    /// Count the activities the schedule holds, scheduled or not.
+    ///
+    /// Moves such as [`swap`](Schedule::swap),
+    /// [`move_activity`](Schedule::move_activity) and
+    /// [`shuffle`](Schedule::shuffle) never change this count, so it is the
+    /// fixed denominator for occupancy and normalization, and an invariant
+    /// check. Only editing the activities themselves, through
+    /// [`set_activity_at`](Schedule::set_activity_at),
+    /// [`take_activity_at`](Schedule::take_activity_at),
+    /// [`retain_unscheduled`](Schedule::retain_unscheduled) or
+    /// [`with_slots_mut`](Schedule::with_slots_mut), can change it.
+    ///
+    /// The count is kept as the schedule is edited, so this takes constant
+    /// time and is cheap enough to call from a penalty function.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, Schedule};
+    ///
+    /// let mut schedule = Schedule::new(2, 2, 0..6);
+    /// assert_eq!(schedule.total_activities(), 6);
+    ///
+    /// schedule.swap(Cell::Slot(0, 0), Cell::Unscheduled(1)).unwrap();
+    /// schedule.shuffle(&mut fastrand::Rng::with_seed(3));
+    /// schedule.improve(|s| s.get_unscheduled_activities().sum::<i32>()).run();
+    /// assert_eq!(schedule.total_activities(), 6);
+    ///
+    /// schedule.retain_unscheduled(|_| false);
+    /// assert_eq!(schedule.total_activities(), 4);
+    /// schedule.with_slots_mut(|mut slots| slots[(1, 1)] = None);
+    /// assert_eq!(schedule.total_activities(), 3);
+    /// ```
+    pub fn total_activities(&self) -> usize {
+        self.nactivities
+    }
+
+    /// Count the activities afresh, after an edit the count can't follow.
+    fn recount(&mut self) {
+        let scheduled = self.slots.iter().flatten().count();
+        self.nactivities = scheduled + self.unscheduled.iter().flatten().count();
+    }
+
+    /// Get the number of positions in the unscheduled list.
+    ///
+    /// This includes positions left empty when an activity was swapped into
+    /// an empty grid slot, so it can exceed the number of activities yielded by
+    /// [`get_unscheduled_activities`](Schedule::get_unscheduled_activities).
+    /// Valid [`Cell::Unscheduled`] indices are `0..unscheduled_len()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(1, 1, vec![1, 2, 3].into_iter());
+    /// assert_eq!(schedule.unscheduled_len(), 2);
+    /// ```
+    pub fn unscheduled_len(&self) -> usize {
+        self.unscheduled.len()
+    }
+
+    /// Iterate over every cell of the schedule.
+    ///
+    /// Yields all grid slots in place-major order, then every position of the
+    /// unscheduled list. This is the location universe the built-in search
+    /// swaps over, less any cells fixed by [`Constraints`], so custom
+    /// optimizers can reuse its neighborhood.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, Schedule};
+    ///
+    /// let schedule = Schedule::new(2, 3, 0..8);
+    /// let cells: Vec<Cell> = schedule.all_cells().collect();
+    /// assert_eq!(cells.len(), 2 * 3 + schedule.unscheduled_len());
+    /// assert_eq!(cells[0], Cell::Slot(0, 0));
+    /// assert_eq!(cells[1], Cell::Slot(0, 1));
+    /// assert_eq!(cells[6], Cell::Unscheduled(0));
+    /// ```
+    pub fn all_cells(&self) -> impl Iterator<Item = Cell> {
+        let (nplaces, ntimes) = self.slots.dim();
+        (0..nplaces)
+            .flat_map(move |p| (0..ntimes).map(move |t| Cell::Slot(p, t)))
+            .chain((0..self.unscheduled.len()).map(Cell::Unscheduled))
+    }
+
+    /// Turn the schedule back into the problem instance it solves.
+    ///
+    /// The instance gets the schedule's dimensions and buffer time slots,
+    /// and every scheduled and unscheduled activity: grid activities in
+    /// place-major order, then the unscheduled ones. The layout itself is not
+    /// kept, so this persists the problem for reproducing a run rather than
+    /// its solution. Constraints are kept too, though what a locked cell
+    /// holds depends on the layout the instance is rebuilt with.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Schedule, SchedulingInstance};
+    ///
+    /// let instance = SchedulingInstance {
+    ///     id: "talks".to_string(),
+    ///     nplaces: 2,
+    ///     ntimes: 2,
+    ///     activities: vec![4, 1, 3, 1, 5, 9],
+    ///     buffer_times: 0,
+    ///     constraints: Default::default(),
+    ///     place_labels: None,
+    ///     time_labels: None,
+    /// };
+    /// let mut schedule = Schedule::from(instance);
+    /// schedule.improve(|s| s.get_unscheduled_activities().sum::<i32>()).run();
+    ///
+    /// let round_trip = schedule.into_instance("talks".to_string());
+    /// assert_eq!((round_trip.nplaces, round_trip.ntimes), (2, 2));
+    /// let mut activities = round_trip.activities;
+    /// activities.sort();
+    /// assert_eq!(activities, [1, 1, 3, 4, 5, 9]);
+    /// ```
+    pub fn into_instance(self, id: String) -> SchedulingInstance<A> {
+        let (nplaces, ntimes) = self.slots.dim();
+        let activities = self
+            .slots
+            .into_iter()
+            .chain(self.unscheduled)
+            .flatten()
+            .collect();
+        SchedulingInstance {
+            id,
+            nplaces,
+            ntimes,
+            activities,
+            buffer_times: self.buffer_times,
+            constraints: self.constraints,
+            place_labels: self.place_labels,
+            time_labels: self.time_labels,
+        }
+    }
+
+    /// Copy out the problem instance the schedule solves, keeping the
+    /// schedule.
+    ///
+    /// This is [`into_instance`](Schedule::into_instance) for a schedule
+    /// still in use, cloning each activity, with the same activity order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let mut schedule = Schedule::new(2, 2, vec![4, 1, 3, 1, 5, 9].into_iter());
+    /// schedule.improve(|s| s.get_unscheduled_activities().sum::<i32>()).run();
+    ///
+    /// // Rerun the same problem from its initial layout
+    /// let instance = schedule.to_instance("talks".to_string());
+    /// let moved = schedule.clone().into_instance("talks".to_string());
+    /// assert_eq!(instance.activities, moved.activities);
+    /// let rebuilt = Schedule::from(instance);
+    /// assert_eq!(rebuilt.dimensions(), schedule.dimensions());
+    ///
+    /// let sorted = |s: &Schedule<i32>| {
+    ///     let mut all: Vec<i32> = s.iter_scheduled().map(|(_, _, &a)| a).collect();
+    ///     all.extend(s.get_unscheduled_activities());
+    ///     all.sort();
+    ///     all
+    /// };
+    /// assert_eq!(sorted(&rebuilt), sorted(&schedule));
+    /// ```
+    pub fn to_instance(&self, id: String) -> SchedulingInstance<A> {
+        let (nplaces, ntimes) = self.slots.dim();
+        let activities = self
+            .slots
+            .iter()
+            .chain(&self.unscheduled)
+            .flatten()
+            .cloned()
+            .collect();
+        SchedulingInstance {
+            id,
+            nplaces,
+            ntimes,
+            activities,
+            buffer_times: self.buffer_times,
+            constraints: self.constraints.clone(),
+            place_labels: self.place_labels.clone(),
+            time_labels: self.time_labels.clone(),
+        }
+    }
+
+    /// Swap the contents of two cells.
+    ///
+    /// Either cell may be empty, so this can move an activity into an empty
+    /// slot or out to the unscheduled list.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if either cell is out of bounds, in which case
+    /// the schedule is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{BoundsError, Cell, Schedule};
+    ///
+    /// let mut schedule = Schedule::new(1, 2, vec![1, 2, 3].into_iter());
+    /// schedule.swap(Cell::Slot(0, 0), Cell::Unscheduled(0)).unwrap();
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap(), Some(&3));
+    ///
+    /// let err = schedule.swap(Cell::Slot(0, 2), Cell::Slot(0, 0));
+    /// assert!(matches!(err, Err(BoundsError::Time(2))));
+    /// ```
+    pub fn swap(&mut self, a: Cell, b: Cell) -> Result<(), BoundsError> {
+        self.check_cell(a)?;
+        self.check_cell(b)?;
+        self.swap_locations(a, b);
+        Ok(())
+    }
+
+    /// Move the activity in `from` into the empty cell `to`.
+    ///
+    /// Unlike [`swap`](Schedule::swap), this never displaces another
+    /// activity, which makes it the safe primitive for manual edits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MoveError::Bounds`] if either cell is out of bounds,
+    /// [`MoveError::EmptySource`] if `from` holds no activity, and
+    /// [`MoveError::Occupied`] if `to` already holds one. The schedule is
+    /// unchanged on error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, MoveError, Schedule};
+    ///
+    /// // Place 0 holds 1 and 2, place 1 holds 3 and an empty slot
+    /// let mut schedule = Schedule::new(2, 2, vec![1, 2, 3].into_iter());
+    ///
+    /// schedule.move_activity(Cell::Slot(0, 0), Cell::Slot(1, 1)).unwrap();
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap(), None);
+    /// assert_eq!(schedule.get_activity_at(1, 1).unwrap(), Some(&1));
+    ///
+    /// let empty = schedule.move_activity(Cell::Slot(0, 0), Cell::Slot(1, 1));
+    /// assert!(matches!(empty, Err(MoveError::EmptySource(Cell::Slot(0, 0)))));
+    ///
+    /// let occupied = schedule.move_activity(Cell::Slot(0, 1), Cell::Slot(1, 0));
+    /// assert!(matches!(occupied, Err(MoveError::Occupied(Cell::Slot(1, 0)))));
+    /// assert_eq!(schedule.get_activity_at(1, 0).unwrap(), Some(&3));
+    ///
+    /// let outside = schedule.move_activity(Cell::Slot(0, 1), Cell::Slot(2, 0));
+    /// assert!(matches!(outside, Err(MoveError::Bounds(_))));
+    /// ```
+    pub fn move_activity(&mut self, from: Cell, to: Cell) -> Result<(), MoveError> {
+        self.check_cell(from)?;
+        self.check_cell(to)?;
+        if self.is_empty_cell(from) {
+            return Err(MoveError::EmptySource(from));
+        }
+        if !self.is_empty_cell(to) {
+            return Err(MoveError::Occupied(to));
+        }
+        self.swap_locations(from, to);
+        Ok(())
+    }
+
+    /// Apply a batch of [`move_activity`](Schedule::move_activity) moves in
+    /// order, all or nothing.
+    ///
+    /// Each move sees the schedule as left by the moves before it, so a
+    /// planner can chain moves through a cell it has just emptied.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`MoveError`] of the first move that fails, after undoing
+    /// every move before it, so the schedule is exactly as it started.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, MoveError, Schedule};
+    ///
+    /// // Place 0 holds 1 and 2, place 1 holds 3 and an empty slot
+    /// let mut schedule = Schedule::new(2, 2, vec![1, 2, 3].into_iter());
+    /// let before = schedule.clone();
+    ///
+    /// // The second move targets a cell that is still occupied
+    /// let moves = [
+    ///     (Cell::Slot(0, 0), Cell::Slot(1, 1)),
+    ///     (Cell::Slot(0, 1), Cell::Slot(1, 0)),
+    ///     (Cell::Slot(1, 0), Cell::Slot(0, 0)),
+    /// ];
+    /// let err = schedule.apply_moves(&moves);
+    /// assert!(matches!(err, Err(MoveError::Occupied(Cell::Slot(1, 0)))));
+    /// assert_eq!(schedule, before);
+    ///
+    /// // Freeing the cell first makes the batch valid
+    /// schedule.apply_moves(&[moves[0], moves[2], moves[1]]).unwrap();
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap(), Some(&3));
+    /// assert_eq!(schedule.get_activity_at(1, 0).unwrap(), Some(&2));
+    /// assert_eq!(schedule.get_activity_at(1, 1).unwrap(), Some(&1));
+    /// ```
+    pub fn apply_moves(&mut self, moves: &[(Cell, Cell)]) -> Result<(), MoveError> {
+        for (done, &(from, to)) in moves.iter().enumerate() {
+            if let Err(err) = self.move_activity(from, to) {
+                for &(from, to) in moves[..done].iter().rev() {
+                    self.swap_locations(to, from);
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Schedule the activity at `unscheduled_index` into the slot at
+    /// `(place, time)`.
+    ///
+    /// An activity already in the slot takes the scheduled one's place in
+    /// the unscheduled list. Otherwise the entry is removed, and later
+    /// unscheduled activities each move down one index. Neither this nor
+    /// [`unschedule_at`](Schedule::unschedule_at) ever leaves an empty
+    /// entry in the unscheduled list, though entries left empty by the
+    /// search stay where they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MoveError::Bounds`] if either location is out of bounds,
+    /// and [`MoveError::EmptySource`] if the unscheduled entry holds no
+    /// activity. The schedule is unchanged on error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, MoveError, Schedule};
+    ///
+    /// // Slots hold 1 and 2, with 3 and 4 waiting
+    /// let mut schedule = Schedule::new(1, 2, 1..=4);
+    ///
+    /// // 2 is displaced into 4's old entry
+    /// schedule.schedule_from_unscheduled(1, 0, 1).unwrap();
+    /// assert_eq!(schedule.row(0).unwrap(), [Some(1), Some(4)]);
+    /// assert!(schedule.get_unscheduled_activities().eq(&[3, 2]));
+    ///
+    /// // Filling an empty slot removes the entry
+    /// schedule.take_activity_at(0, 0).unwrap();
+    /// schedule.schedule_from_unscheduled(0, 0, 0).unwrap();
+    /// assert_eq!(schedule.row(0).unwrap(), [Some(3), Some(4)]);
+    /// assert_eq!(schedule.unscheduled_len(), 1);
+    ///
+    /// let outside = schedule.schedule_from_unscheduled(1, 0, 0);
+    /// assert!(matches!(outside, Err(MoveError::Bounds(_))));
+    /// ```
+    pub fn schedule_from_unscheduled(
+        &mut self,
+        unscheduled_index: usize,
+        place: usize,
+        time: usize,
+    ) -> Result<(), MoveError> {
+        let from = Cell::Unscheduled(unscheduled_index);
+        self.check_cell(from)?;
+        self.check_cell(Cell::Slot(place, time))?;
+        let Some(activity) = self.unscheduled[unscheduled_index].take() else {
+            return Err(MoveError::EmptySource(from));
+        };
+        match self.slots[(place, time)].replace(activity) {
+            Some(displaced) => self.unscheduled[unscheduled_index] = Some(displaced),
+            None => {
+                self.unscheduled.remove(unscheduled_index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the activity at `(place, time)` to the end of the unscheduled
+    /// list, leaving the slot empty.
+    ///
+    /// Returns the activity's new unscheduled index, or `None` if the slot
+    /// was already empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if `(place, time)` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let mut schedule = Schedule::new(1, 2, 1..=3);
+    /// assert_eq!(schedule.unschedule_at(0, 0), Ok(Some(1)));
+    /// assert_eq!(schedule.unschedule_at(0, 0), Ok(None));
+    /// assert!(schedule.get_unscheduled_activities().eq(&[3, 1]));
+    ///
+    /// // And back again
+    /// schedule.schedule_from_unscheduled(1, 0, 0).unwrap();
+    /// assert_eq!(schedule.row(0).unwrap(), [Some(1), Some(2)]);
+    /// assert!(schedule.get_unscheduled_activities().eq(&[3]));
+    /// ```
+    pub fn unschedule_at(
+        &mut self,
+        place: usize,
+        time: usize,
+    ) -> Result<Option<usize>, BoundsError> {
+        self.check_cell(Cell::Slot(place, time))?;
+        let Some(activity) = self.slots[(place, time)].take() else {
+            return Ok(None);
+        };
+        self.unscheduled.push(Some(activity));
+        Ok(Some(self.unscheduled.len() - 1))
+    }
+
+    /// Evaluate a penalty function as if two cells were swapped.
+    ///
+    /// Applies the swap, computes `penalty_fn(self)`, and swaps back, so
+    /// external search algorithms can score candidate moves without writing
+    /// their own undo. The schedule is restored even if `penalty_fn` panics.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if either cell is out of bounds, without
+    /// calling `penalty_fn`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, Schedule};
+    ///
+    /// // Penalty: the activity in the first slot should be large
+    /// let penalty = |s: &Schedule<i32>| -s.get_activity_at(0, 0).unwrap().copied().unwrap_or(0);
+    ///
+    /// let mut schedule = Schedule::new(1, 2, vec![1, 2, 3].into_iter());
+    /// let before = schedule.clone();
+    /// let moved = schedule.penalty_of_move(Cell::Slot(0, 0), Cell::Unscheduled(0), penalty);
+    /// assert_eq!(moved.unwrap(), -3);
+    ///
+    /// // Nothing was committed
+    /// assert_eq!(schedule.slots(), before.slots());
+    /// assert_eq!(penalty(&schedule), -1);
+    ///
+    /// // Even a panicking penalty function leaves the schedule as it was
+    /// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    ///     schedule.penalty_of_move(Cell::Slot(0, 0), Cell::Slot(0, 1), |_| -> i32 { panic!() })
+    /// }));
+    /// assert!(result.is_err());
+    /// assert_eq!(schedule.slots(), before.slots());
+    /// ```
+    pub fn penalty_of_move<P>(
+        &mut self,
+        a: Cell,
+        b: Cell,
+        penalty_fn: impl FnOnce(&Schedule<A>) -> P,
+    ) -> Result<P, BoundsError> {
+        /// Swaps the cells back when dropped, including during unwinding.
+        struct Undo<'s, A: Clone> {
+            schedule: &'s mut Schedule<A>,
+            a: Cell,
+            b: Cell,
+        }
+
+        impl<A: Clone> Drop for Undo<'_, A> {
+            fn drop(&mut self) {
+                self.schedule.swap_locations(self.b, self.a);
+            }
+        }
+
+        self.swap(a, b)?;
+        let undo = Undo {
+            schedule: self,
+            a,
+            b,
+        };
+        Ok(penalty_fn(undo.schedule))
+    }
+
+    /// Swap two cells if that strictly lowers the penalty, returning whether
+    /// the swap was kept.
+    ///
+    /// This is one step of a hand-written hill-climber: the swap is scored
+    /// with [`penalty_of_move`](Schedule::penalty_of_move), so a rejected or
+    /// panicking evaluation always leaves the schedule as it was.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoundsError`] if either cell is out of bounds, without
+    /// calling `penalty_fn`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, Schedule};
+    ///
+    /// // Larger activities want earlier slots
+    /// let lateness = |s: &Schedule<usize>| {
+    ///     s.slots().indexed_iter().filter_map(|((_, t), a)| a.map(|a| a * t)).sum::<usize>()
+    /// };
+    ///
+    /// let mut schedule = Schedule::new(1, 2, vec![1, 2].into_iter());
+    /// assert!(schedule.swap_if_improves(Cell::Slot(0, 0), Cell::Slot(0, 1), lateness).unwrap());
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap(), Some(&2));
+    ///
+    /// // Swapping back would be worse, so it is undone
+    /// let kept = schedule.clone();
+    /// assert!(!schedule.swap_if_improves(Cell::Slot(0, 0), Cell::Slot(0, 1), lateness).unwrap());
+    /// assert_eq!(schedule, kept);
+    /// ```
+    pub fn swap_if_improves<P: PartialOrd>(
+        &mut self,
+        a: Cell,
+        b: Cell,
+        penalty_fn: impl Fn(&Schedule<A>) -> P,
+    ) -> Result<bool, BoundsError> {
+        self.check_cell(a)?;
+        self.check_cell(b)?;
+        let before = penalty_fn(self);
+        let after = self.penalty_of_move(a, b, &penalty_fn)?;
+        let improves = after < before;
+        if improves {
+            self.swap_locations(a, b);
+        }
+        Ok(improves)
+    }
+
+    /// Swap the contents of two cells without bounds checking.
+    ///
+    /// This is for custom move generators in hot loops that have already
+    /// validated their cells. Bounds are still checked with `debug_assert!`
+    /// in debug builds.
+    ///
+    /// # Safety
+    ///
+    /// Both cells must be in bounds: for [`Cell::Slot`] the place and time
+    /// must be below [`dimensions`](Schedule::dimensions), and for
+    /// [`Cell::Unscheduled`] the index must be below
+    /// [`unscheduled_len`](Schedule::unscheduled_len). Use
+    /// [`swap`](Schedule::swap) when this is not already known.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Cell, Schedule};
+    ///
+    /// let mut schedule = Schedule::new(2, 2, vec![1, 2, 3, 4].into_iter());
+    /// let (nplaces, ntimes) = schedule.dimensions();
+    /// for p in 0..nplaces {
+    ///     for t in 0..ntimes / 2 {
+    ///         let (a, b) = (Cell::Slot(p, t), Cell::Slot(p, ntimes - 1 - t));
+    ///         // SAFETY: p < nplaces and both times are below ntimes
+    ///         unsafe { schedule.swap_unchecked(a, b) };
+    ///     }
+    /// }
+    /// assert_eq!(schedule.get_activity_at(0, 0).unwrap(), Some(&2));
+    /// assert_eq!(schedule.get_activity_at(1, 1).unwrap(), Some(&3));
+    /// ```
+    pub unsafe fn swap_unchecked(&mut self, a: Cell, b: Cell) {
+        debug_assert!(self.check_cell(a).is_ok(), "cell {a:?} out of bounds");
+        debug_assert!(self.check_cell(b).is_ok(), "cell {b:?} out of bounds");
+
+        if a == b {
+            return;
+        }
+        // SAFETY: the caller guarantees both cells are in bounds, and they
+        // are distinct so the two pointers don't alias.
+        unsafe {
+            let ptr_a = self.cell_ptr(a);
+            let ptr_b = self.cell_ptr(b);
+            std::ptr::swap(ptr_a, ptr_b);
+        }
+    }
+
+    /// Raw pointer to the contents of `cell`.
+    ///
+    /// # Safety
+    ///
+    /// `cell` must be in bounds.
+    unsafe fn cell_ptr(&mut self, cell: Cell) -> *mut Option<A> {
+        // SAFETY: the caller guarantees the cell is in bounds
+        unsafe {
+            match cell {
+                Cell::Slot(p, t) => self.slots.uget_mut((p, t)),
+                Cell::Unscheduled(i) => self.unscheduled.get_unchecked_mut(i),
+            }
+        }
+    }
+
+    /// Count scheduled and unscheduled activities per topic.
+    ///
+    /// The `topic` closure extracts the grouping key from each activity. Each
+    /// key maps to `(scheduled, unscheduled)` counts; keys only appear if at
+    /// least one activity has them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// #[derive(Clone)]
+    /// struct Talk { topic: usize }
+    ///
+    /// let talks = [1, 1, 2, 1, 3].map(|topic| Talk { topic });
+    /// let schedule = Schedule::new(1, 3, talks.into_iter()); // 3 slots, 5 talks
+    ///
+    /// let counts = schedule.topic_counts(|t| t.topic);
+    /// assert_eq!(counts[&1], (2, 1));
+    /// assert_eq!(counts[&2], (1, 0));
+    /// assert_eq!(counts[&3], (0, 1));
+    ///
+    /// // Every activity is counted exactly once
+    /// let total: usize = counts.values().map(|(s, u)| s + u).sum();
+    /// assert_eq!(total, 5);
+    /// ```
+    pub fn topic_counts<K>(&self, topic: impl Fn(&A) -> K) -> HashMap<K, (usize, usize)>
+    where
+        K: Eq + Hash,
+    {
+        let mut counts = HashMap::new();
+        for a in self.slots.iter().flatten() {
+            counts.entry(topic(a)).or_insert((0, 0)).0 += 1;
+        }
+        for a in self.get_unscheduled_activities() {
+            counts.entry(topic(a)).or_insert((0, 0)).1 += 1;
+        }
+        counts
+    }
+
+    /// Histogram of priorities of scheduled activities.
+    ///
+    /// The `priority` closure extracts the priority from each activity. The
+    /// result maps each priority to the number of scheduled activities having
+    /// it, in ascending priority order. Unscheduled activities are not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::new(2, 2, vec![5, 3, 5, 1, 9].into_iter());
+    /// let histogram = schedule.priority_histogram(|&p| p);
+    ///
+    /// assert_eq!(histogram.into_iter().collect::<Vec<_>>(), [(1, 1), (3, 1), (5, 2)]);
+    /// ```
+    pub fn priority_histogram<K>(&self, priority: impl Fn(&A) -> K) -> BTreeMap<K, usize>
+    where
+        K: Ord,
+    {
+        let mut histogram = BTreeMap::new();
+        for a in self.slots.iter().flatten() {
+            *histogram.entry(priority(a)).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Find the `n` most crowded time slots.
+    ///
+    /// Returns `(time, count)` pairs for the time slots holding the most
+    /// scheduled activities, most crowded first; ties go to the earlier time
+    /// slot. Fewer than `n` pairs are returned if there are fewer time slots.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // Four activities fill place 0 at times 0..3 and place 1 at time 0
+    /// let schedule = Schedule::new(2, 3, vec![1, 2, 3, 4].into_iter());
+    /// assert_eq!(schedule.densest_columns(2), [(0, 2), (1, 1)]);
+    /// assert_eq!(schedule.densest_columns(10).len(), 3);
+    /// ```
+    pub fn densest_columns(&self, n: usize) -> Vec<(usize, usize)> {
+        let mut columns: Vec<(usize, usize)> = self
+            .slots
+            .axis_iter(Axis(1))
+            .map(|column| column.iter().flatten().count())
+            .enumerate()
+            .collect();
+        columns.sort_by_key(|&(t, count)| (Reverse(count), t));
+        columns.truncate(n);
+        columns
+    }
+
+    /// Sum a transition cost over activities back to back in the same place.
+    ///
+    /// `cost` is called for every pair of activities at `(place, time)` and
+    /// `(place, time + 1)`, earlier activity first, and the results are
+    /// added up. Penalty functions can use this to discourage, say, two talks
+    /// by the same speaker in one room without a break for setup.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // Place 0 holds 1 then 2, place 1 holds 3 then an empty slot
+    /// let schedule = Schedule::new(2, 2, vec![1, 2, 3].into_iter());
+    /// let cost = schedule.transition_cost(|a, b| (a * 10 + b) as f32);
+    /// assert_eq!(cost, 12.0);
+    /// ```
+    pub fn transition_cost(&self, cost: impl Fn(&A, &A) -> f32) -> f32 {
+        let (nplaces, ntimes) = self.slots.dim();
+        let mut total = 0.0;
+        for p in 0..nplaces {
+            for t in 1..ntimes {
+                if let (Some(a), Some(b)) = (&self.slots[(p, t - 1)], &self.slots[(p, t)]) {
+                    total += cost(a, b);
+                }
+            }
+        }
+        total
+    }
+
+    /// Score this schedule relative to a baseline schedule.
+    ///
+    /// Returns `penalty(self) / penalty(baseline)`, so a schedule as bad as
+    /// the baseline scores 1 and a penalty-free one scores 0. Taking the
+    /// initial schedule as the baseline makes scores comparable across
+    /// instances of different sizes, where raw penalties are not. A baseline
+    /// with no penalty gives 0, since nothing can improve on it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let lateness = |s: &Schedule<usize>| {
+    ///     s.slots().indexed_iter()
+    ///         .filter_map(|((_, t), a)| a.map(|a| (a * t) as f32))
+    ///         .sum::<f32>()
+    /// };
+    /// let initial = Schedule::new(1, 3, vec![1, 2, 3].into_iter());
+    /// assert_eq!(initial.normalized_penalty(&initial, lateness), 1.0);
+    ///
+    /// let sorted = Schedule::new(1, 3, vec![3, 2, 1].into_iter());
+    /// assert_eq!(sorted.normalized_penalty(&initial, lateness), 4.0 / 8.0);
+    /// ```
+    pub fn normalized_penalty<F>(&self, baseline: &Schedule<A>, penalty: F) -> f32
+    where
+        F: Fn(&Schedule<A>) -> f32,
+    {
+        let base = penalty(baseline);
+        if base <= 0.0 {
+            return 0.0;
+        }
+        penalty(self) / base
+    }
+
+    /// Randomly rearrange all activities, as a restart does.
+    ///
+    /// All activities, scheduled or not, are shuffled with `rng` and dealt
+    /// back out: slots first in place-major order, then the unscheduled
+    /// entries. Cells fixed by [`Constraints`] keep their contents. Seeding
+    /// `rng` gives a reproducible random starting layout, for instance to
+    /// benchmark a penalty function from several starts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let mut a = Schedule::new(3, 3, 0..12);
+    /// let mut b = a.clone();
+    /// a.shuffle(&mut fastrand::Rng::with_seed(7));
+    /// b.shuffle(&mut fastrand::Rng::with_seed(7));
+    /// assert_eq!(a, b);
+    /// assert_eq!(a.empty_slots_count(), 0);
+    /// ```
+    pub fn shuffle(&mut self, rng: &mut Rng) {
+        // Collect all activities from both slots and unscheduled
+        let mut all_activities = Vec::new();
+
+        // Collect from slots, leaving constrained cells alone
+        let fixed = self.fixed_cells();
+        for (slot, _) in self
+            .slots
+            .iter_mut()
+            .zip(&fixed)
+            .filter(|(_, &fixed)| !fixed)
+        {
+            if let Some(activity) = slot.take() {
+                all_activities.push(activity);
+            }
+        }
+
+        // Collect from unscheduled
+        for unscheduled_slot in self.unscheduled.iter_mut() {
+            if let Some(activity) = unscheduled_slot.take() {
+                all_activities.push(activity);
+            }
+        }
+
+        // Shuffle the activities
+        for i in (1..all_activities.len()).rev() {
+            let j = rng.usize(0..=i);
+            all_activities.swap(i, j);
+        }
+
+        // Redistribute activities: fill slots first, then unscheduled
+        let mut activity_iter = all_activities.into_iter();
+
+        // Fill slots
+        for (slot, _) in self
+            .slots
+            .iter_mut()
+            .zip(&fixed)
+            .filter(|(_, &fixed)| !fixed)
+        {
+            if let Some(activity) = activity_iter.next() {
+                *slot = Some(activity);
+            }
+        }
+
+        // Fill unscheduled
+        for unscheduled_slot in self.unscheduled.iter_mut() {
+            if let Some(activity) = activity_iter.next() {
+                *unscheduled_slot = Some(activity);
+            }
+        }
+    }
+
+    /// Randomly rearrange all activities, spreading each topic across the
+    /// time slots.
+    ///
+    /// Like [`shuffle`](Schedule::shuffle), a uniformly random choice of
+    /// activities goes into the grid, the rest to the unscheduled list, and
+    /// cells fixed by [`Constraints`] keep their contents. The grid
+    /// activities are then grouped by `topic` and dealt out one time slot
+    /// after another, so a topic shares a time slot with itself only when
+    /// it has more activities than there are time slots. The topic order
+    /// and the order within each topic are random.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // Three topics of three activities each on a 3x3 grid
+    /// let mut schedule = Schedule::new(3, 3, 0..9usize);
+    /// schedule.shuffle_balanced(&mut fastrand::Rng::with_seed(5), |a| a % 3);
+    /// for column in schedule.slots().columns() {
+    ///     let mut topics: Vec<usize> = column.iter().flatten().map(|a| a % 3).collect();
+    ///     topics.sort();
+    ///     assert_eq!(topics, [0, 1, 2]);
+    /// }
+    /// ```
+    pub fn shuffle_balanced(&mut self, rng: &mut Rng, topic: impl Fn(&A) -> usize) {
+        self.shuffle(rng);
+
+        // Empty the movable grid cells, noting the free cells of each column
+        let fixed = self.fixed_cells();
+        let (nplaces, ntimes) = self.slots.dim();
+        let mut free: Vec<Vec<usize>> = vec![Vec::new(); ntimes];
+        let mut groups: BTreeMap<usize, Vec<A>> = BTreeMap::new();
+        for p in (0..nplaces).rev() {
+            for (t, free) in free.iter_mut().enumerate() {
+                if fixed[(p, t)] {
+                    continue;
+                }
+                free.push(p);
+                if let Some(activity) = self.slots[(p, t)].take() {
+                    groups.entry(topic(&activity)).or_default().push(activity);
+                }
+            }
+        }
+        let mut groups: Vec<Vec<A>> = groups.into_values().collect();
+        rng.shuffle(&mut groups);
+
+        // Deal topic after topic, striping across the columns with room
+        let mut t = 0;
+        for activity in groups.into_iter().flatten() {
+            while free[t].is_empty() {
+                t = (t + 1) % ntimes;
+            }
+            let p = free[t].pop().unwrap();
+            self.slots[(p, t)] = Some(activity);
+            t = (t + 1) % ntimes;
+        }
+    }
+
+    fn swap_locations(&mut self, loc1: Cell, loc2: Cell) {
+        use Cell::*;
+
+        let activity1 = match loc1 {
+            Slot(p, t) => self.slots[(p, t)].take(),
+            Unscheduled(i) => self.unscheduled[i].take(),
+        };
+        let activity2 = match loc2 {
+            Slot(p, t) => self.slots[(p, t)].take(),
+            Unscheduled(i) => self.unscheduled[i].take(),
+        };
+
+        match loc1 {
+            Slot(p, t) => self.slots[(p, t)] = activity2,
+            Unscheduled(i) => self.unscheduled[i] = activity2,
+        }
+        match loc2 {
+            Slot(p, t) => self.slots[(p, t)] = activity1,
+            Unscheduled(i) => self.unscheduled[i] = activity1,
+        }
+    }
+
+    /// Move every activity in an unlocked slot that `allowed` rejects to
+    /// the unscheduled list, filling empty entries first, and return how
+    /// many moved.
+    fn unschedule_disallowed(&mut self, allowed: impl Fn(&A, usize, usize) -> bool) -> usize {
+        let locked = &self.constraints.locked;
+        let mut moved = 0;
+        // Popped lowest first
+        let mut holes: Vec<usize> = (0..self.unscheduled.len())
+            .rev()
+            .filter(|&i| self.unscheduled[i].is_none())
+            .collect();
+        for ((p, t), slot) in self.slots.indexed_iter_mut() {
+            if locked.contains(&(p, t)) || slot.as_ref().is_none_or(|a| allowed(a, p, t)) {
+                continue;
+            }
+            moved += 1;
+            match holes.pop() {
+                Some(i) => self.unscheduled[i] = slot.take(),
+                None => self.unscheduled.push(slot.take()),
+            }
+        }
+        moved
+    }
+
+    /// What an in-bounds `cell` holds.
+    fn occupant(&self, cell: Cell) -> Option<&A> {
+        match cell {
+            Cell::Slot(p, t) => self.slots[(p, t)].as_ref(),
+            Cell::Unscheduled(i) => self.unscheduled[i].as_ref(),
+        }
+    }
+
+    fn is_empty_cell(&self, cell: Cell) -> bool {
+        self.occupant(cell).is_none()
+    }
+
+    fn check_cell(&self, cell: Cell) -> Result<(), BoundsError> {
+        let (nplaces, ntimes) = self.slots.dim();
+        match cell {
+            Cell::Slot(p, _) if p >= nplaces => Err(BoundsError::Place(p)),
+            Cell::Slot(_, t) if t >= ntimes => Err(BoundsError::Time(t)),
+            Cell::Unscheduled(i) if i >= self.unscheduled.len() => Err(BoundsError::Unscheduled(i)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Pad the unscheduled list as the search `config` needs it: leaving
+    /// room for every activity covers any scheduling target.
+    fn reserve_for(&mut self, config: &ImproveConfig) {
+        if config.or_moves {
+            self.reserve_unscheduling(0);
+        } else if let Some(k) = config.target_scheduled {
+            self.reserve_unscheduling(k);
+        }
+    }
+
+    /// Pad the unscheduled list with empty entries so that swaps can reduce
+    /// the grid to `k` activities.
+    fn reserve_unscheduling(&mut self, k: usize) {
+        let nscheduled = self.slots.iter().flatten().count();
+        let nholes = self.unscheduled.iter().filter(|opt| opt.is_none()).count();
+        let needed = nscheduled.saturating_sub(k).saturating_sub(nholes);
+        self.unscheduled
+            .extend(std::iter::repeat_with(|| None).take(needed));
+    }
+
+    /// Create an improver for this schedule with the given penalty function.
+    ///
+    /// Returns an [`Improver`] that can be configured with various parameters
+    /// before running the improvement.
+    ///
+    /// Improvement starts from the schedule's current layout, so a schedule
+    /// produced by an earlier run can be warm-started and improved further.
+    ///
+    /// # Arguments
+    ///
+    /// * `penalty_fn` - Function that evaluates schedule quality. Lower scores are better.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// #[derive(Clone)]
+    /// struct Meeting { priority: usize }
+    ///
+    /// let mut schedule = Schedule::new(3, 5, vec![Meeting { priority: 10 }].into_iter());
+    ///
+    /// // Simple improvement
+    /// schedule.improve(|s: &Schedule<Meeting>| {
+    ///     let unscheduled_count = s.get_unscheduled_activities().count() + s.empty_slots_count();
+    ///     let priority_penalty = s.get_unscheduled_activities().map(|m| m.priority as f32).sum::<f32>();
+    ///     (unscheduled_count, priority_penalty)
+    /// }).run();
+    ///
+    /// // With custom parameters
+    /// schedule.improve(|s: &Schedule<Meeting>| {
+    ///     let unscheduled_count = s.get_unscheduled_activities().count() + s.empty_slots_count();
+    ///     let priority_penalty = s.get_unscheduled_activities().map(|m| m.priority as f32).sum::<f32>();
+    ///     (unscheduled_count, priority_penalty)
+    /// }).max_swaps(2000).with_noise().restarts(3).run();
+    /// ```
+    pub fn improve<F, P>(&mut self, penalty_fn: F) -> Improver<'_, A, F, P>
+    where
+        F: Fn(&Schedule<A>) -> P,
+        P: Copy + PartialOrd + Debug,
+    {
+        Improver::new(self, penalty_fn, |_| true)
+    }
+
+    /// Create an improver for a penalty function with hard constraints.
+    ///
+    /// The penalty function returns `Ok(score)` for schedules satisfying all
+    /// hard constraints and `Err(`[`Infeasible`]`)` otherwise. Any feasible
+    /// score beats any infeasible one, so greedy moves never leave the
+    /// feasible region once it is reached, and noise moves into an infeasible
+    /// state are rejected. A schedule that starts out infeasible is steered
+    /// toward feasibility by the violation count carried in [`Infeasible`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::{Infeasible, Schedule};
+    ///
+    /// // Activity 3 would score best at time 0, but time 0 is forbidden to it
+    /// let penalty = |s: &Schedule<usize>| {
+    ///     let early = (0..2).any(|p| s.get_activity_at(p, 0).unwrap() == Some(&3));
+    ///     if early {
+    ///         Err(Infeasible(1))
+    ///     } else {
+    ///         Ok(1.0f32)
+    ///     }
+    /// };
+    ///
+    /// let mut schedule = Schedule::new(2, 2, 0..4usize);
+    /// schedule.improve_feasible(penalty).with_noise().restarts(5).with_rng_seed(1).run();
+    ///
+    /// assert!(penalty(&schedule).is_ok());
+    /// ```
+    pub fn improve_feasible<F, S>(
+        &mut self,
+        penalty_fn: F,
+    ) -> Improver<'_, A, F, Result<S, Infeasible>>
+    where
+        F: Fn(&Schedule<A>) -> Result<S, Infeasible>,
+        S: Copy + PartialOrd + Debug,
+    {
+        Improver::new(self, penalty_fn, Result::is_ok)
+    }
+    /// Create an improver that ranks several objectives lexicographically.
+    ///
+    /// The objectives are listed most important first. A candidate beats
+    /// another when it improves the first objective on which they differ, so
+    /// no gain on a later objective pays for a loss on an earlier one. The
+    /// improver's penalty is the array of objective values, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// // First keep odd activities out of time 0, then pack time 0 with
+    /// // the largest activities that remain
+    /// let odd_early = |s: &Schedule<usize>| {
+    ///     (0..2).filter(|&p| s.get_activity_at(p, 0).unwrap().is_some_and(|a| a % 2 == 1)).count()
+    /// };
+    /// let early_sum = |s: &Schedule<usize>| {
+    ///     -((0..2).filter_map(|p| s.get_activity_at(p, 0).unwrap().copied()).sum::<usize>() as i64)
+    /// };
+    ///
+    /// let mut schedule = Schedule::new(2, 2, 0..4usize);
+    /// schedule
+    ///     .improve_lexicographic([&|s: &Schedule<usize>| odd_early(s) as i64, &early_sum])
+    ///     .restarts(3)
+    ///     .with_rng_seed(1)
+    ///     .run();
+    ///
+    /// assert_eq!(odd_early(&schedule), 0);
+    /// assert_eq!(early_sum(&schedule), -2);
+    /// ```
+    pub fn improve_lexicographic<'a, P, const N: usize>(
+        &'a mut self,
+        objectives: [&'a Objective<'a, A, P>; N],
+    ) -> Improver<'a, A, impl Fn(&Schedule<A>) -> [P; N] + 'a, [P; N]>
+    where
+        P: Copy + PartialOrd + Debug,
+    {
+        let penalty_fn =
+            move |schedule: &Schedule<A>| std::array::from_fn(|i| objectives[i](schedule));
+        Improver::new(self, penalty_fn, |_| true)
+    }
+}
+
+impl<A: Hash> Schedule<A> {
+    /// Hash the schedule's contents, for detecting revisited states.
+    ///
+    /// The fingerprint covers the dimensions, the buffer time count, which
+    /// activity sits in which slot, and the unscheduled activities as a
+    /// multiset: their order and any empty unscheduled entries don't matter.
+    /// Equal schedules always have equal fingerprints. The hash is
+    /// deterministic within a build, but may change between Rust releases,
+    /// so don't persist it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let a = Schedule::new(1, 2, vec![1, 2, 3, 4].into_iter());
+    /// let mut b = a.clone();
+    /// assert_eq!(a, b);
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    ///
+    /// // Reordering unscheduled activities keeps the fingerprint
+    /// let reordered = Schedule::new(1, 2, vec![1, 2, 4, 3].into_iter());
+    /// assert_eq!(a.fingerprint(), reordered.fingerprint());
+    ///
+    /// // Dropping an unscheduled activity matches a schedule built without it
+    /// b.retain_unscheduled(|&x| x != 3);
+    /// let c = Schedule::new(1, 2, vec![1, 2, 4].into_iter());
+    /// assert_eq!(b.fingerprint(), c.fingerprint());
+    ///
+    /// // Moving a scheduled activity changes it
+    /// let d = Schedule::new(1, 2, vec![2, 1, 3, 4].into_iter());
+    /// assert_ne!(a.fingerprint(), d.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.slots.dim().hash(&mut hasher);
+        self.buffer_times.hash(&mut hasher);
+        for slot in &self.slots {
+            slot.hash(&mut hasher);
+        }
+
+        // Combine unscheduled hashes commutatively so that order is ignored
+        let mut nunscheduled = 0usize;
+        let mut unscheduled = 0u64;
+        for a in self.unscheduled.iter().flatten() {
+            let mut h = DefaultHasher::new();
+            a.hash(&mut h);
+            unscheduled = unscheduled.wrapping_add(h.finish());
+            nunscheduled += 1;
+        }
+        nunscheduled.hash(&mut hasher);
+        unscheduled.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<A: Clone> From<SchedulingInstance<A>> for Schedule<A> {
+    /// Build the initial schedule for an instance, as [`Schedule::new`] does,
+    /// keeping its buffer time slots, constraints and labels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid is too large to allocate or the labels do not fit
+    /// it; check with [`SchedulingInstance::validate`] first for untrusted
+    /// instances.
+    fn from(instance: SchedulingInstance<A>) -> Self {
+        let mut schedule = Schedule::new(
+            instance.nplaces,
+            instance.ntimes,
+            instance.activities.into_iter(),
+        )
+        .with_buffer_times(instance.buffer_times)
+        .with_constraints(instance.constraints);
+        if let Some(labels) = instance.place_labels {
+            schedule = schedule.with_place_labels(labels);
+        }
+        if let Some(labels) = instance.time_labels {
+            schedule = schedule.with_time_labels(labels);
+        }
+        schedule
+    }
+}
+
+/// Outcome of one swap iteration of a single run.
+enum RunStep<P> {
+    /// The run found a new best schedule with this penalty.
+    Improved(P),
+    /// The run moved (or tried to) without a new best.
+    Moved,
+    /// The run is over: budget, timeout or convergence.
+    Finished,
+}
+
+/// Penalty arithmetic for [`Improver::cache_swap_costs`], captured where
+/// the penalty type is known to support it.
+#[derive(Clone, Copy)]
+struct SwapCostOps<P> {
+    add: fn(P, P) -> P,
+    sub: fn(P, P) -> P,
+}
+
+/// Penalty of each candidate swap of a run, kept between greedy steps.
+struct SwapCosts<P> {
+    ops: SwapCostOps<P>,
+    /// Score of swapping locations `i < j`, at `[[i, j]]`, if still known
+    scores: Array2<Option<SwapScore<P>>>,
+    /// The time slot of each location, `None` if unscheduled
+    times: Vec<Option<usize>>,
+    /// The locations in each time slot
+    columns: Vec<Vec<usize>>,
+}
+
+/// A candidate swap as evaluated at some greedy step.
+#[derive(Clone, Copy)]
+struct SwapScore<P> {
+    /// The step that evaluated the swap, numbered like
+    /// [`RunState::swap_iter`]
+    step: usize,
+    /// The schedule's penalty then
+    before: P,
+    /// The penalty with the swap made
+    after: P,
+}
+
+impl<P: Copy + PartialOrd> SwapCosts<P> {
+    fn new(ops: SwapCostOps<P>, locations: &[Cell]) -> Self {
+        let ntotal = locations.len();
+        let times: Vec<Option<usize>> = locations
+            .iter()
+            .map(|&cell| match cell {
+                Cell::Slot(_, t) => Some(t),
+                Cell::Unscheduled(_) => None,
+            })
+            .collect();
+        let ntimes = times.iter().flatten().max().map_or(0, |t| t + 1);
+        let mut columns = vec![Vec::new(); ntimes];
+        for (i, t) in times.iter().enumerate() {
+            if let &Some(t) = t {
+                columns[t].push(i);
+            }
+        }
+        Self {
+            ops,
+            scores: Array2::from_elem((ntotal, ntotal), None),
+            times,
+            columns,
+        }
+    }
+
+    /// What swapping `key` at `step` should give, now that the schedule's
+    /// penalty is `penalty`, and whether that was scored at an earlier
+    /// step. `None` if the swap must be evaluated again.
+    fn predict(&self, key: [usize; 2], penalty: P, step: usize) -> Option<(P, bool)> {
+        let score = self.scores[key]?;
+        if score.step == step {
+            return Some((score.after, false));
+        }
+        // Adding first never takes an unsigned penalty below zero. A
+        // prediction that would is stale, and so is evaluated again.
+        let raised = (self.ops.add)(penalty, score.after);
+        if raised < score.before {
+            return None;
+        }
+        Some(((self.ops.sub)(raised, score.before), true))
+    }
+
+    /// Record what swapping `key` gave at `step`.
+    fn record(&mut self, key: [usize; 2], step: usize, before: P, after: P) {
+        self.scores[key] = Some(SwapScore {
+            step,
+            before,
+            after,
+        });
+    }
+
+    /// Forget every swap involving location `i`, whose contents changed,
+    /// or any other location in its time slot, whose neighbours did.
+    fn invalidate(&mut self, i: usize) {
+        let touched = match self.times[i] {
+            Some(t) => &self.columns[t][..],
+            None => std::slice::from_ref(&i),
+        };
+        for &k in touched {
+            self.scores.row_mut(k).fill(None);
+            self.scores.column_mut(k).fill(None);
+        }
+    }
+}
+
+/// Add the time since `start` to `total`, both of which are `None` unless
+/// the search is being profiled.
+fn add_elapsed(total: &mut Option<Duration>, start: Option<Instant>) {
+    if let (Some(total), Some(start)) = (total, start) {
+        *total += start.elapsed();
+    }
+}
+
+/// An [`Acceptance`] rule as it stands during one run.
+struct Acceptor<P> {
+    rule: Acceptance,
+    energy: fn(&P) -> f64,
+    /// The annealing temperature, the great deluge water level, or the
+    /// starting energy for late acceptance
+    level: f64,
+    /// Late acceptance: the current energy after each of the last
+    /// `history` swaps tried, as a ring that grows only as swaps are
+    /// tried, so a history longer than the run costs nothing
+    history: Vec<f64>,
+    /// Swaps tried so far
+    tried: usize,
+}
+
+impl<P> Acceptor<P> {
+    fn new(rule: Acceptance, energy: fn(&P) -> f64, initial: &P) -> Self {
+        let start = energy(initial);
+        let level = match rule {
+            Acceptance::Annealing {
+                initial_temperature,
+                ..
+            } => initial_temperature,
+            Acceptance::GreatDeluge { .. } | Acceptance::Late { .. } => start,
+        };
+        Self {
+            rule,
+            energy,
+            level,
+            history: Vec::new(),
+            tried: 0,
+        }
+    }
+
+    /// Whether to move from a schedule scoring `current` to one scoring
+    /// `candidate`.
+    fn accepts(&self, current: &P, candidate: &P, rng: &mut Rng) -> bool {
+        let (now, next) = ((self.energy)(current), (self.energy)(candidate));
+        if next <= now {
+            return true;
+        }
+        match self.rule {
+            Acceptance::Annealing { .. } => rng.f64() < ((now - next) / self.level).exp(),
+            Acceptance::GreatDeluge { .. } => next <= self.level,
+            Acceptance::Late { history } if self.tried < history => next <= self.level,
+            Acceptance::Late { history } => next <= self.history[self.tried % history],
+        }
+    }
+
+    /// Move on to the next swap, the last one having left the schedule
+    /// scoring `current`.
+    fn advance(&mut self, current: &P) {
+        match self.rule {
+            Acceptance::Annealing { cooling, .. } => self.level *= cooling,
+            Acceptance::GreatDeluge { rain } => self.level -= rain,
+            Acceptance::Late { history } => {
+                let now = (self.energy)(current);
+                if self.history.len() < history {
+                    self.history.push(now);
+                } else {
+                    self.history[self.tried % history] = now;
                 }
-            } else if !noise {
-                // Pure greedy search: stop when no improvement found (local optimum reached)
-                break;
             }
         }
+        self.tried += 1;
+    }
+}
+
+/// State of a single improvement run, advanced one swap iteration at a time.
+struct RunState<A, P> {
+    rng: Rng,
+    all_locations: Vec<Cell>,
+    nslots: usize,
+    ntotal: usize,
+    nswaps: usize,
+    swap_iter: usize,
+    scan: Vec<usize>,
+    penalty: P,
+    best_penalty: P,
+    best_schedule: Schedule<A>,
+    timeout_checker: Option<TimeoutChecker>,
+    swap_costs: Option<SwapCosts<P>>,
+    /// Time spent evaluating moves so far, kept only when profiling
+    evaluating: Option<Duration>,
+    /// The rule keeping random swaps, for a sampling search
+    acceptor: Option<Acceptor<P>>,
+    /// Location pairs swapped by the last greedy steps, oldest first,
+    /// keyed like [`SwapCosts::scores`]
+    tabu: VecDeque<[usize; 2]>,
+}
+
+impl<A: Clone, P: Copy + PartialOrd + Debug> RunState<A, P> {
+    /// Set up a run on `schedule`, or `None` if it has no locations at all.
+    fn start<F>(
+        schedule: &Schedule<A>,
+        penalty_fn: &F,
+        config: &ImproveConfig,
+        swap_costs: Option<SwapCostOps<P>>,
+        energy: Option<fn(&P) -> f64>,
+        rng: Rng,
+        observer: &mut Observer<'_, A, P>,
+    ) -> Option<Self>
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        // Setup: calculate dimensions and generate all possible locations
+        let (nplaces, ntimes) = schedule.slots.dim();
+        let nunscheduled = schedule.unscheduled.len();
+        let all_locations = schedule.movable_cells();
+        let ntotal = all_locations.len();
+        let nslots = ntotal - nunscheduled;
+        let swap_costs = swap_costs.map(|ops| SwapCosts::new(ops, &all_locations));
+
+        // Empty schedules leave nothing to optimize
+        if ntotal == 0 {
+            return None;
+        }
+        let nswaps = config
+            .max_swaps
+            .unwrap_or_else(|| default_swap_budget(nplaces, ntimes, nunscheduled));
+
+        let scan: Vec<usize> = match config.scan_order {
+            ScanOrder::Reversed => (0..ntotal).rev().collect(),
+            ScanOrder::Lexicographic | ScanOrder::Shuffled => (0..ntotal).collect(),
+        };
+
+        // Initialize best solution tracking for this single run
+        let phase = config.profile.then(Instant::now);
+        let best_penalty = penalty_fn(schedule);
+        let evaluating = phase.map(|start| start.elapsed());
+        observer.improved(schedule, best_penalty);
+
+        Some(Self {
+            rng,
+            all_locations,
+            nslots,
+            ntotal,
+            nswaps,
+            swap_iter: 0,
+            scan,
+            penalty: best_penalty,
+            best_penalty,
+            best_schedule: schedule.clone(),
+            timeout_checker: config.timeout.map(TimeoutChecker::new),
+            swap_costs,
+            evaluating,
+            acceptor: config
+                .acceptance
+                .zip(energy)
+                .map(|(rule, energy)| Acceptor::new(rule, energy, &best_penalty)),
+            tabu: VecDeque::with_capacity(config.tabu_tenure),
+        })
+    }
+
+    /// Perform one swap iteration: a noise move or a greedy move.
+    fn step<F>(
+        &mut self,
+        schedule: &mut Schedule<A>,
+        penalty_fn: &F,
+        feasible: fn(&P) -> bool,
+        config: &ImproveConfig,
+        observer: &mut Observer<'_, A, P>,
+        moves: &Moves<'_, A, P>,
+    ) -> RunStep<P>
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        let swap_iter = self.swap_iter;
+        if swap_iter >= self.nswaps {
+            return RunStep::Finished;
+        }
+        self.swap_iter += 1;
+        observer.poll();
+
+        // Check for timeout
+        if let Some(ref mut checker) = self.timeout_checker {
+            if checker.should_timeout(swap_iter) {
+                debug!(
+                    "timeout after {swap_iter} swaps, best penalty {:?}",
+                    self.best_penalty
+                );
+                return RunStep::Finished;
+            }
+        }
+
+        if self.acceptor.is_some() {
+            return self.sampled_step(schedule, penalty_fn, feasible, observer, moves);
+        }
+
+        let noise_prob = config.noise_prob;
+        let locations = &self.all_locations;
+
+        // Noise move: random swap that may disimprove (escape local optima)
+        if noise_prob > 0.0 && self.nslots > 0 && self.ntotal > 1 && self.rng.f32() < noise_prob {
+            let i = self.rng.usize(0..self.nslots); // Always pick from scheduled slots
+            let mut j = self.rng.usize(0..(self.ntotal - 1)); // Pick from reduced range
+            if j >= i {
+                j += 1; // Skip over i to avoid self-swap
+            }
+            if !moves.permit(schedule, locations[i], locations[j]) {
+                return RunStep::Moved;
+            }
+
+            let phase = self.evaluating.map(|_| Instant::now());
+            schedule.swap_locations(locations[i], locations[j]);
+            let new_penalty = penalty_fn(schedule);
+            add_elapsed(&mut self.evaluating, phase);
+
+            // Never wander into a state violating hard constraints
+            if !feasible(&new_penalty) {
+                schedule.swap_locations(locations[j], locations[i]);
+                return RunStep::Moved;
+            }
+
+            // Otherwise accept noise moves (even if they disimprove)
+            self.penalty = new_penalty;
+            if let Some(costs) = &mut self.swap_costs {
+                costs.invalidate(i);
+                costs.invalidate(j);
+            }
+
+            // Update best if this noise move happened to improve
+            if new_penalty < self.best_penalty {
+                return self.new_best(schedule, observer);
+            }
+            return RunStep::Moved;
+        }
+
+        // Greedy move: find the best improving swap among all possibilities,
+        // visiting pairs in the configured scan order
+        if config.scan_order == ScanOrder::Shuffled {
+            self.rng.shuffle(&mut self.scan);
+        }
+        let tabu = config.tabu_tenure > 0;
+        let phase = self.evaluating.map(|_| Instant::now());
+        let best = loop {
+            match self.best_swap(schedule, penalty_fn, feasible, config, moves) {
+                // A score from an earlier step is only right for a penalty
+                // summed per time slot, so check it before making the swap
+                Some((i, j, predicted, true)) => {
+                    let (ci, cj) = (self.all_locations[i], self.all_locations[j]);
+                    schedule.swap_locations(ci, cj);
+                    let actual = penalty_fn(schedule);
+                    schedule.swap_locations(cj, ci);
+                    if actual == predicted {
+                        break Some((i, j, actual));
+                    }
+                    // Scan again with the corrected score in place
+                    if let Some(costs) = &mut self.swap_costs {
+                        let key = [i.min(j), i.max(j)];
+                        costs.record(key, self.swap_iter, self.penalty, actual);
+                    }
+                }
+                Some((i, j, penalty, false)) => break Some((i, j, penalty)),
+                None => break None,
+            }
+        };
+        add_elapsed(&mut self.evaluating, phase);
+
+        // Apply the best greedy move if one was found
+        let locations = &self.all_locations;
+        if let Some((i, j, cur_penalty)) = best {
+            schedule.swap_locations(locations[i], locations[j]);
+            trace!(
+                "swap {swap_iter}: penalty {:?} -> {cur_penalty:?}",
+                self.penalty
+            );
+            self.penalty = cur_penalty;
+            if tabu {
+                if self.tabu.len() == config.tabu_tenure {
+                    self.tabu.pop_front();
+                }
+                self.tabu.push_back([i.min(j), i.max(j)]);
+            }
+            if let Some(costs) = &mut self.swap_costs {
+                costs.invalidate(i);
+                costs.invalidate(j);
+            }
+            // Caller-computed changes add up, so start each step from the
+            // truth
+            if moves.penalty_delta.is_some() {
+                let phase = self.evaluating.map(|_| Instant::now());
+                self.penalty = penalty_fn(schedule);
+                add_elapsed(&mut self.evaluating, phase);
+            }
+
+            // Update best if this greedy move improved our overall best
+            if self.penalty < self.best_penalty {
+                return self.new_best(schedule, observer);
+            }
+            RunStep::Moved
+        } else if noise_prob == 0.0 {
+            // Pure greedy search: stop when no improvement found (local optimum reached)
+            debug!(
+                "converged after {swap_iter} swaps with penalty {:?}",
+                self.penalty
+            );
+            RunStep::Finished
+        } else {
+            RunStep::Moved
+        }
+    }
+
+    /// Try one random swap, keeping it if the acceptance rule does.
+    fn sampled_step<F>(
+        &mut self,
+        schedule: &mut Schedule<A>,
+        penalty_fn: &F,
+        feasible: fn(&P) -> bool,
+        observer: &mut Observer<'_, A, P>,
+        moves: &Moves<'_, A, P>,
+    ) -> RunStep<P>
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        // With nothing to swap, no step can ever change the schedule
+        if self.nslots == 0 || self.ntotal < 2 {
+            return RunStep::Finished;
+        }
+        let i = self.rng.usize(0..self.nslots);
+        let mut j = self.rng.usize(0..(self.ntotal - 1));
+        if j >= i {
+            j += 1;
+        }
+        let (ci, cj) = (self.all_locations[i], self.all_locations[j]);
+        let Some(acceptor) = &mut self.acceptor else {
+            unreachable!("sampling steps need an acceptance rule");
+        };
+        if !moves.permit(schedule, ci, cj) {
+            acceptor.advance(&self.penalty);
+            return RunStep::Moved;
+        }
+
+        let phase = self.evaluating.map(|_| Instant::now());
+        schedule.swap_locations(ci, cj);
+        let new_penalty = penalty_fn(schedule);
+        add_elapsed(&mut self.evaluating, phase);
+
+        let keep =
+            feasible(&new_penalty) && acceptor.accepts(&self.penalty, &new_penalty, &mut self.rng);
+        if !keep {
+            schedule.swap_locations(cj, ci);
+            acceptor.advance(&self.penalty);
+            return RunStep::Moved;
+        }
+        acceptor.advance(&new_penalty);
+        self.penalty = new_penalty;
+        if let Some(costs) = &mut self.swap_costs {
+            costs.invalidate(i);
+            costs.invalidate(j);
+        }
+        if new_penalty < self.best_penalty {
+            return self.new_best(schedule, observer);
+        }
+        RunStep::Moved
+    }
+
+    /// Scan every candidate swap for the best greedy move.
+    ///
+    /// Returns the two location indices to swap, the penalty the swap
+    /// gives, and whether that penalty was cached at an earlier step rather
+    /// than evaluated at this one.
+    fn best_swap<F>(
+        &mut self,
+        schedule: &mut Schedule<A>,
+        penalty_fn: &F,
+        feasible: fn(&P) -> bool,
+        config: &ImproveConfig,
+        moves: &Moves<'_, A, P>,
+    ) -> Option<(usize, usize, P, bool)>
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        let nslots = self.nslots;
+        let locations = &self.all_locations;
+        let tabu = config.tabu_tenure > 0;
+        // Worsening tabu steps, like noise moves, never leave the feasible
+        // region once it is reached
+        let stay_feasible = tabu && feasible(&self.penalty);
+        let mut cur_best = None;
+        // Tabu search takes the best allowed swap, even a worsening one
+        let mut cur_penalty = (!tabu).then_some(self.penalty);
+        'scan: for (a, &i) in self.scan.iter().enumerate() {
+            for &j in &self.scan[a + 1..] {
+                // Swapping two unscheduled positions, or two empty
+                // cells, never changes anything
+                if i >= nslots && j >= nslots {
+                    continue;
+                }
+                let (ci, cj) = (locations[i], locations[j]);
+                if schedule.is_empty_cell(ci) && schedule.is_empty_cell(cj) {
+                    continue;
+                }
+                if !moves.permit(schedule, ci, cj) {
+                    continue;
+                }
+
+                let key = [i.min(j), i.max(j)];
+                let cached = self
+                    .swap_costs
+                    .as_ref()
+                    .and_then(|costs| costs.predict(key, self.penalty, self.swap_iter));
+                let (new_penalty, earlier) = cached.unwrap_or_else(|| {
+                    let new_penalty = match &moves.penalty_delta {
+                        Some(d) => (d.add)(self.penalty, (d.delta)(schedule, ci, cj)),
+                        None => {
+                            schedule.swap_locations(ci, cj);
+                            let new_penalty = penalty_fn(schedule);
+                            schedule.swap_locations(cj, ci);
+                            new_penalty
+                        }
+                    };
+                    if let Some(costs) = &mut self.swap_costs {
+                        costs.record(key, self.swap_iter, self.penalty, new_penalty);
+                    }
+                    (new_penalty, false)
+                });
+                if stay_feasible && !feasible(&new_penalty) {
+                    continue;
+                }
+                // A tabu swap is allowed only if it beats the best so far
+                let aspires = new_penalty < self.best_penalty;
+                if tabu && !aspires && self.tabu.contains(&key) {
+                    continue;
+                }
+                if cur_penalty.is_none_or(|p| p > new_penalty) {
+                    cur_best = Some((i, j, earlier));
+                    cur_penalty = Some(new_penalty);
+                }
+                if config.first_improvement && new_penalty < self.penalty {
+                    break 'scan;
+                }
+            }
+        }
+        let (i, j, earlier) = cur_best?;
+        Some((i, j, cur_penalty?, earlier))
+    }
+
+    /// Record the current schedule as this run's best.
+    fn new_best(
+        &mut self,
+        schedule: &Schedule<A>,
+        observer: &mut Observer<'_, A, P>,
+    ) -> RunStep<P> {
+        self.best_penalty = self.penalty;
+        self.best_schedule = schedule.clone();
+        observer.improved(schedule, self.best_penalty);
+        RunStep::Improved(self.best_penalty)
+    }
+}
+
+/// A complete improvement process with optional restarts, advanced one
+/// improvement at a time.
+///
+/// The search works by:
+/// 1. Trying all possible swaps between locations (schedule slots and unscheduled list)
+/// 2. Keeping the swap that gives the best improvement
+/// 3. Repeating until no improvement is found or max swaps reached
+/// 4. Optionally restarting with a random reshuffle to escape local optima
+struct Search<A, P> {
+    /// Per-run parameters, with budgets divided for proportional restarts
+    config: ImproveConfig,
+    nruns: usize,
+    runs_started: usize,
+    /// Number of the run after which the search ends, normally `nruns`
+    runs_end: usize,
+    master_rng: Rng,
+    /// The layout every restart reshuffles, kept unless restarts continue
+    /// from the previous run
+    start: Option<Schedule<A>>,
+    run: Option<RunState<A, P>>,
+    /// Best result across runs, tracked only when there are restarts
+    best: Option<(P, Schedule<A>)>,
+    /// Final penalty of each run so far, in order
+    run_penalties: Vec<P>,
+    profiler: Option<Profiler>,
+    swap_costs: Option<SwapCostOps<P>>,
+    energy: Option<fn(&P) -> f64>,
+    /// The schedule's own constraints, while [`Improver::fill_only`] or
+    /// [`Improver::pin`] has locked more slots
+    saved_constraints: Option<Constraints>,
+    done: bool,
+}
+
+/// Timing state of a profiled [`Search`].
+struct Profiler {
+    /// Start of the current run
+    run_start: Instant,
+    shuffling: Duration,
+    runs: Vec<Profile>,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Self {
+            run_start: Instant::now(),
+            shuffling: Duration::ZERO,
+            runs: Vec::new(),
+        }
+    }
+
+    fn start_run(&mut self) {
+        self.run_start = Instant::now();
+        self.shuffling = Duration::ZERO;
+    }
+
+    fn finish_run(&mut self, evaluating: Duration) {
+        self.runs.push(Profile {
+            evaluating,
+            shuffling: self.shuffling,
+            total: self.run_start.elapsed(),
+        });
+    }
+}
+
+impl<A: Clone, P: Copy + PartialOrd + Debug> Search<A, P> {
+    fn new(
+        schedule: &mut Schedule<A>,
+        config: &ImproveConfig,
+        swap_costs: Option<SwapCostOps<P>>,
+        energy: Option<fn(&P) -> f64>,
+    ) -> Self {
+        schedule.reserve_for(config);
+        let run_config = config.per_run(schedule.slots.dim(), schedule.unscheduled.len());
+        Self::with_run_config(schedule, run_config, swap_costs, energy)
+    }
+
+    /// Set up a search whose per-run parameters are already resolved by
+    /// [`ImproveConfig::per_run`], on a schedule already reserved for them.
+    fn with_run_config(
+        schedule: &mut Schedule<A>,
+        config: ImproveConfig,
+        swap_costs: Option<SwapCostOps<P>>,
+        energy: Option<fn(&P) -> f64>,
+    ) -> Self {
+        let locks_more = config.fill_only || !config.pinned.is_empty();
+        let saved_constraints = locks_more.then(|| {
+            let saved = schedule.constraints.clone();
+            let scheduled = schedule
+                .slots
+                .indexed_iter()
+                .filter(|(_, slot)| config.fill_only && slot.is_some())
+                .map(|(cell, _)| cell);
+            let locked = &mut schedule.constraints.locked;
+            locked.extend(scheduled.chain(config.pinned.iter().copied()));
+            locked.sort_unstable();
+            locked.dedup();
+            saved
+        });
+
+        // Each run draws its own seed from the master generator
+        let master_rng = match config.seed {
+            Some(seed) => Rng::with_seed(seed),
+            None => Rng::new(),
+        };
+
+        // nrestarts <= 1 means a single run without restarts
+        let nruns = config.restarts.unwrap_or(0).max(1);
+        let start = (nruns > 1 && config.restart_policy != RestartPolicy::Continue)
+            .then(|| schedule.clone());
+        let profiler = config.profile.then(Profiler::new);
+        Self {
+            config,
+            nruns,
+            runs_started: 0,
+            runs_end: nruns,
+            master_rng,
+            start,
+            run: None,
+            best: None,
+            run_penalties: Vec::new(),
+            profiler,
+            swap_costs,
+            energy,
+            saved_constraints,
+            done: false,
+        }
+    }
+
+    /// Limit the search to its run number `restart`, which begins exactly
+    /// as it would in the full sequence of runs.
+    fn only_run(mut self, restart: usize) -> Self {
+        for _ in 0..restart {
+            self.master_rng.u64(..);
+        }
+        self.runs_started = restart;
+        self.runs_end = restart + 1;
+        self
+    }
+
+    /// Advance to the next new best schedule of a run, returning the best
+    /// penalty so far, or `None` once the search is over.
+    fn next_improvement<F>(
+        &mut self,
+        schedule: &mut Schedule<A>,
+        penalty_fn: &F,
+        feasible: fn(&P) -> bool,
+        observer: &mut Observer<'_, A, P>,
+        restarts: &mut Restarts<'_, A, P>,
+        moves: &Moves<'_, A, P>,
+    ) -> Option<P>
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        while !self.done {
+            let Some(run) = &mut self.run else {
+                self.start_run(schedule, penalty_fn, observer, restarts, moves);
+                continue;
+            };
+            match run.step(
+                schedule,
+                penalty_fn,
+                feasible,
+                &self.config,
+                observer,
+                moves,
+            ) {
+                RunStep::Improved(p) => {
+                    return Some(match &self.best {
+                        Some((best, _)) if *best < p => *best,
+                        _ => p,
+                    });
+                }
+                RunStep::Moved => (),
+                RunStep::Finished => self.finish_run(schedule, penalty_fn),
+            }
+        }
+        None
+    }
+
+    /// Begin the next run, or wrap up the search if all runs are done.
+    fn start_run<F>(
+        &mut self,
+        schedule: &mut Schedule<A>,
+        penalty_fn: &F,
+        observer: &mut Observer<'_, A, P>,
+        restarts: &mut Restarts<'_, A, P>,
+        moves: &Moves<'_, A, P>,
+    ) where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        if self.runs_started == self.runs_end {
+            self.finish(schedule);
+            return;
+        }
+        let restart = self.runs_started;
+        if let (Some(on_restart), Some(best)) = (&mut restarts.on_restart, self.best_run_penalty())
+        {
+            if restart > 0 && !on_restart(restart, best) {
+                debug!("restart {restart}/{}: stopped by callback", self.nruns - 1);
+                self.finish(schedule);
+                return;
+            }
+        }
+        self.runs_started += 1;
+        if let Some(profiler) = &mut self.profiler {
+            profiler.start_run();
+        }
+        let mut rng = Rng::with_seed(self.master_rng.u64(..));
+        if restart > 0 {
+            // Try additional restarts with reshuffling
+            if let Some((best_penalty, _)) = &self.best {
+                debug!(
+                    "restart {restart}/{}: best penalty so far {best_penalty:?}",
+                    self.nruns - 1
+                );
+            }
+            let shuffle_start = Instant::now();
+            if let Some(start) = &self.start {
+                schedule.clone_from(start);
+            }
+            restarts.shuffle.apply(schedule, &mut rng);
+            if let Some(profiler) = &mut self.profiler {
+                profiler.shuffling = shuffle_start.elapsed();
+            }
+        }
+        if let Some(allowed) = &moves.allowed {
+            let moved = schedule.unschedule_disallowed(allowed);
+            if moved > 0 {
+                debug!("run {restart}: unscheduled {moved} activities from slots they may not use");
+            }
+        }
+        match RunState::start(
+            schedule,
+            penalty_fn,
+            &self.config,
+            self.swap_costs,
+            self.energy,
+            rng,
+            observer,
+        ) {
+            Some(run) => self.run = Some(run),
+            None => self.finish_run(schedule, penalty_fn),
+        }
+    }
 
+    /// Restore the current run's best schedule and weigh it against the
+    /// other runs.
+    fn finish_run<F>(&mut self, schedule: &mut Schedule<A>, penalty_fn: &F)
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
         // Restore the best solution found during this single run
-        *self = best_schedule;
+        let mut evaluating = Duration::ZERO;
+        if let Some(run) = self.run.take() {
+            self.run_penalties.push(run.best_penalty);
+            *schedule = run.best_schedule;
+            evaluating = run.evaluating.unwrap_or_default();
+        }
+        if self.nruns > 1 {
+            self.weigh_run(schedule, penalty_fn);
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.finish_run(evaluating);
+        }
+    }
+
+    /// Keep the restored result of the run just finished if the restart
+    /// policy prefers it to the best so far.
+    fn weigh_run<F>(&mut self, schedule: &Schedule<A>, penalty_fn: &F)
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        let current_penalty = penalty_fn(schedule);
+        let restart = self.runs_started - 1;
+        let accept = match &self.best {
+            None => true,
+            Some((best_penalty, _)) => {
+                debug!("restart {restart} finished with penalty {current_penalty:?}");
+                // Update best if this restart found a better solution
+                match self.config.restart_policy {
+                    RestartPolicy::KeepBest => current_penalty < *best_penalty,
+                    RestartPolicy::AcceptEqual => current_penalty <= *best_penalty,
+                    RestartPolicy::Continue => false,
+                }
+            }
+        };
+        if accept {
+            self.best = Some((current_penalty, schedule.clone()));
+        }
+    }
+
+    /// The best final penalty of the runs so far.
+    fn best_run_penalty(&self) -> Option<P> {
+        self.run_penalties
+            .iter()
+            .copied()
+            .reduce(|best, p| if p < best { p } else { best })
+    }
+
+    /// End the search, restoring the best solution found across all runs.
+    fn finish(&mut self, schedule: &mut Schedule<A>) {
+        self.done = true;
+        if self.config.restart_policy != RestartPolicy::Continue {
+            if let Some((_, best)) = self.best.take() {
+                *schedule = best;
+            }
+        }
+        if let Some(constraints) = self.saved_constraints.take() {
+            schedule.constraints = constraints;
+        }
+    }
+
+    /// Stop early, keeping the best schedule found so far.
+    fn stop<F>(&mut self, schedule: &mut Schedule<A>, penalty_fn: &F)
+    where
+        F: Fn(&Schedule<A>) -> P,
+    {
+        if self.done {
+            return;
+        }
+        if self.run.is_some() {
+            self.finish_run(schedule, penalty_fn);
+        }
+        self.finish(schedule);
+    }
+}
+
+/// Iterator over the improvements of a search, from [`Improver::steps`].
+///
+/// Each item is the best penalty found so far, yielded whenever a run finds
+/// a new best schedule. The search advances only while the iterator is
+/// driven, and [`schedule`](Steps::schedule) shows its current state.
+/// Dropping the iterator early stops the search, leaving the schedule in
+/// the best state found so far.
+pub struct Steps<'a, A, F, P>
+where
+    A: Clone,
+    F: Fn(&Schedule<A>) -> P,
+    P: Copy + PartialOrd + Debug,
+{
+    schedule: &'a mut Schedule<A>,
+    penalty_fn: F,
+    feasible: fn(&P) -> bool,
+    observer: Observer<'a, A, P>,
+    restarts: Restarts<'a, A, P>,
+    moves: Moves<'a, A, P>,
+    search: Search<A, P>,
+}
+
+impl<A, F, P> Steps<'_, A, F, P>
+where
+    A: Clone,
+    F: Fn(&Schedule<A>) -> P,
+    P: Copy + PartialOrd + Debug,
+{
+    /// The schedule as the search has left it so far.
+    ///
+    /// Between steps this is the schedule just reached, which during noisy
+    /// search or a later restart need not be the best one found.
+    pub fn schedule(&self) -> &Schedule<A> {
+        self.schedule
+    }
+
+    /// The final penalty of each finished run, the initial run first and
+    /// then each restart in order.
+    ///
+    /// This shows whether later restarts pay their way. A schedule with no
+    /// cells and no activities has nothing to run, and records nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::Schedule;
+    ///
+    /// let mut schedule = Schedule::new(2, 3, 0..6usize);
+    /// let mut steps = schedule
+    ///     .improve(|s: &Schedule<usize>| s.get_activity_at(0, 0).unwrap().map_or(0, |&a| a))
+    ///     .max_swaps(50)
+    ///     .restarts(4)
+    ///     .with_rng_seed(7)
+    ///     .steps();
+    /// steps.by_ref().for_each(drop);
+    ///
+    /// let penalties = steps.restart_penalties();
+    /// assert_eq!(penalties.len(), 4);
+    /// assert!(penalties.iter().all(|&p| p == 0));
+    /// ```
+    pub fn restart_penalties(&self) -> &[P] {
+        &self.search.run_penalties
+    }
+
+    /// Where each finished run spent its time, in run order, if the
+    /// improver was set to [`profile`](Improver::profile); otherwise empty.
+    pub fn run_profiles(&self) -> &[Profile] {
+        self.search.profiler.as_ref().map_or(&[], |p| &p.runs)
+    }
+}
+
+impl<A, F, P> Iterator for Steps<'_, A, F, P>
+where
+    A: Clone,
+    F: Fn(&Schedule<A>) -> P,
+    P: Copy + PartialOrd + Debug,
+{
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        self.search.next_improvement(
+            self.schedule,
+            &self.penalty_fn,
+            self.feasible,
+            &mut self.observer,
+            &mut self.restarts,
+            &self.moves,
+        )
+    }
+}
+
+impl<A, F, P> Drop for Steps<'_, A, F, P>
+where
+    A: Clone,
+    F: Fn(&Schedule<A>) -> P,
+    P: Copy + PartialOrd + Debug,
+{
+    fn drop(&mut self) {
+        self.search.stop(self.schedule, &self.penalty_fn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capacity_hint_preallocates_overflow() {
+        let activities = (0..100_000).filter(|_| true);
+        let schedule = Schedule::with_capacity_hint(10, 10, 100_000, activities);
+        assert_eq!(schedule.unscheduled.len(), 99_900);
+        assert_eq!(schedule.unscheduled.capacity(), 99_900);
+    }
+
+    #[test]
+    fn exact_iterators_preallocate_overflow() {
+        let schedule = Schedule::new(10, 10, 0..100_000);
+        assert_eq!(schedule.unscheduled.capacity(), 99_900);
     }
 }
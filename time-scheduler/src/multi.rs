@@ -0,0 +1,324 @@
+//! Schedules whose places can hold several activities at once.
+//!
+//! A [`Schedule`] holds at most one activity per place and time. A
+//! [`MultiSchedule`] gives each place a capacity instead, as for a large
+//! hall hosting parallel lightning talks. It is built on an ordinary
+//! schedule of *lanes*: a place with capacity `k` owns `k` consecutive
+//! lanes, each a row of the lane schedule holding one activity per time
+//! slot. The activities at a place and time are those in its lanes at that
+//! time, in lane order.
+//!
+//! Because every lane cell holds one activity, the usual swap search
+//! respects the capacities without knowing about them: no move can put
+//! more activities in a place than it has lanes. Capacity is a hard limit.
+//! A softer one, such as a hall that is best with three of its four
+//! lanes in use, is up to the penalty function, which sees the schedule by
+//! place through [`Places`] and can charge for every activity beyond it.
+//!
+//! Improver settings that name places, such as [`Improver::pin`] or
+//! [`Improver::allowed_slots`], name lanes; [`MultiSchedule::lanes_of`]
+//! translates.
+//!
+//! [`Improver::pin`]: crate::Improver::pin
+//! [`Improver::allowed_slots`]: crate::Improver::allowed_slots
+
+use std::fmt::Debug;
+use std::ops::Range;
+
+use thiserror::Error;
+
+use crate::{BoundsError, Improver, Schedule};
+
+/// Error type for [`MultiSchedule::add_activity_at`].
+#[derive(Debug, Error)]
+pub enum CapacityError<A> {
+    /// The place or time slot is out of bounds.
+    #[error(transparent)]
+    Bounds(#[from] BoundsError),
+
+    /// Every lane of the place is taken at that time. The rejected
+    /// activity is handed back.
+    #[error("place {place} is full at time {time}")]
+    Full {
+        /// Place index
+        place: usize,
+        /// Time slot index
+        time: usize,
+        /// The activity that did not fit
+        activity: A,
+    },
+}
+
+/// A schedule in which each place holds up to its capacity in activities
+/// at each time slot.
+///
+/// See the [module documentation](self) for the data model.
+///
+/// # Examples
+///
+/// ```rust
+/// use time_scheduler::multi::MultiSchedule;
+///
+/// // A hall for three parallel talks and a room for one
+/// let schedule = MultiSchedule::new(&[3, 1], 2, 1..7);
+/// assert_eq!(schedule.get_activities_at(0, 0).unwrap(), [&1, &3, &5]);
+/// assert_eq!(schedule.get_activities_at(1, 1).unwrap(), Vec::<&i32>::new());
+/// assert_eq!(schedule.lanes().get_unscheduled_activities().count(), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiSchedule<A> {
+    /// One row per lane, each place's lanes together
+    lanes: Schedule<A>,
+    /// First lane of each place, then the total number of lanes
+    offsets: Vec<usize>,
+}
+
+impl<A: Clone> MultiSchedule<A> {
+    /// Create a schedule with one capacity per place, laid out as
+    /// [`Schedule::new`] lays out its rows: activities fill the lanes in
+    /// order and the rest are unscheduled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lane schedule is too large to allocate, as
+    /// [`Schedule::new`] does.
+    pub fn new(
+        capacities: &[usize],
+        ntimes: usize,
+        activities: impl ExactSizeIterator<Item = A>,
+    ) -> Self {
+        let offsets: Vec<usize> = std::iter::once(0)
+            .chain(capacities.iter().scan(0usize, |total, &capacity| {
+                *total += capacity;
+                Some(*total)
+            }))
+            .collect();
+        let nlanes = offsets[capacities.len()];
+        Self {
+            lanes: Schedule::new(nlanes, ntimes, activities),
+            offsets,
+        }
+    }
+
+    /// Put `activity` in the first free lane of `place` at `time`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CapacityError::Bounds`] for a place or time slot out of
+    /// bounds, and [`CapacityError::Full`], holding the activity, if the
+    /// place is already at capacity then.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::multi::{CapacityError, MultiSchedule};
+    ///
+    /// let mut schedule = MultiSchedule::new(&[2], 1, std::iter::empty());
+    /// schedule.add_activity_at(0, 0, "a").unwrap();
+    /// schedule.add_activity_at(0, 0, "b").unwrap();
+    /// match schedule.add_activity_at(0, 0, "c") {
+    ///     Err(CapacityError::Full { activity, .. }) => assert_eq!(activity, "c"),
+    ///     other => panic!("expected a full place, got {other:?}"),
+    /// }
+    /// assert_eq!(schedule.get_activities_at(0, 0).unwrap(), [&"a", &"b"]);
+    /// ```
+    pub fn add_activity_at(
+        &mut self,
+        place: usize,
+        time: usize,
+        activity: A,
+    ) -> Result<(), CapacityError<A>> {
+        let lanes = self.lanes_of(place)?;
+        self.places().check_time(time)?;
+        let free = lanes
+            .clone()
+            .find(|&lane| matches!(self.lanes.get_activity_at(lane, time), Ok(None)));
+        match free {
+            Some(lane) => {
+                self.lanes.set_activity_at(lane, time, activity)?;
+                Ok(())
+            }
+            None => Err(CapacityError::Full {
+                place,
+                time,
+                activity,
+            }),
+        }
+    }
+
+    /// Get the activities at a place and time, in lane order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] or [`BoundsError::Time`] for a place
+    /// or time slot out of bounds.
+    pub fn get_activities_at(&self, place: usize, time: usize) -> Result<Vec<&A>, BoundsError> {
+        self.places().get_activities_at(place, time)
+    }
+
+    /// Get how many activities `place` can hold at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] if `place` is out of bounds.
+    pub fn capacity(&self, place: usize) -> Result<usize, BoundsError> {
+        self.places().capacity(place)
+    }
+
+    /// Get the dimensions `(nplaces, ntimes)`, counting places rather than
+    /// lanes.
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.places().dimensions()
+    }
+
+    /// Get the lanes belonging to `place`, as rows of
+    /// [`lanes`](MultiSchedule::lanes).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] if `place` is out of bounds.
+    pub fn lanes_of(&self, place: usize) -> Result<Range<usize>, BoundsError> {
+        self.places().lanes_of(place)
+    }
+
+    /// View the schedule by place, as penalty functions see it.
+    pub fn places(&self) -> Places<'_, A> {
+        Places {
+            lanes: &self.lanes,
+            offsets: &self.offsets,
+        }
+    }
+
+    /// The underlying schedule of lanes, which also holds the unscheduled
+    /// activities.
+    pub fn lanes(&self) -> &Schedule<A> {
+        &self.lanes
+    }
+
+    /// The underlying schedule of lanes, for the [`Schedule`] methods that
+    /// work cell by cell.
+    pub fn lanes_mut(&mut self) -> &mut Schedule<A> {
+        &mut self.lanes
+    }
+
+    /// Create an improver for a penalty function over places.
+    ///
+    /// The search swaps lane cells, so it never exceeds a capacity; the
+    /// penalty function sees each candidate through [`Places`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use time_scheduler::multi::{MultiSchedule, Places};
+    ///
+    /// // Topics (the activity values) clash when they share a time slot,
+    /// // so the hall runs one talk of each topic at a time
+    /// let clashes = |places: Places<'_, u32>| {
+    ///     let (nplaces, ntimes) = places.dimensions();
+    ///     let mut clashes = 0;
+    ///     for t in 0..ntimes {
+    ///         let mut topics: Vec<u32> = (0..nplaces)
+    ///             .flat_map(|p| places.get_activities_at(p, t).unwrap())
+    ///             .copied()
+    ///             .collect();
+    ///         let all = topics.len();
+    ///         topics.sort_unstable();
+    ///         topics.dedup();
+    ///         clashes += all - topics.len();
+    ///     }
+    ///     clashes
+    /// };
+    /// let mut schedule = MultiSchedule::new(&[2], 2, [1, 2, 1, 2].into_iter());
+    /// assert_eq!(clashes(schedule.places()), 2);
+    /// schedule.improve(clashes).run();
+    /// assert_eq!(clashes(schedule.places()), 0);
+    /// ```
+    pub fn improve<F, P>(&mut self, penalty_fn: F) -> Improver<'_, A, impl Fn(&Schedule<A>) -> P, P>
+    where
+        F: Fn(Places<'_, A>) -> P,
+        P: Copy + PartialOrd + Debug,
+    {
+        let offsets = self.offsets.clone();
+        self.lanes.improve(move |lanes: &Schedule<A>| {
+            penalty_fn(Places {
+                lanes,
+                offsets: &offsets,
+            })
+        })
+    }
+}
+
+/// A read-only view of a [`MultiSchedule`] by place.
+///
+/// This is what [`MultiSchedule::improve`] hands the penalty function for
+/// each candidate layout.
+#[derive(Debug)]
+pub struct Places<'s, A> {
+    lanes: &'s Schedule<A>,
+    offsets: &'s [usize],
+}
+
+impl<A> Clone for Places<'_, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for Places<'_, A> {}
+
+impl<'s, A: Clone> Places<'s, A> {
+    /// Get the activities at a place and time, in lane order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] or [`BoundsError::Time`] for a place
+    /// or time slot out of bounds.
+    pub fn get_activities_at(&self, place: usize, time: usize) -> Result<Vec<&'s A>, BoundsError> {
+        let lanes = self.lanes_of(place)?;
+        self.check_time(time)?;
+        let lanes_schedule = self.lanes;
+        Ok(lanes
+            .filter_map(|lane| lanes_schedule.get_activity_at(lane, time).ok().flatten())
+            .collect())
+    }
+
+    /// Get how many activities `place` can hold at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] if `place` is out of bounds.
+    pub fn capacity(&self, place: usize) -> Result<usize, BoundsError> {
+        self.lanes_of(place).map(|lanes| lanes.len())
+    }
+
+    /// Get the dimensions `(nplaces, ntimes)`, counting places rather than
+    /// lanes.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.offsets.len() - 1, self.lanes.dimensions().1)
+    }
+
+    /// Get the lanes belonging to `place`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BoundsError::Place`] if `place` is out of bounds.
+    pub fn lanes_of(&self, place: usize) -> Result<Range<usize>, BoundsError> {
+        if place >= self.offsets.len() - 1 {
+            return Err(BoundsError::Place(place));
+        }
+        Ok(self.offsets[place]..self.offsets[place + 1])
+    }
+
+    /// The underlying schedule of lanes, which also holds the unscheduled
+    /// activities.
+    pub fn lanes(&self) -> &'s Schedule<A> {
+        self.lanes
+    }
+
+    fn check_time(&self, time: usize) -> Result<(), BoundsError> {
+        if time >= self.lanes.dimensions().1 {
+            return Err(BoundsError::Time(time));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::cell::Cell;
+
+use common::lateness;
+use time_scheduler::{Acceptance, Infeasible, ScanOrder, Schedule};
+
+mod common;
+
+#[test]
+fn first_improvement_needs_far_fewer_evaluations() {
+    // Every swap-local optimum of lateness is global, so both scans
+    // should reach the same value
+    let initial = Schedule::new(6, 8, 0..60);
+
+    let solve = |first_improvement: bool| {
+        let evaluations = Cell::new(0usize);
+        let penalty = |s: &Schedule<usize>| {
+            evaluations.set(evaluations.get() + 1);
+            lateness(s)
+        };
+        let mut schedule = initial.clone();
+        let improver = schedule
+            .improve(penalty)
+            .scan_order(ScanOrder::Shuffled)
+            .with_rng_seed(17);
+        if first_improvement {
+            improver.first_improvement().run();
+        } else {
+            improver.run();
+        }
+        (lateness(&schedule), evaluations.get())
+    };
+
+    let (best_penalty, best_evaluations) = solve(false);
+    let (first_penalty, first_evaluations) = solve(true);
+    assert_eq!(first_penalty, best_penalty);
+    assert!(
+        first_evaluations * 4 < best_evaluations,
+        "first-improvement used {first_evaluations} evaluations, best-improvement {best_evaluations}"
+    );
+}
+
+const RULES: [Acceptance; 3] = [
+    Acceptance::Annealing {
+        initial_temperature: 10.0,
+        cooling: 0.995,
+    },
+    Acceptance::GreatDeluge { rain: 0.05 },
+    Acceptance::Late { history: 20 },
+];
+
+fn energy(penalty: &usize) -> f64 {
+    *penalty as f64
+}
+
+/// How many slots differ between two schedules of one shape.
+fn distance(a: &Schedule<usize>, b: &Schedule<usize>) -> usize {
+    a.slots()
+        .iter()
+        .zip(b.slots().iter())
+        .filter(|(x, y)| x != y)
+        .count()
+}
+
+#[test]
+fn sampling_rules_improve_reproducibly() {
+    let initial = Schedule::new(3, 4, 0..12);
+    for rule in RULES {
+        let solve = |seed: u64| {
+            let mut schedule = initial.clone();
+            schedule
+                .improve(lateness)
+                .acceptance(rule, energy)
+                .max_swaps(2000)
+                .with_rng_seed(seed)
+                .run();
+            schedule
+        };
+        let solved = solve(1);
+        assert!(lateness(&solved) < lateness(&initial), "{rule:?}");
+        assert_eq!(solve(1), solved, "{rule:?}");
+    }
+}
+
+#[test]
+fn sampling_tries_one_swap_per_step() {
+    for rule in RULES {
+        let evaluations = Cell::new(0usize);
+        let penalty = |s: &Schedule<usize>| {
+            evaluations.set(evaluations.get() + 1);
+            lateness(s)
+        };
+        let mut schedule = Schedule::new(3, 4, 0..12);
+        schedule
+            .improve(penalty)
+            .acceptance(rule, energy)
+            .max_swaps(50)
+            .with_rng_seed(4)
+            .run();
+        assert_eq!(evaluations.get(), 1 + 50, "{rule:?}");
+    }
+}
+
+#[test]
+fn only_hot_annealing_leaves_the_optimum() {
+    // In one place every swap changes lateness, and descending order is
+    // the only optimum: a search that keeps no worse swap never tries a
+    // schedule more than one swap from it
+    let optimum = Schedule::new(1, 6, (0..6).rev());
+    let farthest = |rule: Acceptance| {
+        let farthest = Cell::new(0);
+        let penalty = |s: &Schedule<usize>| {
+            farthest.set(farthest.get().max(distance(s, &optimum)));
+            lateness(s)
+        };
+        let mut schedule = optimum.clone();
+        schedule
+            .improve(penalty)
+            .acceptance(rule, energy)
+            .max_swaps(200)
+            .with_rng_seed(2)
+            .run();
+        assert_eq!(schedule, optimum, "{rule:?}");
+        farthest.get()
+    };
+
+    let hot = Acceptance::Annealing {
+        initial_temperature: 1000.0,
+        cooling: 1.0,
+    };
+    assert!(farthest(hot) > 2);
+    assert_eq!(farthest(Acceptance::GreatDeluge { rain: 0.0 }), 2);
+    assert_eq!(farthest(Acceptance::Late { history: 1 }), 2);
+}
+
+#[test]
+fn sampling_never_keeps_an_infeasible_swap() {
+    // Any swap at all breaks a hard constraint, however hot the anneal
+    let start = Schedule::new(1, 4, 0..4);
+    let farthest = Cell::new(0);
+    let penalty = |s: &Schedule<usize>| {
+        farthest.set(farthest.get().max(distance(s, &start)));
+        if s == &start {
+            Ok(0)
+        } else {
+            Err(Infeasible(1))
+        }
+    };
+    let hot = Acceptance::Annealing {
+        initial_temperature: 1000.0,
+        cooling: 1.0,
+    };
+    let mut schedule = start.clone();
+    schedule
+        .improve_feasible(penalty)
+        .acceptance(hot, |p| match p {
+            Ok(p) => *p as f64,
+            Err(Infeasible(n)) => 1000.0 + *n as f64,
+        })
+        .max_swaps(100)
+        .with_rng_seed(6)
+        .run();
+    assert_eq!(schedule, start);
+    assert_eq!(farthest.get(), 2);
+}
+
+#[test]
+#[should_panic(expected = "cooling factor")]
+fn annealing_must_cool() {
+    let mut schedule = Schedule::new(1, 2, 0..2usize);
+    let rule = Acceptance::Annealing {
+        initial_temperature: 1.0,
+        cooling: 1.5,
+    };
+    schedule.improve(lateness).acceptance(rule, energy);
+}
+
+#[test]
+fn late_acceptance_history_grows_with_the_swaps_tried() {
+    // A history longer than the budget is never read past the start, so
+    // it must cost no more than one exactly as long as the budget
+    let solve = |history: usize| {
+        let mut schedule = Schedule::new(3, 4, 0..12);
+        schedule
+            .improve(lateness)
+            .acceptance(Acceptance::Late { history }, energy)
+            .max_swaps(50)
+            .with_rng_seed(3)
+            .run();
+        schedule
+    };
+    assert_eq!(solve(usize::MAX), solve(50));
+}
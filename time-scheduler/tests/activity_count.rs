@@ -0,0 +1,63 @@
+use common::lateness;
+use time_scheduler::{Cell, Schedule};
+
+mod common;
+
+#[test]
+fn moves_keep_the_activity_count() {
+    let mut schedule = Schedule::new(3, 4, 0..15usize);
+    let mut rng = fastrand::Rng::with_seed(7);
+    for _ in 0..200 {
+        let cells: Vec<Cell> = schedule.all_cells().collect();
+        let from = cells[rng.usize(..cells.len())];
+        let to = cells[rng.usize(..cells.len())];
+        schedule.swap(from, to).unwrap();
+        // Moves onto occupied cells or out of empty ones are refused
+        let _ = schedule.move_activity(from, to);
+        assert_eq!(schedule.total_activities(), 15);
+    }
+
+    schedule.shuffle(&mut rng);
+    schedule.improve(lateness).with_noise().max_swaps(100).run();
+    assert_eq!(schedule.total_activities(), 15);
+}
+
+#[test]
+fn editing_activities_changes_the_count() {
+    // 0 to 3 fill the 2x2 grid, 4 and 5 are left over
+    let mut schedule = Schedule::new(2, 2, 0..6usize);
+    assert_eq!(schedule.total_activities(), 6);
+
+    // Replacing an activity hands the old one back to the caller
+    assert_eq!(schedule.set_activity_at(0, 0, 10), Ok(Some(0)));
+    assert_eq!(schedule.total_activities(), 6);
+    assert_eq!(schedule.take_activity_at(0, 0), Ok(Some(10)));
+    assert_eq!(schedule.total_activities(), 5);
+    assert_eq!(schedule.set_activity_at(0, 0, 10), Ok(None));
+    assert_eq!(schedule.total_activities(), 6);
+
+    // Unscheduling moves 3 without losing it
+    schedule.unschedule_at(1, 1).unwrap();
+    assert_eq!(schedule.total_activities(), 6);
+
+    // Only 4 is left unscheduled
+    schedule.retain_unscheduled(|&a| a % 2 == 0);
+    assert_eq!(schedule.total_activities(), 4);
+}
+
+#[test]
+fn every_way_in_counts_the_activities() {
+    let built = Schedule::new(2, 3, 0..8usize);
+    let json = serde_json::to_string(&built).unwrap();
+    let read: Schedule<usize> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.total_activities(), 8);
+    assert_eq!(read, built);
+
+    let rows = Schedule::try_from(vec![vec![Some(1), None], vec![None, Some(2)]]).unwrap();
+    assert_eq!(rows.total_activities(), 2);
+
+    // A view can add activities as well as drop them
+    let mut schedule = rows;
+    schedule.with_slots_mut(|mut slots| slots[(0, 1)] = Some(3));
+    assert_eq!(schedule.total_activities(), 3);
+}
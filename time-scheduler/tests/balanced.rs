@@ -0,0 +1,65 @@
+use fastrand::Rng;
+use time_scheduler::{RestartShuffle, Schedule};
+
+fn topic(a: &usize) -> usize {
+    a % 5
+}
+
+/// Same-topic pairs sharing a time slot.
+fn clashes(s: &Schedule<usize>) -> usize {
+    s.slots()
+        .columns()
+        .into_iter()
+        .map(|column| {
+            let topics: Vec<usize> = column.iter().flatten().map(topic).collect();
+            (0..topics.len())
+                .map(|i| topics[i + 1..].iter().filter(|&&b| b == topics[i]).count())
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+#[test]
+fn balanced_shuffle_clusters_topics_less_than_uniform() {
+    // 26 activities for 24 cells, in five topics of uneven size
+    let start = Schedule::new(4, 6, (0..23).chain([30, 35, 40]));
+    let (mut uniform, mut balanced) = (0, 0);
+    for seed in 0..200 {
+        let mut schedule = start.clone();
+        schedule.shuffle(&mut Rng::with_seed(seed));
+        uniform += clashes(&schedule);
+
+        let mut schedule = start.clone();
+        schedule.shuffle_balanced(&mut Rng::with_seed(seed), topic);
+        assert_eq!(schedule.unscheduled_len(), 2);
+        assert_eq!(schedule.empty_slots_count(), 0);
+        balanced += clashes(&schedule);
+    }
+    assert!(
+        balanced * 2 < uniform,
+        "balanced {balanced}, uniform {uniform}"
+    );
+}
+
+#[test]
+fn balanced_restarts_keep_every_activity() {
+    let mut schedule = Schedule::new(4, 6, 0..26usize);
+    schedule
+        .improve(clashes)
+        .with_noise()
+        .max_swaps(200)
+        .restarts(4)
+        .restart_shuffle(RestartShuffle::balanced(topic))
+        .with_rng_seed(3)
+        .run();
+
+    let mut all: Vec<usize> = schedule
+        .slots()
+        .iter()
+        .flatten()
+        .chain(schedule.get_unscheduled_activities())
+        .copied()
+        .collect();
+    all.sort();
+    assert_eq!(all, (0..26).collect::<Vec<_>>());
+}
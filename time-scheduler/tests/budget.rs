@@ -0,0 +1,122 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use common::lateness;
+use time_scheduler::{default_swap_budget, Schedule};
+
+mod common;
+
+#[test]
+fn default_budget_with_timeout_terminates_promptly() {
+    // Noisy search never converges, so only the timeout can stop this run
+    let penalty = |s: &Schedule<usize>| {
+        s.slots()
+            .indexed_iter()
+            .filter_map(|((p, t), a)| a.map(|a| (a * t) ^ p))
+            .sum::<usize>()
+    };
+    let mut schedule = Schedule::new(10, 10, 0..150);
+
+    let start = Instant::now();
+    schedule
+        .improve(penalty)
+        .with_noise()
+        .timeout(Duration::from_millis(100))
+        .with_rng_seed(1)
+        .run();
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn unbounded_budget_respects_the_timeout() {
+    let penalty = |s: &Schedule<usize>| {
+        s.slots()
+            .indexed_iter()
+            .filter_map(|((p, t), a)| a.map(|a| (a * t) ^ p))
+            .sum::<usize>()
+    };
+
+    for proportional in [false, true] {
+        let mut schedule = Schedule::new(6, 6, 0..40);
+        let start = Instant::now();
+        let improver = schedule
+            .improve(penalty)
+            .with_noise()
+            .max_swaps(usize::MAX)
+            .timeout(Duration::from_millis(150))
+            .with_rng_seed(2);
+        if proportional {
+            improver.restarts_proportional(3).run();
+        } else {
+            improver.run();
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
+
+/// Penalty evaluations of a run in which every step is a noise move, and
+/// so costs exactly one evaluation.
+fn noisy_evaluations(max_swaps: Option<usize>, restarts: usize, proportional: bool) -> usize {
+    let count = Cell::new(0usize);
+    let penalty = |s: &Schedule<usize>| {
+        count.set(count.get() + 1);
+        lateness(s)
+    };
+    let mut schedule = Schedule::new(3, 3, 0..7);
+    let mut improver = schedule.improve(penalty).noise_prob(1.0).with_rng_seed(9);
+    if let Some(n) = max_swaps {
+        improver = improver.max_swaps(n);
+    }
+    if proportional {
+        improver.restarts_proportional(restarts).run();
+    } else {
+        improver.restarts(restarts).run();
+    }
+    count.get()
+}
+
+#[test]
+fn proportional_restarts_divide_the_swap_budget() {
+    assert_eq!(
+        noisy_evaluations(Some(100), 4, true),
+        noisy_evaluations(Some(25), 4, false)
+    );
+
+    // Without a budget the default one is divided too
+    let default_total = default_swap_budget(3, 3, 0);
+    assert_eq!(
+        noisy_evaluations(None, 3, true),
+        noisy_evaluations(Some(default_total / 3), 3, false)
+    );
+
+    // Rounding down never leaves a run without swaps
+    let single = noisy_evaluations(Some(1), 5, false);
+    assert_eq!(noisy_evaluations(Some(2), 5, true), single);
+    assert!(single > noisy_evaluations(Some(0), 5, false));
+}
+
+#[test]
+fn zero_timeout_leaves_the_schedule_unchanged() {
+    let penalty = lateness;
+    let start = Schedule::new(3, 3, 0..12);
+
+    for noise in [false, true] {
+        let mut schedule = start.clone();
+        let improver = schedule
+            .improve(penalty)
+            .max_swaps(1000)
+            .timeout(Duration::ZERO)
+            .with_rng_seed(3);
+        if noise {
+            improver.with_noise().run();
+        } else {
+            improver.run();
+        }
+        assert_eq!(schedule, start);
+    }
+
+    // Without the timeout the same search does change it
+    let mut schedule = start.clone();
+    schedule.improve(penalty).max_swaps(1000).run();
+    assert!(penalty(&schedule) < penalty(&start));
+}
@@ -0,0 +1,69 @@
+use time_scheduler::multi::{CapacityError, MultiSchedule, Places};
+use time_scheduler::BoundsError;
+
+#[test]
+fn full_places_reject_more_activities() {
+    // A hall for two and a room for one, over two time slots; the initial
+    // layout fills the hall's lanes and leaves the room empty
+    let mut schedule = MultiSchedule::new(&[2, 1], 2, 0..4);
+    assert_eq!(schedule.get_activities_at(0, 1).unwrap(), [&1, &3]);
+
+    let rejected = schedule.add_activity_at(0, 1, 9);
+    assert!(matches!(
+        rejected,
+        Err(CapacityError::Full {
+            place: 0,
+            time: 1,
+            activity: 9
+        })
+    ));
+    assert_eq!(schedule.get_activities_at(0, 1).unwrap(), [&1, &3]);
+
+    schedule.add_activity_at(1, 1, 9).unwrap();
+    assert_eq!(schedule.get_activities_at(1, 1).unwrap(), [&9]);
+    assert!(matches!(
+        schedule.add_activity_at(1, 1, 10),
+        Err(CapacityError::Full { activity: 10, .. })
+    ));
+
+    assert!(matches!(
+        schedule.add_activity_at(2, 0, 11),
+        Err(CapacityError::Bounds(BoundsError::Place(2)))
+    ));
+    assert_eq!(schedule.get_activities_at(0, 2), Err(BoundsError::Time(2)));
+    assert_eq!(schedule.capacity(1), Ok(1));
+    assert_eq!(schedule.lanes_of(1), Ok(2..3));
+    assert_eq!(schedule.dimensions(), (2, 2));
+}
+
+#[test]
+fn search_keeps_within_capacity() {
+    // A hall for three and a room for one. The hall is best with only two
+    // talks at a time, the room costs a little and leaving out costs most
+    let penalty = |places: Places<'_, usize>| {
+        let mut penalty = 0;
+        for t in 0..2 {
+            let hall = places.get_activities_at(0, t).unwrap().len();
+            penalty += 5 * hall.saturating_sub(2);
+            penalty += 3 * places.get_activities_at(1, t).unwrap().len();
+        }
+        penalty + 20 * places.lanes().get_unscheduled_activities().count()
+    };
+    // The initial layout packs the hall full at both times
+    let mut schedule = MultiSchedule::new(&[3, 1], 2, 0..7);
+    assert_eq!(penalty(schedule.places()), 10 + 3);
+
+    schedule
+        .improve(penalty)
+        .with_noise()
+        .max_swaps(200)
+        .with_rng_seed(1)
+        .run();
+    // Seven talks in eight lane cells: both room slots, and one time with
+    // the hall over its comfortable two
+    assert_eq!(penalty(schedule.places()), 5 + 6);
+    for t in 0..2 {
+        assert!(schedule.get_activities_at(0, t).unwrap().len() <= 3);
+        assert_eq!(schedule.get_activities_at(1, t).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,13 @@
+//! Fixtures shared by the integration tests.
+
+use time_scheduler::Schedule;
+
+/// Larger activities belong in earlier time slots: each scheduled
+/// activity costs its value times its time slot. Every swap-local
+/// optimum of this penalty is global.
+pub fn lateness(s: &Schedule<usize>) -> usize {
+    s.slots()
+        .indexed_iter()
+        .filter_map(|((_, t), a)| a.map(|a| a * t))
+        .sum()
+}
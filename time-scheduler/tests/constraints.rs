@@ -0,0 +1,78 @@
+use common::lateness;
+use time_scheduler::{Constraints, InstanceError, Schedule, SchedulingInstance};
+
+mod common;
+
+#[test]
+fn constraints_round_trip_through_instance_json() {
+    let json = r#"{
+        "id": "rooms",
+        "nplaces": 3,
+        "ntimes": 4,
+        "activities": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
+        "constraints": {"locked": [[0, 3]], "forbidden": [[2, 0], [2, 1]]}
+    }"#;
+    let instance: SchedulingInstance<usize> = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        instance.constraints,
+        Constraints {
+            locked: vec![(0, 3)],
+            forbidden: vec![(2, 0), (2, 1)],
+        }
+    );
+    instance.validate().unwrap();
+
+    let text = serde_json::to_string(&instance).unwrap();
+    let back: SchedulingInstance<usize> = serde_json::from_str(&text).unwrap();
+    assert_eq!(back.constraints, instance.constraints);
+
+    // No constraints at all leaves the JSON as it was
+    let plain: SchedulingInstance<usize> =
+        serde_json::from_str(r#"{"id": "x", "nplaces": 1, "ntimes": 1, "activities": []}"#)
+            .unwrap();
+    assert!(plain.constraints.is_empty());
+    assert!(!serde_json::to_string(&plain)
+        .unwrap()
+        .contains("constraints"));
+}
+
+#[test]
+fn out_of_grid_constraints_are_rejected() {
+    let json = r#"{"id": "x", "nplaces": 2, "ntimes": 2, "activities": [],
+        "constraints": {"forbidden": [[0, 2]]}}"#;
+    let instance: SchedulingInstance<usize> = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        instance.validate(),
+        Err(InstanceError::ConstraintOutOfBounds { place: 0, time: 2 })
+    );
+
+    let schedule = r#"{"slots": [[1, 2]], "unscheduled": [],
+        "constraints": {"locked": [[1, 0]]}}"#;
+    assert!(serde_json::from_str::<Schedule<usize>>(schedule).is_err());
+}
+
+#[test]
+fn improve_respects_constraints() {
+    // Without constraints, 10 would move to time 0; lock it late instead,
+    // and close both early slots of place 2
+    let constraints = Constraints {
+        locked: vec![(0, 3)],
+        forbidden: vec![(2, 0), (2, 1)],
+    };
+    for seed in 0..5 {
+        let mut schedule = Schedule::new(3, 4, [1, 2, 3, 10, 5, 6, 7, 8, 9, 4, 11, 12].into_iter())
+            .with_constraints(constraints.clone());
+        schedule
+            .improve(lateness)
+            .with_noise()
+            .restarts(3)
+            .with_rng_seed(seed)
+            .run();
+
+        assert_eq!(schedule.get_activity_at(0, 3).unwrap(), Some(&10));
+        assert_eq!(schedule.get_activity_at(2, 0).unwrap(), None);
+        assert_eq!(schedule.get_activity_at(2, 1).unwrap(), None);
+        assert_eq!(schedule.empty_slots_count(), 0);
+        assert_eq!(schedule.constraints(), &constraints);
+    }
+}
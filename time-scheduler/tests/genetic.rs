@@ -0,0 +1,123 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::lateness;
+use time_scheduler::{Constraints, Schedule};
+
+mod common;
+
+fn activities(s: &Schedule<usize>) -> Vec<usize> {
+    let mut all: Vec<usize> = s
+        .slots()
+        .iter()
+        .flatten()
+        .chain(s.get_unscheduled_activities())
+        .copied()
+        .collect();
+    all.sort_unstable();
+    all
+}
+
+fn evolve(start: &Schedule<usize>, seed: u64) -> Schedule<usize> {
+    let mut schedule = start.clone();
+    schedule
+        .evolve(lateness)
+        .population(12)
+        .generations(30)
+        .islands(3)
+        .migration_interval(4)
+        .migrants(2)
+        .with_rng_seed(seed)
+        .run();
+    schedule
+}
+
+#[test]
+fn every_island_keeps_the_activities_and_constraints() {
+    let constraints = Constraints {
+        locked: vec![(0, 3)],
+        forbidden: vec![(2, 0)],
+    };
+    let start = Schedule::new(3, 4, 0..16).with_constraints(constraints);
+    let locked = start.get_activity_at(0, 3).unwrap().copied();
+    let expected = activities(&start);
+
+    // Every individual bred on every island, before and after each
+    // migration, passes through the penalty function
+    let evaluations = AtomicUsize::new(0);
+    let penalty = |s: &Schedule<usize>| {
+        evaluations.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(activities(s), expected);
+        assert_eq!(s.get_activity_at(0, 3).unwrap().copied(), locked);
+        assert_eq!(s.get_activity_at(2, 0).unwrap(), None);
+        lateness(s)
+    };
+    let mut schedule = start.clone();
+    schedule
+        .evolve(penalty)
+        .population(10)
+        .generations(12)
+        .islands(4)
+        .migration_interval(3)
+        .migrants(3)
+        .with_rng_seed(1)
+        .run();
+
+    // The start, then every island's initial population and generations,
+    // each of which keeps its best and breeds the rest
+    assert_eq!(
+        evaluations.load(Ordering::Relaxed),
+        1 + 4 * (10 - 1) + 4 * 12 * (10 - 1)
+    );
+    assert_eq!(activities(&schedule), expected);
+    assert!(lateness(&schedule) < lateness(&start));
+}
+
+#[test]
+fn seeded_searches_are_reproducible() {
+    let start = Schedule::new(4, 5, 0..24);
+    for seed in 0..3 {
+        assert_eq!(evolve(&start, seed), evolve(&start, seed), "seed {seed}");
+    }
+}
+
+#[test]
+fn an_optimal_start_is_kept() {
+    // Largest first is the one best order, so nothing can replace it
+    let start = Schedule::new(1, 4, [3, 2, 1, 0].into_iter());
+    assert_eq!(lateness(&start), 2 + 2);
+    for seed in 0..3 {
+        assert_eq!(evolve(&start, seed), start, "seed {seed}");
+    }
+}
+
+#[test]
+#[should_panic(expected = "2 migrants would replace")]
+fn migrants_must_leave_room_on_the_island() {
+    let mut schedule = Schedule::new(2, 2, 0..4);
+    schedule
+        .evolve(lateness)
+        .population(2)
+        .migrants(2)
+        .islands(2)
+        .run();
+}
+
+#[cfg(not(feature = "parallel"))]
+#[test]
+fn single_threaded_penalties_need_not_be_shareable() {
+    use std::cell::Cell;
+
+    let calls = Cell::new(0);
+    let penalty = |s: &Schedule<usize>| {
+        calls.set(calls.get() + 1);
+        lateness(s)
+    };
+    let mut schedule = Schedule::new(2, 3, 0..6);
+    schedule
+        .evolve(penalty)
+        .population(4)
+        .generations(2)
+        .with_rng_seed(2)
+        .run();
+    assert_eq!(calls.get(), 1 + (4 - 1) + 2 * (4 - 1));
+}
@@ -0,0 +1,48 @@
+use time_scheduler::Schedule;
+
+/// Odd activities placed at time 0; the primary objective.
+fn odd_early(schedule: &Schedule<usize>) -> i64 {
+    (0..2)
+        .filter(|&p| {
+            schedule
+                .get_activity_at(p, 0)
+                .unwrap()
+                .is_some_and(|a| a % 2 == 1)
+        })
+        .count() as i64
+}
+
+/// Negated sum of the activities at time 0; the secondary objective.
+fn early_sum(schedule: &Schedule<usize>) -> i64 {
+    -((0..2)
+        .filter_map(|p| schedule.get_activity_at(p, 0).unwrap().copied())
+        .sum::<usize>() as i64)
+}
+
+#[test]
+fn secondary_gain_never_buys_primary_loss() {
+    // A weighted sum of the two would rather put 3 and 2 at time 0
+    // (1 - 5 = -4) than 0 and 2 (0 - 2 = -2)
+    for seed in 0..10 {
+        let mut schedule = Schedule::new(2, 2, 0..4usize);
+        let mut reports = Vec::new();
+        schedule
+            .improve_lexicographic([&odd_early, &early_sum])
+            .with_noise()
+            .restarts(3)
+            .with_rng_seed(seed)
+            .on_progress(|progress| reports.push(progress.penalty))
+            .run();
+        for pair in reports.windows(2) {
+            assert!(
+                pair[1] < pair[0],
+                "seed {seed}: {:?} after {:?}",
+                pair[1],
+                pair[0]
+            );
+        }
+
+        assert_eq!(odd_early(&schedule), 0, "seed {seed}");
+        assert_eq!(early_sum(&schedule), -2, "seed {seed}");
+    }
+}
@@ -0,0 +1,69 @@
+use std::sync::Mutex;
+
+use common::lateness;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use time_scheduler::Schedule;
+
+mod common;
+
+struct CaptureLogger {
+    messages: Mutex<Vec<(Level, String)>>,
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let message = record.args().to_string();
+        self.messages
+            .lock()
+            .unwrap()
+            .push((record.level(), message));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger {
+    messages: Mutex::new(Vec::new()),
+};
+
+#[test]
+fn restarts_and_convergence_are_logged() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let penalty = lateness;
+    let mut schedule = Schedule::new(2, 3, 0..6);
+    schedule.improve(penalty).restarts(3).with_rng_seed(5).run();
+
+    let messages = LOGGER.messages.lock().unwrap();
+    let restarts = messages
+        .iter()
+        .filter(|(level, m)| *level == Level::Debug && m.starts_with("restart 1/2"))
+        .count();
+    assert_eq!(restarts, 1);
+    assert!(messages.iter().any(|(_, m)| m.starts_with("converged")));
+    assert!(messages
+        .iter()
+        .any(|(level, m)| *level == Level::Warn && m.starts_with("no swap budget")));
+    assert!(messages
+        .iter()
+        .any(|(level, m)| *level == Level::Trace && m.contains("->")));
+    drop(messages);
+
+    // Setting up an endless noisy search warns, and dropping its steps
+    // stops it before it starts
+    let steps = schedule
+        .improve(penalty)
+        .with_noise()
+        .max_swaps(usize::MAX)
+        .steps();
+    drop(steps);
+    let messages = LOGGER.messages.lock().unwrap();
+    assert!(messages
+        .iter()
+        .any(|(level, m)| *level == Level::Warn && m.contains("will not stop")));
+}
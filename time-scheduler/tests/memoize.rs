@@ -0,0 +1,46 @@
+use std::cell::Cell;
+
+use common::lateness;
+use time_scheduler::Schedule;
+
+mod common;
+
+#[test]
+fn memoization_saves_evaluations_without_changing_result() {
+    let start = Schedule::new(2, 3, 0..7);
+
+    let solve = |capacity: Option<usize>| {
+        let calls = Cell::new(0usize);
+        let penalty = |s: &Schedule<usize>| {
+            calls.set(calls.get() + 1);
+            lateness(s)
+        };
+        let mut schedule = start.clone();
+        let improver = schedule.improve(penalty).restarts(20).with_rng_seed(6);
+        match capacity {
+            Some(capacity) => improver.memoize(capacity).run(),
+            None => improver.run(),
+        }
+        (schedule, calls.get())
+    };
+
+    let (plain, plain_calls) = solve(None);
+    let (memoized, memoized_calls) = solve(Some(10_000));
+    assert_eq!(memoized, plain);
+    // Every greedy step re-evaluates the state it came from, and restarts
+    // of a small instance meet the same states again
+    assert!(
+        memoized_calls * 4 < plain_calls * 3,
+        "{memoized_calls} calls with memoization, {plain_calls} without"
+    );
+
+    // A tiny cache still gives the same answer
+    let (small, small_calls) = solve(Some(4));
+    assert_eq!(small, plain);
+    assert!(small_calls <= plain_calls);
+
+    // Capacity 0 turns memoization off
+    let (off, off_calls) = solve(Some(0));
+    assert_eq!(off, plain);
+    assert_eq!(off_calls, plain_calls);
+}
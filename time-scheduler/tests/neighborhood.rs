@@ -0,0 +1,221 @@
+use std::cell::Cell;
+
+use common::lateness;
+use time_scheduler::{Infeasible, Schedule};
+
+mod common;
+
+#[test]
+fn empty_pairs_are_not_evaluated() {
+    // 20 slots, half of them empty: of the 190 slot pairs, the 45 pairs
+    // of two empty slots can't change the schedule
+    let mut schedule = Schedule::new(4, 5, 0..10);
+    let evaluations = Cell::new(0usize);
+    schedule
+        .improve(|_: &Schedule<usize>| {
+            evaluations.set(evaluations.get() + 1);
+            0
+        })
+        .run();
+    // One evaluation of the initial schedule, then one failed scan
+    assert_eq!(evaluations.get(), 1 + 190 - 45);
+}
+
+#[test]
+fn fill_only_never_moves_scheduled_activities() {
+    let slots = vec![
+        vec![Some(0), Some(1), None, Some(2)],
+        vec![Some(3), None, Some(4), Some(5)],
+        vec![Some(6), Some(7), None, None],
+    ];
+    let start: Schedule<usize> = serde_json::from_value(serde_json::json!({
+        "slots": slots,
+        "unscheduled": [10, 11, 12, 13, 14, 15],
+    }))
+    .unwrap();
+    // Late slots cost a little, leaving an activity out costs a lot
+    let penalty = |s: &Schedule<usize>| {
+        let missed: usize = s.get_unscheduled_activities().map(|a| 100 * a).sum();
+        lateness(s) + missed
+    };
+
+    for seed in 0..5 {
+        let mut schedule = start.clone();
+        schedule
+            .improve(penalty)
+            .fill_only()
+            .noise_prob(0.3)
+            .restarts(3)
+            .max_swaps(200)
+            .with_rng_seed(seed)
+            .run();
+
+        for ((place, time), slot) in schedule.slots().indexed_iter() {
+            match slots[place][time] {
+                Some(placed) => assert_eq!(*slot, Some(placed), "seed {seed}"),
+                None => assert!(slot.is_some_and(|a| a >= 10), "seed {seed}"),
+            }
+        }
+        let mut waiting: Vec<usize> = schedule.get_unscheduled_activities().copied().collect();
+        waiting.sort();
+        assert_eq!(waiting, [10, 11], "seed {seed}");
+        assert_eq!(schedule.constraints(), start.constraints());
+    }
+}
+
+#[test]
+fn tabu_search_keeps_moving_past_a_local_optimum() {
+    // Descending order is the only local optimum of lateness, but two
+    // worsening swaps away lies an even better schedule
+    let penalty = |s: &Schedule<usize>| match s.row(0).unwrap()[..] {
+        [Some(2), Some(3), Some(0), Some(1)] => 0,
+        _ => lateness(s),
+    };
+    let optimum = Schedule::new(1, 4, [3, 2, 1, 0].into_iter());
+    let stuck = penalty(&optimum);
+
+    let mut greedy = optimum.clone();
+    greedy.improve(penalty).run();
+    assert_eq!(greedy, optimum);
+
+    let mut tabu = optimum.clone();
+    tabu.improve(penalty).tabu(2).max_swaps(10).run();
+    assert!(penalty(&tabu) < stuck);
+    assert_eq!(tabu.row(0).unwrap(), [Some(2), Some(3), Some(0), Some(1)]);
+}
+
+#[test]
+fn tabu_search_stays_feasible() {
+    // Every swap away from [0, 1, 2] breaks a hard constraint, so tabu
+    // search has nowhere to go
+    let evaluations = Cell::new(0usize);
+    let penalty = |s: &Schedule<usize>| {
+        evaluations.set(evaluations.get() + 1);
+        match s.row(0).unwrap()[..] {
+            [Some(0), Some(1), Some(2)] => Ok(5),
+            _ => Err(Infeasible(1)),
+        }
+    };
+    let mut schedule = Schedule::new(1, 3, 0..3);
+    schedule
+        .improve_feasible(penalty)
+        .tabu(2)
+        .max_swaps(10)
+        .run();
+    assert_eq!(penalty(&schedule), Ok(5));
+    // The initial evaluation and one scan of the three swaps, plus the
+    // check just above
+    assert_eq!(evaluations.get(), 1 + 3 + 1);
+}
+
+#[test]
+fn or_moves_leave_slots_empty_when_that_is_best() {
+    // Odd activities cost more scheduled than left out, even ones less
+    let penalty = |s: &Schedule<usize>| {
+        let placed: usize = s
+            .slots()
+            .iter()
+            .flatten()
+            .map(|&a| if a % 2 == 1 { 20 } else { 1 })
+            .sum();
+        let missed = 5 * s.get_unscheduled_activities().count();
+        placed + missed
+    };
+    // 12 slots for 10 activities: 5 even scheduled at 1 each, 5 odd
+    // left out at 5 each
+    let best = 5 + 5 * 5;
+
+    let mut swaps = Schedule::new(3, 4, 0..10);
+    swaps.improve(penalty).run();
+    assert!(penalty(&swaps) > best);
+
+    let mut or_moves = Schedule::new(3, 4, 0..10);
+    or_moves.improve(penalty).or_moves().run();
+    assert_eq!(penalty(&or_moves), best);
+    assert_eq!(or_moves.empty_slots_count(), 7);
+    assert!(or_moves.slots().iter().flatten().all(|a| a % 2 == 0));
+}
+
+#[test]
+fn allowed_slots_are_never_left() {
+    // Each speaker is away for one time slot; the cheapest layout ignores that
+    let allowed = |&a: &usize, _: usize, t: usize| (a + t) % 3 != 0;
+    let cost = |a: usize, t: usize| (a * 7 + t * 3) % 5;
+    let penalty = |s: &Schedule<usize>| {
+        for (p, t, a) in s.iter_scheduled() {
+            assert!(allowed(a, p, t), "evaluated {a} at ({p}, {t})");
+        }
+        let placed: usize = s.iter_scheduled().map(|(_, t, &a)| cost(a, t)).sum();
+        placed + 100 * s.get_unscheduled_activities().count()
+    };
+
+    // Every layout of the six activities over the 2x3 grid, as times
+    fn layouts(rest: &mut Vec<usize>, slots: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        if rest.is_empty() {
+            out.push(slots.clone());
+        }
+        for i in 0..rest.len() {
+            let a = rest.remove(i);
+            slots.push(a);
+            layouts(rest, slots, out);
+            slots.pop();
+            rest.insert(i, a);
+        }
+    }
+    let mut all = Vec::new();
+    layouts(&mut (0..6).collect(), &mut Vec::new(), &mut all);
+    // Slot k is place k / 3 at time k % 3
+    let total = |layout: &[usize]| -> usize { (0..6).map(|k| cost(layout[k], k % 3)).sum() };
+    let fits = |layout: &[usize]| (0..6).all(|k| allowed(&layout[k], k / 3, k % 3));
+    let unconstrained = all.iter().map(|l| total(l)).min().unwrap();
+    let best = all
+        .iter()
+        .filter(|l| fits(l))
+        .map(|l| total(l))
+        .min()
+        .unwrap();
+    assert!(unconstrained < best);
+
+    // The initial layout breaks the rule, and so do restart shuffles
+    let mut schedule = Schedule::new(2, 3, 0..6);
+    schedule
+        .improve(penalty)
+        .allowed_slots(allowed)
+        .with_noise()
+        .max_swaps(200)
+        .restarts(10)
+        .with_rng_seed(5)
+        .run();
+    assert_eq!(penalty(&schedule), best);
+    assert_eq!(schedule.iter_scheduled().count(), 6);
+}
+
+#[test]
+fn pinned_slots_survive_noise_and_restarts() {
+    // Every activity would rather be elsewhere than where it starts
+    let penalty = |s: &Schedule<usize>| {
+        let misplaced: usize = s
+            .iter_scheduled()
+            .map(|(p, t, &a)| usize::from(a != (p * 4 + t + 3) % 12))
+            .sum();
+        misplaced + s.get_unscheduled_activities().count()
+    };
+    let mut schedule = Schedule::new(3, 4, 0..10);
+    let keynote = schedule.get_activity_at(1, 2).unwrap().copied();
+    schedule
+        .improve(penalty)
+        .pin(1, 2)
+        .pin(2, 3)
+        .with_noise()
+        .max_swaps(500)
+        .restarts(5)
+        .with_rng_seed(11)
+        .run();
+    assert_eq!(schedule.get_activity_at(1, 2).unwrap().copied(), keynote);
+    // An empty pinned slot stays empty
+    assert_eq!(schedule.get_activity_at(2, 3).unwrap(), None);
+    assert!(schedule.constraints().is_empty());
+    // Only the pinned 6, the 9 it keeps out and the 2 with nowhere to go
+    // are misplaced
+    assert_eq!(penalty(&schedule), 3);
+}
@@ -0,0 +1,24 @@
+use time_scheduler::Schedule;
+
+#[test]
+fn noise_skips_schedules_with_nothing_to_swap() {
+    // One cell holding the only activity: a noise move has no partner
+    let mut schedule = Schedule::new(1, 1, 0..1usize);
+    schedule
+        .improve(|s: &Schedule<usize>| s.empty_slots_count())
+        .noise_prob(1.0)
+        .max_swaps(10)
+        .with_rng_seed(1)
+        .run();
+    assert_eq!(schedule.get_activity_at(0, 0).unwrap(), Some(&0));
+
+    // No cells at all, only unscheduled activities
+    let mut schedule = Schedule::new(0, 1, 0..2usize);
+    schedule
+        .improve(|s: &Schedule<usize>| s.get_unscheduled_activities().count())
+        .noise_prob(1.0)
+        .max_swaps(10)
+        .with_rng_seed(1)
+        .run();
+    assert_eq!(schedule.get_unscheduled_activities().count(), 2);
+}
@@ -0,0 +1,147 @@
+use common::lateness;
+use time_scheduler::{Cell, RestartPolicy, Schedule};
+
+mod common;
+
+fn solve_parallel(start: &Schedule<usize>, seed: u64, policy: RestartPolicy) -> Schedule<usize> {
+    let mut schedule = start.clone();
+    schedule
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(100)
+        .restarts(4)
+        .restart_policy(policy)
+        .with_rng_seed(seed)
+        .run_parallel();
+    schedule
+}
+
+#[test]
+fn seeded_parallel_runs_are_reproducible() {
+    let start = Schedule::new(3, 4, 0..16);
+    for seed in 0..2 {
+        for policy in [RestartPolicy::KeepBest, RestartPolicy::AcceptEqual] {
+            let first = solve_parallel(&start, seed, policy);
+            let second = solve_parallel(&start, seed, policy);
+            assert_eq!(first, second, "seed {seed}, {policy:?}");
+        }
+    }
+}
+
+#[test]
+fn parallel_runs_match_sequential_ones() {
+    let start = Schedule::new(3, 4, 0..16);
+    for seed in 0..3 {
+        for policy in [RestartPolicy::KeepBest, RestartPolicy::AcceptEqual] {
+            let mut sequential = start.clone();
+            sequential
+                .improve(lateness)
+                .with_noise()
+                .max_swaps(100)
+                .restarts(4)
+                .restart_policy(policy)
+                .with_rng_seed(seed)
+                .run();
+            let parallel = solve_parallel(&start, seed, policy);
+            assert_eq!(parallel, sequential, "seed {seed}, {policy:?}");
+        }
+    }
+}
+
+#[test]
+fn parallel_restarts_find_what_sequential_ones_do() {
+    // Lateness is least with the four smallest activities scheduled first
+    // and the four largest left out
+    let start = Schedule::new(4, 5, 0..24);
+    let mut sequential = start.clone();
+    sequential
+        .improve(lateness)
+        .max_swaps(500)
+        .restarts(4)
+        .with_rng_seed(1)
+        .run();
+    for seed in 0..3 {
+        let mut parallel = start.clone();
+        parallel
+            .improve(lateness)
+            .max_swaps(500)
+            .restarts(4)
+            .with_rng_seed(seed)
+            .run_parallel();
+        assert_eq!(lateness(&parallel), lateness(&sequential), "seed {seed}");
+    }
+}
+
+#[test]
+fn continuing_restarts_run_in_order() {
+    let start = Schedule::new(4, 5, 0..24);
+    let solve = |parallel: bool| {
+        let mut schedule = start.clone();
+        let improver = schedule
+            .improve(lateness)
+            .with_noise()
+            .max_swaps(200)
+            .restarts(3)
+            .restart_policy(RestartPolicy::Continue)
+            .with_rng_seed(5);
+        if parallel {
+            improver.run_parallel();
+        } else {
+            improver.run();
+        }
+        schedule
+    };
+    assert_eq!(solve(true), solve(false));
+}
+
+#[test]
+fn memos_deltas_and_allowed_slots_run_in_parallel() {
+    let start = Schedule::new(3, 4, 0..16usize);
+    // A slot's share of the lateness, given what it holds
+    let cost = |cell: Cell, holding: Option<&usize>| match cell {
+        Cell::Slot(_, t) => holding.map_or(0, |a| a * t),
+        Cell::Unscheduled(_) => 0,
+    };
+    let delta = |s: &Schedule<usize>, a: Cell, b: Cell| {
+        let (in_a, in_b) = (s.activity_in(a).unwrap(), s.activity_in(b).unwrap());
+        // Unsigned, so add before taking away
+        (cost(a, in_b) + cost(b, in_a)) as i64 - (cost(a, in_a) + cost(b, in_b)) as i64
+    };
+    let solve = |parallel: bool| {
+        let mut schedule = start.clone();
+        let improver = schedule
+            .improve(|s: &Schedule<usize>| lateness(s) as i64)
+            .penalty_delta(delta)
+            .allowed_slots(|&a, _, t| a != 0 || t > 0)
+            .memoize(1000)
+            .max_swaps(100)
+            .restarts(4)
+            .with_rng_seed(6);
+        if parallel {
+            improver.run_parallel();
+        } else {
+            improver.run();
+        }
+        schedule
+    };
+    let parallel = solve(true);
+    assert_eq!(parallel, solve(false));
+    assert!(!parallel.column(0).unwrap().contains(&Some(0)));
+}
+
+#[test]
+#[should_panic(expected = "penalty gave up")]
+fn penalty_panics_reach_the_caller() {
+    let mut schedule = Schedule::new(2, 2, 0..6usize);
+    schedule
+        .improve(|s: &Schedule<usize>| -> usize {
+            if s.get_activity_at(0, 0).unwrap() == Some(&5) {
+                panic!("penalty gave up");
+            }
+            lateness(s)
+        })
+        .max_swaps(50)
+        .restarts(4)
+        .with_rng_seed(2)
+        .run_parallel();
+}
@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+use common::lateness;
+use time_scheduler::Schedule;
+
+mod common;
+
+#[test]
+fn profiled_phases_fit_in_the_runtime() {
+    let mut schedule = Schedule::new(3, 4, 0..14usize);
+    let start = Instant::now();
+    let mut steps = schedule
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(40)
+        .restarts(3)
+        .with_rng_seed(1)
+        .profile()
+        .steps();
+    steps.by_ref().for_each(drop);
+    let wall = start.elapsed();
+    let profiles = steps.run_profiles().to_vec();
+    drop(steps);
+
+    assert_eq!(profiles.len(), 3);
+    for (run, profile) in profiles.iter().enumerate() {
+        assert!(profile.evaluating > Duration::ZERO, "{profile:?}");
+        assert!(profile.evaluating + profile.shuffling <= profile.total);
+        let parts = profile.evaluating + profile.swapping() + profile.shuffling;
+        assert_eq!(parts, profile.total);
+        // Only restarts reshuffle
+        assert_eq!(run > 0, profile.shuffling > Duration::ZERO, "{profile:?}");
+    }
+    let total: Duration = profiles.iter().map(|p| p.total).sum();
+    assert!(total <= wall, "{total:?} > {wall:?}");
+}
+
+#[test]
+fn unprofiled_runs_record_nothing() {
+    let mut schedule = Schedule::new(2, 2, 0..4usize);
+    let mut steps = schedule.improve(lateness).restarts(2).steps();
+    steps.by_ref().for_each(drop);
+    assert!(steps.run_profiles().is_empty());
+}
@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+use common::lateness;
+use time_scheduler::Schedule;
+
+mod common;
+
+#[test]
+fn soft_deadline_fires_before_hard_stop() {
+    let soft = Duration::from_millis(20);
+    let hard = Duration::from_millis(200);
+    let mut reports = Vec::new();
+    let mut schedule = Schedule::new(5, 5, 0..25);
+
+    let start = Instant::now();
+    schedule
+        .improve(lateness)
+        .with_noise()
+        .with_rng_seed(3)
+        .timeout(hard)
+        .soft_deadline(soft)
+        .on_progress(|progress| {
+            reports.push((
+                progress.elapsed,
+                progress.soft_deadline_passed,
+                progress.penalty,
+            ))
+        })
+        .run();
+    let total = start.elapsed();
+
+    let first_ready = reports
+        .iter()
+        .position(|&(_, passed, _)| passed)
+        .expect("soft deadline was never announced");
+    let (ready_at, _, ready_penalty) = reports[first_ready];
+    assert!(ready_at >= soft && ready_at < total);
+    assert!(total >= hard);
+
+    // The announcement repeats the best schedule so far, and nothing
+    // before the deadline claims to be ready
+    assert!(reports[..first_ready]
+        .iter()
+        .all(|&(t, passed, _)| !passed && t < soft));
+    assert_eq!(ready_penalty, reports[first_ready - 1].2);
+    assert!(lateness(&schedule) <= ready_penalty);
+}
@@ -0,0 +1,30 @@
+use common::lateness;
+use time_scheduler::Schedule;
+
+mod common;
+
+/// Solve a fresh schedule with every random choice drawn from `seed`.
+fn solve(seed: u64) -> Schedule<usize> {
+    let mut schedule = Schedule::new(3, 4, 0..16);
+    schedule
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(200)
+        .restarts(3)
+        .with_rng_seed(seed)
+        .run();
+    schedule
+}
+
+#[test]
+fn same_seed_gives_same_schedule() {
+    for seed in 0..5 {
+        assert_eq!(solve(seed), solve(seed), "seed {seed}");
+    }
+}
+
+#[test]
+fn different_seeds_can_differ() {
+    let first = solve(0);
+    assert!((1..10).any(|seed| solve(seed) != first));
+}
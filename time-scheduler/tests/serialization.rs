@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use time_scheduler::{Constraints, Schedule, SchedulingInstance};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Talk {
+    title: String,
+    priority: u32,
+}
+
+fn talks(n: u32) -> impl ExactSizeIterator<Item = Talk> {
+    (0..n).map(|priority| Talk {
+        title: format!("talk {priority}"),
+        priority,
+    })
+}
+
+#[test]
+fn solved_schedule_round_trips_through_json() {
+    let constraints = Constraints {
+        locked: vec![(0, 0)],
+        forbidden: vec![(2, 3)],
+    };
+    let mut schedule = Schedule::new(3, 4, talks(13))
+        .with_buffer_times(1)
+        .with_constraints(constraints);
+    // High priorities want early slots, and missing out costs most
+    schedule
+        .improve(|s: &Schedule<Talk>| {
+            let lateness: u32 = s
+                .iter_scheduled()
+                .map(|(_, time, talk)| talk.priority * time as u32)
+                .sum();
+            let missed: u32 = s
+                .get_unscheduled_activities()
+                .map(|t| 100 * t.priority)
+                .sum();
+            lateness + missed
+        })
+        .with_rng_seed(3)
+        .run();
+    assert!(schedule.iter_slots().any(|(_, _, slot)| slot.is_none()));
+    assert!(schedule.get_unscheduled_activities().count() > 0);
+
+    let json = serde_json::to_string(&schedule).unwrap();
+    let back: Schedule<Talk> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, schedule);
+    assert_eq!(back.dimensions(), (3, 4));
+    assert_eq!(back.buffer_times(), 1);
+    assert_eq!(back.constraints(), schedule.constraints());
+}
+
+#[test]
+fn empty_unscheduled_entries_are_not_written() {
+    let mut schedule = Schedule::new(1, 2, talks(2));
+    // Scheduling nothing is best, so both talks leave the grid
+    schedule
+        .improve(|s: &Schedule<Talk>| s.iter_scheduled().count())
+        .or_moves()
+        .run();
+    assert_eq!(schedule.empty_slots_count(), 2);
+
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["slots"], serde_json::json!([[null, null]]));
+    assert_eq!(json["unscheduled"].as_array().unwrap().len(), 2);
+
+    let back: Schedule<Talk> = serde_json::from_value(json).unwrap();
+    assert_eq!(back.slots(), schedule.slots());
+    assert!(back
+        .get_unscheduled_activities()
+        .eq(schedule.get_unscheduled_activities()));
+    assert_eq!(back.unscheduled_len(), 2);
+}
+
+#[test]
+fn labels_survive_instances_and_schedules() {
+    let legacy = r#"{"id": "talks", "nplaces": 2, "ntimes": 2, "activities": [1, 2]}"#;
+    let legacy: SchedulingInstance<u32> = serde_json::from_str(legacy).unwrap();
+    assert_eq!((legacy.place_labels, legacy.time_labels), (None, None));
+
+    let named = r#"{"id": "talks", "nplaces": 2, "ntimes": 2, "activities": [1, 2],
+        "place_labels": ["Hall", "Room A"], "time_labels": ["9:00", "10:00"]}"#;
+    let named: SchedulingInstance<u32> = serde_json::from_str(named).unwrap();
+    named.validate().unwrap();
+    let (place_labels, time_labels) = (named.place_labels.clone(), named.time_labels.clone());
+    let schedule = Schedule::from(named);
+    assert_eq!(schedule.place_labels(), place_labels.as_deref());
+    assert_eq!(
+        schedule.render_grid(|a| a.to_string()),
+        "        9:00  10:00\n\
+         Hall    1     2\n\
+         Room A  .     .\n"
+    );
+
+    // Labels travel with the schedule and back to its instance
+    let json = serde_json::to_string(&schedule).unwrap();
+    assert!(json.contains(r#""time_labels":["9:00","10:00"]"#));
+    let back: Schedule<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, schedule);
+    let instance = back.into_instance("talks".to_string());
+    assert_eq!(instance.place_labels, place_labels);
+    assert_eq!(instance.time_labels, time_labels);
+
+    // Unlabeled schedules write no labels at all
+    let plain = serde_json::to_string(&Schedule::new(1, 1, [1u32].into_iter())).unwrap();
+    assert!(!plain.contains("labels"));
+}
@@ -0,0 +1,115 @@
+use common::lateness;
+use time_scheduler::Schedule;
+
+mod common;
+
+#[test]
+fn exhausted_steps_match_run() {
+    let start = Schedule::new(4, 5, 0..24);
+
+    let mut by_run = start.clone();
+    by_run
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(300)
+        .restarts(3)
+        .with_rng_seed(9)
+        .run();
+
+    let mut by_steps = start.clone();
+    let mut steps = by_steps
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(300)
+        .restarts(3)
+        .with_rng_seed(9)
+        .steps();
+    let mut last = None;
+    for best in steps.by_ref() {
+        assert!(last.is_none_or(|last| best <= last));
+        last = Some(best);
+    }
+    drop(steps);
+
+    assert_eq!(by_steps, by_run);
+    assert_eq!(last, Some(lateness(&by_run)));
+}
+
+#[test]
+fn stopping_early_keeps_best_so_far() {
+    let mut schedule = Schedule::new(4, 5, 0..24);
+    let mut steps = schedule.improve(lateness).with_rng_seed(2).steps();
+    let best = steps.nth(2).unwrap();
+    drop(steps);
+    assert_eq!(lateness(&schedule), best);
+}
+
+#[test]
+fn one_penalty_per_restart() {
+    let mut schedule = Schedule::new(4, 5, 0..24);
+    let mut steps = schedule
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(300)
+        .restarts(5)
+        .with_rng_seed(4)
+        .steps();
+    steps.by_ref().for_each(drop);
+    let penalties = steps.restart_penalties().to_vec();
+    drop(steps);
+
+    assert_eq!(penalties.len(), 5);
+    assert_eq!(penalties.iter().min(), Some(&lateness(&schedule)));
+
+    // A single run without restarts still records its result
+    let mut single = Schedule::new(4, 5, 0..24);
+    let mut steps = single.improve(lateness).max_swaps(300).steps();
+    steps.by_ref().for_each(drop);
+    assert_eq!(steps.restart_penalties().len(), 1);
+}
+
+#[test]
+fn restart_hook_fires_once_per_restart() {
+    let mut calls = Vec::new();
+    let mut schedule = Schedule::new(4, 5, 0..24);
+    let mut steps = schedule
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(100)
+        .restarts(6)
+        .with_rng_seed(8)
+        .on_restart(|restart, best| {
+            calls.push((restart, best));
+            true
+        })
+        .steps();
+    steps.by_ref().for_each(drop);
+    let penalties = steps.restart_penalties().to_vec();
+    drop(steps);
+
+    let restarts: Vec<usize> = calls.iter().map(|&(restart, _)| restart).collect();
+    assert_eq!(restarts, [1, 2, 3, 4, 5]);
+    for (i, &(_, best)) in calls.iter().enumerate() {
+        assert_eq!(Some(&best), penalties[..=i].iter().min());
+    }
+    assert!(calls.windows(2).all(|pair| pair[1].1 <= pair[0].1));
+}
+
+#[test]
+fn restart_hook_can_stop_the_search() {
+    let mut schedule = Schedule::new(4, 5, 0..24);
+    let mut steps = schedule
+        .improve(lateness)
+        .with_noise()
+        .max_swaps(100)
+        .restarts(6)
+        .with_rng_seed(8)
+        .on_restart(|restart, _| restart < 3)
+        .steps();
+    steps.by_ref().for_each(drop);
+    let penalties = steps.restart_penalties().to_vec();
+    drop(steps);
+
+    assert_eq!(penalties.len(), 3);
+    assert_eq!(Some(&lateness(&schedule)), penalties.iter().min());
+}
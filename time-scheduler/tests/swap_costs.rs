@@ -0,0 +1,309 @@
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::ops::{Add, Sub};
+
+use common::lateness;
+use ndarray::Array3;
+use time_scheduler::{Cell as Location, ScanOrder, Schedule};
+
+mod common;
+
+/// A separable penalty: a random cost for each activity in each slot, and
+/// another for leaving it unscheduled.
+struct Costs {
+    slot: Array3<i64>,
+    unscheduled: Vec<i64>,
+}
+
+impl Costs {
+    fn random(nplaces: usize, ntimes: usize, nactivities: usize, seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        Costs {
+            slot: Array3::from_shape_fn((nplaces, ntimes, nactivities), |_| rng.i64(-20..100)),
+            unscheduled: (0..nactivities).map(|_| rng.i64(50..200)).collect(),
+        }
+    }
+
+    fn penalty(&self, schedule: &Schedule<usize>) -> i64 {
+        let placed: i64 = schedule
+            .slots()
+            .indexed_iter()
+            .filter_map(|((p, t), a)| a.map(|a| self.slot[[p, t, a]]))
+            .sum();
+        let dropped: i64 = schedule
+            .get_unscheduled_activities()
+            .map(|&a| self.unscheduled[a])
+            .sum();
+        placed + dropped
+    }
+
+    /// What `activity` costs where it sits, or an empty cell costs nothing.
+    fn cost_at(&self, location: Location, activity: Option<&usize>) -> i64 {
+        match (location, activity) {
+            (_, None) => 0,
+            (Location::Slot(p, t), Some(&a)) => self.slot[[p, t, a]],
+            (Location::Unscheduled(_), Some(&a)) => self.unscheduled[a],
+        }
+    }
+
+    fn delta(&self, schedule: &Schedule<usize>, a: Location, b: Location) -> i64 {
+        let in_a = schedule.activity_in(a).unwrap();
+        let in_b = schedule.activity_in(b).unwrap();
+        self.cost_at(a, in_b) + self.cost_at(b, in_a)
+            - self.cost_at(a, in_a)
+            - self.cost_at(b, in_b)
+    }
+}
+
+/// Settings a search can be run with, to compare pairs that differ in one.
+#[derive(Clone, Copy)]
+struct Options {
+    cached: bool,
+    noise: bool,
+    order: ScanOrder,
+    first: bool,
+    delta: bool,
+}
+
+const PLAIN: Options = Options {
+    cached: false,
+    noise: false,
+    order: ScanOrder::Lexicographic,
+    first: false,
+    delta: false,
+};
+
+/// What a seeded search did: each new best penalty with the schedule that
+/// reached it, the schedule it ended on, and how often it called the
+/// penalty function.
+#[derive(Debug, PartialEq)]
+struct Outcome<P> {
+    trail: Vec<(P, Schedule<usize>)>,
+    schedule: Schedule<usize>,
+    calls: usize,
+}
+
+/// Search from `start` with `options`, scoring swaps with `delta` when
+/// the options ask for it.
+fn search<P, D>(
+    start: &Schedule<usize>,
+    penalty: impl Fn(&Schedule<usize>) -> P,
+    delta: D,
+    options: Options,
+    seed: u64,
+) -> Outcome<P>
+where
+    P: Copy + PartialOrd + Debug + Add<Output = P> + Sub<Output = P>,
+    D: Fn(&Schedule<usize>, Location, Location) -> P + Send + Sync,
+{
+    let calls = Cell::new(0usize);
+    let counted = |s: &Schedule<usize>| {
+        calls.set(calls.get() + 1);
+        penalty(s)
+    };
+    let mut schedule = start.clone();
+    let mut improver = schedule
+        .improve(counted)
+        .max_swaps(300)
+        .restarts(3)
+        .scan_order(options.order)
+        .with_rng_seed(seed);
+    if options.noise {
+        improver = improver.noise_prob(0.2);
+    }
+    if options.first {
+        improver = improver.first_improvement();
+    }
+    if options.cached {
+        improver = improver.cache_swap_costs();
+    }
+    if options.delta {
+        improver = improver.penalty_delta(delta);
+    }
+    let mut steps = improver.steps();
+    let mut trail = Vec::new();
+    while let Some(best) = steps.next() {
+        trail.push((best, steps.schedule().clone()));
+    }
+    drop(steps);
+    Outcome {
+        trail,
+        schedule,
+        calls: calls.get(),
+    }
+}
+
+/// For penalties without a delta of their own.
+fn no_delta<P>(_: &Schedule<usize>, _: Location, _: Location) -> P {
+    unreachable!("the delta was never asked for")
+}
+
+const ORDERS: [ScanOrder; 3] = [
+    ScanOrder::Lexicographic,
+    ScanOrder::Reversed,
+    ScanOrder::Shuffled,
+];
+
+#[test]
+fn cached_scan_makes_the_same_moves() {
+    let (nplaces, ntimes, nactivities) = (3, 4, 14);
+    for seed in 0..3 {
+        let costs = Costs::random(nplaces, ntimes, nactivities, seed);
+        let start = Schedule::new(nplaces, ntimes, 0..nactivities);
+        let penalty = |s: &Schedule<usize>| costs.penalty(s);
+        for noise in [false, true] {
+            for order in ORDERS {
+                for first in [false, true] {
+                    let options = Options {
+                        noise,
+                        order,
+                        first,
+                        ..PLAIN
+                    };
+                    let cached = Options {
+                        cached: true,
+                        ..options
+                    };
+                    let plain = search(&start, penalty, no_delta, options, seed);
+                    let cached = search(&start, penalty, no_delta, cached, seed);
+                    let case = format!("seed {seed}, noise {noise}, {order:?}, first {first}");
+                    assert_eq!(cached.trail, plain.trail, "{case}");
+                    assert_eq!(cached.schedule, plain.schedule, "{case}");
+                    assert!(
+                        cached.calls < plain.calls,
+                        "{case}: {} calls cached, {} without",
+                        cached.calls,
+                        plain.calls
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn penalty_delta_makes_the_same_moves() {
+    let (nplaces, ntimes, nactivities) = (3, 4, 14);
+    for seed in 0..3 {
+        let costs = Costs::random(nplaces, ntimes, nactivities, seed);
+        let start = Schedule::new(nplaces, ntimes, 0..nactivities);
+        let penalty = |s: &Schedule<usize>| costs.penalty(s);
+        let delta = |s: &Schedule<usize>, a, b| costs.delta(s, a, b);
+        for noise in [false, true] {
+            for cached in [false, true] {
+                let options = Options {
+                    noise,
+                    cached,
+                    ..PLAIN
+                };
+                let local = Options {
+                    delta: true,
+                    ..options
+                };
+                let plain = search(&start, penalty, delta, options, seed);
+                let local = search(&start, penalty, delta, local, seed);
+                let case = format!("seed {seed}, noise {noise}, cached {cached}");
+                assert_eq!(local.trail, plain.trail, "{case}");
+                assert_eq!(local.schedule, plain.schedule, "{case}");
+                assert!(
+                    local.calls < plain.calls,
+                    "{case}: {} calls with deltas, {} without",
+                    local.calls,
+                    plain.calls
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn unsigned_penalties_make_the_same_moves() {
+    // Every improving swap lowers an unsigned penalty, which the cache must
+    // never hold as a negative change
+    let start = Schedule::new(3, 4, 0..16usize);
+    for seed in 0..3 {
+        let options = Options {
+            noise: true,
+            ..PLAIN
+        };
+        let cached = Options {
+            cached: true,
+            ..options
+        };
+        assert_eq!(
+            search(&start, lateness, no_delta, cached, seed).trail,
+            search(&start, lateness, no_delta, options, seed).trail,
+            "seed {seed}"
+        );
+    }
+}
+
+/// Odd activities in one time slot clash, which no single cell's contents
+/// can tell.
+fn clashes(s: &Schedule<usize>) -> usize {
+    let clashes: usize = (0..s.dimensions().1)
+        .map(|t| {
+            let column = s.column(t).unwrap();
+            let odd = column.iter().flatten().filter(|&&a| a % 2 == 1).count();
+            odd * odd * 10
+        })
+        .sum();
+    lateness(s) + clashes
+}
+
+#[test]
+fn clashes_within_a_time_slot_make_the_same_moves() {
+    let start = Schedule::new(3, 4, 0..14usize);
+    for seed in 0..3 {
+        for order in [ScanOrder::Lexicographic, ScanOrder::Shuffled] {
+            for noise in [false, true] {
+                let options = Options {
+                    order,
+                    noise,
+                    ..PLAIN
+                };
+                let cached = Options {
+                    cached: true,
+                    ..options
+                };
+                let plain = search(&start, clashes, no_delta, options, seed);
+                let cached = search(&start, clashes, no_delta, cached, seed);
+                let case = format!("seed {seed}, {order:?}, noise {noise}");
+                assert_eq!(cached.trail, plain.trail, "{case}");
+                assert_eq!(cached.schedule, plain.schedule, "{case}");
+            }
+        }
+    }
+}
+
+#[test]
+fn stale_scores_are_checked_before_moving() {
+    // Odd activities in neighbouring time slots clash too, so a swap
+    // changes the scores of others in slots it didn't touch
+    let neighbours = |s: &Schedule<usize>| -> usize {
+        let odd: Vec<usize> = (0..s.dimensions().1)
+            .map(|t| {
+                let column = s.column(t).unwrap();
+                column.iter().flatten().filter(|&&a| a % 2 == 1).count()
+            })
+            .collect();
+        let across: usize = odd.windows(2).map(|w| w[0] * w[1] * 10).sum();
+        clashes(s) + across
+    };
+    for seed in 0..3 {
+        let mut schedule = Schedule::new(3, 4, 0..14usize);
+        let mut steps = schedule
+            .improve(neighbours)
+            .cache_swap_costs()
+            .scan_order(ScanOrder::Shuffled)
+            .with_rng_seed(seed)
+            .steps();
+        // Every move made is a real improvement, scored truthfully
+        let mut last = usize::MAX;
+        while let Some(best) = steps.next() {
+            assert_eq!(best, neighbours(steps.schedule()), "seed {seed}");
+            assert!(best < last, "seed {seed}: {best} after {last}");
+            last = best;
+        }
+    }
+}
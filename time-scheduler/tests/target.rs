@@ -0,0 +1,49 @@
+use common::lateness;
+use time_scheduler::Schedule;
+
+mod common;
+
+#[test]
+fn target_scheduled_lets_a_full_grid_shrink() {
+    let penalty = |s: &Schedule<usize>| (s.distance_from_target(2), lateness(s));
+
+    // With no empty unscheduled entries to trade with, nothing can leave
+    let mut fixed = Schedule::new(2, 3, 0..6);
+    fixed.improve(penalty).run();
+    assert_eq!(fixed.distance_from_target(2), 4);
+
+    let mut thinned = Schedule::new(2, 3, 0..6);
+    thinned.improve(penalty).target_scheduled(2).run();
+    assert_eq!(thinned.distance_from_target(2), 0);
+    assert_eq!(thinned.get_unscheduled_activities().count(), 4);
+}
+
+#[test]
+fn the_penalty_decides_which_activities_stay() {
+    // Missing a large activity costs more, so the three largest stay
+    let penalty = |s: &Schedule<usize>| {
+        let missed: usize = s.get_unscheduled_activities().sum();
+        (s.distance_from_target(3), missed)
+    };
+    let mut schedule = Schedule::new(2, 3, 0..6);
+    schedule
+        .improve(penalty)
+        .target_scheduled(3)
+        .with_rng_seed(1)
+        .run();
+    let mut kept: Vec<usize> = schedule.slots().iter().flatten().copied().collect();
+    kept.sort_unstable();
+    assert_eq!(kept, [3, 4, 5]);
+}
+
+#[test]
+fn a_target_above_the_activities_leaves_the_grid_alone() {
+    let start = Schedule::new(2, 2, 0..3);
+    let mut schedule = start.clone();
+    schedule
+        .improve(|s: &Schedule<usize>| s.distance_from_target(10))
+        .target_scheduled(10)
+        .run();
+    assert_eq!(schedule.slots(), start.slots());
+    assert_eq!(schedule.distance_from_target(10), 7);
+}
@@ -0,0 +1,110 @@
+use time_scheduler::{Constraints, InstanceError, InstanceWarning, SchedulingInstance};
+
+fn instance(nplaces: usize, ntimes: usize, nactivities: usize) -> SchedulingInstance<usize> {
+    SchedulingInstance {
+        id: "talks".to_string(),
+        nplaces,
+        ntimes,
+        activities: (0..nactivities).collect(),
+        buffer_times: 0,
+        constraints: Constraints::default(),
+        place_labels: None,
+        time_labels: None,
+    }
+}
+
+#[test]
+fn each_degenerate_instance_has_its_error() {
+    assert_eq!(instance(3, 4, 10).validate(), Ok(()));
+    assert_eq!(instance(0, 4, 10).validate(), Err(InstanceError::NoPlaces));
+    assert_eq!(instance(3, 0, 10).validate(), Err(InstanceError::NoTimes));
+    assert_eq!(
+        instance(3, 4, 0).validate(),
+        Err(InstanceError::NoActivities)
+    );
+    assert_eq!(
+        instance(usize::MAX, 2, 1).validate(),
+        Err(InstanceError::TooLarge {
+            nplaces: usize::MAX,
+            ntimes: 2
+        })
+    );
+
+    let mut outside = instance(3, 4, 10);
+    outside.constraints.locked.push((3, 0));
+    assert_eq!(
+        outside.validate(),
+        Err(InstanceError::ConstraintOutOfBounds { place: 3, time: 0 })
+    );
+
+    // No places is reported before anything else
+    assert_eq!(instance(0, 0, 0).validate(), Err(InstanceError::NoPlaces));
+}
+
+#[test]
+fn labels_must_match_the_grid() {
+    let labels = |n: usize| Some((0..n).map(|i| format!("label {i}")).collect());
+    let labeled = SchedulingInstance {
+        place_labels: labels(3),
+        time_labels: labels(4),
+        ..instance(3, 4, 10)
+    };
+    assert_eq!(labeled.validate(), Ok(()));
+
+    let places = SchedulingInstance {
+        place_labels: labels(2),
+        ..instance(3, 4, 10)
+    };
+    assert_eq!(
+        places.validate(),
+        Err(InstanceError::PlaceLabels {
+            len: 2,
+            expected: 3
+        })
+    );
+
+    let times = SchedulingInstance {
+        time_labels: labels(5),
+        ..instance(3, 4, 10)
+    };
+    assert_eq!(
+        times.validate(),
+        Err(InstanceError::TimeLabels {
+            len: 5,
+            expected: 4
+        })
+    );
+
+    // Mislabeled schedules are rejected when read, too
+    let json = r#"{"slots": [[1, null]], "unscheduled": [], "time_labels": ["9:00"]}"#;
+    assert!(serde_json::from_str::<time_scheduler::Schedule<u32>>(json).is_err());
+}
+
+#[test]
+fn overfull_instances_warn_but_validate() {
+    assert!(instance(3, 4, 12).warnings().is_empty());
+
+    let mut overfull = instance(3, 4, 12);
+    overfull.constraints.forbidden.push((0, 0));
+    overfull.validate().unwrap();
+    assert_eq!(
+        overfull.warnings(),
+        [InstanceWarning::Overfull {
+            activities: 12,
+            slots: 11
+        }]
+    );
+
+    // Buffer time slots hold overflow, not scheduled activities
+    let buffered = SchedulingInstance {
+        buffer_times: 1,
+        ..instance(3, 4, 10)
+    };
+    assert_eq!(
+        buffered.warnings(),
+        [InstanceWarning::Overfull {
+            activities: 10,
+            slots: 9
+        }]
+    );
+}
@@ -1,10 +1,9 @@
 use std::fs::File;
 use std::io::Write;
-use std::iter::from_fn as iter_fn;
 use std::str::FromStr;
 
 use clap::Parser;
-use fastrand::{f64 as random_f64, usize as random_usize};
+use fastrand::Rng;
 use serde::{Deserialize, Serialize};
 use time_scheduler::SchedulingInstance;
 
@@ -60,6 +59,35 @@ impl FromStr for Distribution {
     }
 }
 
+/// Inclusive range of sizes written as `lo:hi`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SizeRange {
+    lo: usize,
+    hi: usize,
+}
+
+impl FromStr for SizeRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (lo, hi) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Size range must be lo:hi, got {s}"))?;
+        let lo = lo.parse().map_err(|_| "Invalid lower bound")?;
+        let hi = hi.parse().map_err(|_| "Invalid upper bound")?;
+        if lo > hi {
+            return Err(format!("Empty size range {lo}:{hi}"));
+        }
+        Ok(SizeRange { lo, hi })
+    }
+}
+
+impl SizeRange {
+    fn sample(&self, rng: &mut Rng) -> usize {
+        rng.usize(self.lo..=self.hi)
+    }
+}
+
 #[derive(Parser)]
 struct Args {
     #[arg(short, long, help = "Random seed for reproducibility")]
@@ -91,6 +119,23 @@ struct Args {
         help = "Topic distribution: uniform, zipf:exp, pareto:shape:scale, geometric:p"
     )]
     topic_dist: Distribution,
+    #[arg(
+        long,
+        help = "Sample this many topics per instance, weighted by the topic distribution, and use only those"
+    )]
+    active_topics: Option<usize>,
+    #[arg(long, help = "Randomize each instance's number of places within lo:hi")]
+    vary_places: Option<SizeRange>,
+    #[arg(
+        long,
+        help = "Randomize each instance's number of time slots within lo:hi"
+    )]
+    vary_times: Option<SizeRange>,
+    #[arg(
+        long,
+        help = "Randomize each instance's number of activities within lo:hi"
+    )]
+    vary_activities: Option<SizeRange>,
     #[arg(name = "places", help = "Number of places")]
     nplaces: usize,
     #[arg(name = "timeslots", help = "Number of time slots")]
@@ -106,23 +151,25 @@ pub struct Activity {
 }
 
 impl Distribution {
-    fn sample(&self, min: usize, max: usize) -> usize {
+    fn sample(&self, rng: &mut Rng, min: usize, max: usize) -> usize {
         match self {
-            Distribution::Uniform => random_usize(min..=max),
-            Distribution::Zipf { exponent } => Self::sample_zipf(min, max, *exponent),
-            Distribution::Pareto { shape, scale } => Self::sample_pareto(min, max, *shape, *scale),
-            Distribution::Geometric { p } => Self::sample_geometric(min, max, *p),
+            Distribution::Uniform => rng.usize(min..=max),
+            Distribution::Zipf { exponent } => Self::sample_zipf(rng, min, max, *exponent),
+            Distribution::Pareto { shape, scale } => {
+                Self::sample_pareto(rng, min, max, *shape, *scale)
+            }
+            Distribution::Geometric { p } => Self::sample_geometric(rng, min, max, *p),
         }
     }
 
-    fn sample_zipf(min: usize, max: usize, exponent: f64) -> usize {
+    fn sample_zipf(rng: &mut Rng, min: usize, max: usize, exponent: f64) -> usize {
         let n = max - min + 1;
         let mut sum = 0.0;
         for i in 1..=n {
             sum += 1.0 / (i as f64).powf(exponent);
         }
 
-        let u = random_f64();
+        let u = rng.f64();
         let target = u * sum;
         let mut cumulative = 0.0;
 
@@ -135,97 +182,162 @@ impl Distribution {
         max
     }
 
-    fn sample_pareto(min: usize, max: usize, shape: f64, scale: f64) -> usize {
-        let u = random_f64();
+    fn sample_pareto(rng: &mut Rng, min: usize, max: usize, shape: f64, scale: f64) -> usize {
+        let u = rng.f64();
         let value = scale * ((1.0 - u).powf(-1.0 / shape));
         let normalized = ((value - scale) / (10.0 * scale)).clamp(0.0, 1.0);
         min + ((max - min) as f64 * (1.0 - normalized)) as usize
     }
 
-    fn sample_geometric(min: usize, max: usize, p: f64) -> usize {
-        let u = random_f64();
+    fn sample_geometric(rng: &mut Rng, min: usize, max: usize, p: f64) -> usize {
+        let u = rng.f64();
         let value = ((1.0 - u).ln() / p.ln()).floor() as usize;
         let range = max - min + 1;
         min + (value % range)
     }
 }
 
+/// Draws from a distribution before giving up on rejection sampling.
+const MAX_REJECTIONS: usize = 1000;
+
+/// Sample `k` distinct topics from `1..=ntopics`, weighted by `topic_dist`:
+/// repeated draws with duplicates rejected. Topics the distribution almost
+/// never produces are filled in uniformly if the draws run out.
+fn sample_active_topics(
+    rng: &mut Rng,
+    k: usize,
+    ntopics: usize,
+    topic_dist: &Distribution,
+) -> Vec<usize> {
+    let mut active = Vec::with_capacity(k);
+    let mut draws = 0;
+    while active.len() < k && draws < MAX_REJECTIONS * k {
+        let topic = topic_dist.sample(rng, 1, ntopics);
+        if !active.contains(&topic) {
+            active.push(topic);
+        }
+        draws += 1;
+    }
+    while active.len() < k {
+        let topic = rng.usize(1..=ntopics);
+        if !active.contains(&topic) {
+            active.push(topic);
+        }
+    }
+    active
+}
+
 impl Activity {
+    /// Draw a random activity. With `active_topics`, topics outside the
+    /// list are redrawn, so the topic distribution is restricted to it.
     fn random_with_distributions(
+        rng: &mut Rng,
         min_priority: usize,
         max_priority: usize,
         ntopics: usize,
         priority_dist: &Distribution,
         topic_dist: &Distribution,
+        active_topics: Option<&[usize]>,
     ) -> Self {
-        let priority = priority_dist.sample(min_priority, max_priority);
-        let topic = topic_dist.sample(1, ntopics);
+        let priority = priority_dist.sample(rng, min_priority, max_priority);
+        let topic = match active_topics {
+            None => topic_dist.sample(rng, 1, ntopics),
+            Some(active) => (0..MAX_REJECTIONS)
+                .map(|_| topic_dist.sample(rng, 1, ntopics))
+                .find(|topic| active.contains(topic))
+                .unwrap_or_else(|| active[rng.usize(..active.len())]),
+        };
         Self { priority, topic }
     }
-
-    fn randoms_with_distributions(
-        mut n: usize,
-        min_priority: usize,
-        max_priority: usize,
-        ntopics: usize,
-        priority_dist: Distribution,
-        topic_dist: Distribution,
-    ) -> impl Iterator<Item = Self> {
-        iter_fn(move || {
-            if n > 0 {
-                n -= 1;
-                Some(Self::random_with_distributions(
-                    min_priority,
-                    max_priority,
-                    ntopics,
-                    &priority_dist,
-                    &topic_dist,
-                ))
-            } else {
-                None
-            }
-        })
-    }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = Args::parse();
-
-    if let Some(seed) = args.seed {
-        fastrand::seed(seed);
+impl Args {
+    /// Overwrite the distribution settings with those of any preset.
+    fn apply_preset(&mut self) {
+        if self.unconference {
+            self.min_priority = 1;
+            self.max_priority = 50;
+            self.ntopics = 8;
+            self.priority_dist = Distribution::Pareto {
+                shape: 1.8,
+                scale: 1.0,
+            };
+            self.topic_dist = Distribution::Zipf { exponent: 1.2 };
+        }
     }
+}
 
-    // Apply unconference preset if requested
-    if args.unconference {
-        args.min_priority = 1;
-        args.max_priority = 50;
-        args.ntopics = 8;
-        args.priority_dist = Distribution::Pareto {
-            shape: 1.8,
-            scale: 1.0,
-        };
-        args.topic_dist = Distribution::Zipf { exponent: 1.2 };
-    }
+/// Generate the instances described by `args`. With a seed, the output is
+/// fully determined by the arguments.
+fn generate_instances(args: &Args) -> Vec<SchedulingInstance<Activity>> {
+    let mut rng = match args.seed {
+        Some(seed) => Rng::with_seed(seed),
+        None => Rng::new(),
+    };
 
     let mut instances = Vec::new();
 
+    let varied =
+        args.vary_places.is_some() || args.vary_times.is_some() || args.vary_activities.is_some();
+
     for i in 0..args.count {
+        let nplaces = args
+            .vary_places
+            .map_or(args.nplaces, |r| r.sample(&mut rng));
+        let ntimes = args.vary_times.map_or(args.ntimes, |r| r.sample(&mut rng));
+        let nactivities = args
+            .vary_activities
+            .map_or(args.nactivities, |r| r.sample(&mut rng));
+        let id = if varied {
+            format!("instance_{i:03}_{nplaces}x{ntimes}x{nactivities}")
+        } else {
+            format!("instance_{i:03}")
+        };
+        let active_topics = args
+            .active_topics
+            .map(|k| sample_active_topics(&mut rng, k, args.ntopics, &args.topic_dist));
         let instance = SchedulingInstance::<Activity> {
-            id: format!("instance_{i:03}"),
-            nplaces: args.nplaces,
-            ntimes: args.ntimes,
-            activities: Activity::randoms_with_distributions(
-                args.nactivities,
-                args.min_priority,
-                args.max_priority,
-                args.ntopics,
-                args.priority_dist.clone(),
-                args.topic_dist.clone(),
-            )
-            .collect(),
+            id,
+            nplaces,
+            ntimes,
+            activities: (0..nactivities)
+                .map(|_| {
+                    Activity::random_with_distributions(
+                        &mut rng,
+                        args.min_priority,
+                        args.max_priority,
+                        args.ntopics,
+                        &args.priority_dist,
+                        &args.topic_dist,
+                        active_topics.as_deref(),
+                    )
+                })
+                .collect(),
+            buffer_times: 0,
+            constraints: Default::default(),
+            place_labels: None,
+            time_labels: None,
         };
         instances.push(instance);
     }
+    instances
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = Args::parse();
+    args.apply_preset();
+    if let Some(k) = args.active_topics {
+        if k == 0 || k > args.ntopics {
+            return Err(format!("--active-topics must be within 1..={}", args.ntopics).into());
+        }
+    }
+
+    let instances = generate_instances(&args);
+    for instance in &instances {
+        instance
+            .validate()
+            .map_err(|e| format!("instance {}: {e}", instance.id))?;
+    }
 
     let json = serde_json::to_string_pretty(&instances)?;
     let mut file = File::create(&args.output)?;
@@ -237,3 +349,120 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn size_range_parses() {
+        assert_eq!("2:5".parse(), Ok(SizeRange { lo: 2, hi: 5 }));
+        assert!("5:2".parse::<SizeRange>().is_err());
+        assert!("5".parse::<SizeRange>().is_err());
+    }
+
+    #[test]
+    fn size_range_samples_span_range() {
+        let mut rng = Rng::with_seed(7);
+        let range = SizeRange { lo: 3, hi: 6 };
+        let samples: Vec<usize> = (0..200).map(|_| range.sample(&mut rng)).collect();
+        assert_eq!(samples.iter().min(), Some(&3));
+        assert_eq!(samples.iter().max(), Some(&6));
+    }
+
+    #[test]
+    fn active_topics_limit_distinct_topics() {
+        let argv = [
+            "ts-gen",
+            "--seed",
+            "5",
+            "--unconference",
+            "--active-topics",
+            "3",
+            "-c",
+            "10",
+            "-o",
+            "unused.json",
+            "3",
+            "7",
+            "25",
+        ];
+        let mut args = Args::try_parse_from(argv).unwrap();
+        args.apply_preset();
+        let mut all_topics = HashSet::new();
+        for instance in generate_instances(&args) {
+            let topics: HashSet<usize> = instance.activities.iter().map(|a| a.topic).collect();
+            assert!(topics.len() <= 3, "{}: {topics:?}", instance.id);
+            all_topics.extend(topics);
+        }
+        // Different instances cover different themes
+        assert!(all_topics.len() > 3);
+    }
+
+    #[test]
+    fn active_topics_favor_likely_topics() {
+        let mut rng = Rng::with_seed(13);
+        let zipf = Distribution::Zipf { exponent: 1.5 };
+        let picks: Vec<usize> = (0..200)
+            .flat_map(|_| sample_active_topics(&mut rng, 2, 8, &zipf))
+            .collect();
+        // Zipf ranks high values first, so topic 8 is the most likely
+        let count = |topic| picks.iter().filter(|&&t| t == topic).count();
+        assert!(count(8) > count(1));
+        assert!((0..200).all(|i| picks[2 * i] != picks[2 * i + 1]));
+    }
+
+    /// Golden output of the unconference preset. Regenerate it, after
+    /// checking that a change in sampling is intended, with
+    /// `cargo run --bin ts-gen -- --seed 42 --unconference -c 3
+    /// -o ts-gen/tests/golden/unconference-seed-42.json 3 7 25`.
+    const UNCONFERENCE_GOLDEN: &str = include_str!("../tests/golden/unconference-seed-42.json");
+
+    #[test]
+    fn unconference_preset_matches_golden_snapshot() {
+        let argv = [
+            "ts-gen",
+            "--seed",
+            "42",
+            "--unconference",
+            "-c",
+            "3",
+            "-o",
+            "unused.json",
+            "3",
+            "7",
+            "25",
+        ];
+        let mut args = Args::try_parse_from(argv).unwrap();
+        args.apply_preset();
+        let json = serde_json::to_string_pretty(&generate_instances(&args)).unwrap();
+        assert!(
+            json == UNCONFERENCE_GOLDEN,
+            "unconference output drifted from tests/golden/unconference-seed-42.json"
+        );
+    }
+
+    #[test]
+    fn seeded_runs_do_not_share_generator_state() {
+        let argv = [
+            "ts-gen",
+            "--seed",
+            "3",
+            "-c",
+            "2",
+            "-o",
+            "unused.json",
+            "2",
+            "4",
+            "9",
+        ];
+        let args = Args::try_parse_from(argv).unwrap();
+        let first = serde_json::to_string(&generate_instances(&args)).unwrap();
+        // Drawing from the global generator in between changes nothing
+        fastrand::u64(..);
+        let second = serde_json::to_string(&generate_instances(&args)).unwrap();
+        assert_eq!(first, second);
+    }
+}